@@ -45,5 +45,32 @@ fn bench_browser_detection(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_extraction_methods, bench_browser_detection);
+// DevTools ポーリング: 接続が再利用されているかも表示する
+#[cfg(all(feature = "devtools", target_os = "windows"))]
+fn bench_devtools_polling(c: &mut Criterion) {
+    use browser_info::{ChromeDevToolsExtractor, devtools_connection_stats};
+
+    let rt = Runtime::new().unwrap();
+    c.bench_function("devtools_polling", |b| {
+        b.iter(|| rt.block_on(async { black_box(ChromeDevToolsExtractor::is_available().await) }))
+    });
+
+    let stats = devtools_connection_stats();
+    println!(
+        "DevTools connections: {} requests, {} clients built, {} reused",
+        stats.requests,
+        stats.clients_built,
+        stats.reused()
+    );
+}
+
+#[cfg(not(all(feature = "devtools", target_os = "windows")))]
+fn bench_devtools_polling(_c: &mut Criterion) {}
+
+criterion_group!(
+    benches,
+    bench_extraction_methods,
+    bench_browser_detection,
+    bench_devtools_polling
+);
 criterion_main!(benches);
@@ -0,0 +1,45 @@
+//! Append every browsing stretch to a JSONL file
+//!
+//! Runs the watcher and turns its events into [`Visit`]s: a visit ends when the
+//! URL changes, the browser loses focus or tracking is paused.
+//!
+//! ```sh
+//! cargo run --example activity_logger --features storage -- activity.jsonl
+//! ```
+
+use browser_info::BrowserInfo;
+use browser_info::storage::{JsonlStore, Visit, VisitStore};
+use browser_info::watcher::{BrowserWatcher, WatchEventKind};
+use std::time::{Duration, SystemTime};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "activity.jsonl".to_string());
+    let mut store = JsonlStore::open(&path);
+    println!("📝 Logging browser activity to {path} (Ctrl+C to stop)");
+
+    let watcher = BrowserWatcher::spawn(Duration::from_secs(1), |_| {});
+    let events = watcher.subscribe();
+
+    // Page in front and since when
+    let mut current: Option<(BrowserInfo, SystemTime)> = None;
+    while let Some(event) = events.recv() {
+        // Every event ends the running visit; only Changed starts a new one
+        if let Some((info, start)) = current.take() {
+            let duration = event.timestamp.duration_since(start).unwrap_or_default();
+            let visit = Visit::from_info(&info, start, duration);
+            println!("💾 {} ({:?})", visit.domain, duration);
+            store.append(&visit)?;
+        }
+
+        match event.kind {
+            WatchEventKind::Changed(info) if !info.url.is_empty() => {
+                current = Some((info, event.timestamp));
+            }
+            WatchEventKind::Error(message) => eprintln!("⚠️  {message}"),
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -159,6 +159,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ExtractionMethod::Auto,
         ExtractionMethod::DevTools,
         ExtractionMethod::PowerShell,
+        ExtractionMethod::Race,
+        ExtractionMethod::Passive,
     ] {
         match get_browser_info_with_method(method).await {
             Ok(info) => println!(
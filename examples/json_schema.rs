@@ -0,0 +1,13 @@
+//! Print the JSON Schema of `BrowserInfo`
+//!
+//! Regenerate the committed schema after changing `BrowserInfo`:
+//!
+//! ```sh
+//! cargo run --example json_schema --features schemars > schema/browser_info.schema.json
+//! ```
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let schema = browser_info::BrowserInfo::json_schema();
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Save the page in front to a Markdown reading list
+//!
+//! On Windows the clipper registers the global hotkey Ctrl+Alt+S, so it works
+//! while the browser keeps the focus. Elsewhere press Enter in this terminal,
+//! then switch to the browser within three seconds.
+//!
+//! ```sh
+//! cargo run --example page_clipper -- clippings.md
+//! ```
+
+use browser_info::{BrowserInfo, get_active_browser_info};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "clippings.md".to_string());
+    println!("📎 Clipping pages to {path} (Ctrl+C to stop)");
+
+    wait_for_hotkey(|| match get_active_browser_info() {
+        Ok(info) => {
+            if let Err(e) = clip(Path::new(&path), &info) {
+                eprintln!("❌ Cannot write {path}: {e}");
+            }
+        }
+        Err(e) => eprintln!("❌ No page to clip: {e}"),
+    })
+}
+
+fn clip(path: &Path, info: &BrowserInfo) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let title = if info.title.is_empty() {
+        &info.url
+    } else {
+        &info.title
+    };
+    writeln!(
+        file,
+        "- [{}]({}) — {}",
+        title.replace(['[', ']'], ""),
+        info.url,
+        humantime::format_rfc3339_seconds(std::time::SystemTime::now())
+    )?;
+    println!("✅ {}", info.url);
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn wait_for_hotkey(mut on_hotkey: impl FnMut()) -> Result<(), Box<dyn std::error::Error>> {
+    use winapi::um::winuser::{GetMessageW, MOD_ALT, MOD_CONTROL, MSG, RegisterHotKey, WM_HOTKEY};
+
+    let registered = unsafe {
+        RegisterHotKey(
+            std::ptr::null_mut(),
+            1,
+            (MOD_CONTROL | MOD_ALT) as u32,
+            u32::from(b'S'),
+        )
+    };
+    if registered == 0 {
+        return Err("Ctrl+Alt+S is already taken by another application".into());
+    }
+    println!("⌨️  Press Ctrl+Alt+S in the browser");
+
+    let mut message: MSG = Default::default();
+    // Thread hotkeys arrive as WM_HOTKEY on this thread's queue
+    while unsafe { GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) } > 0 {
+        if message.message == WM_HOTKEY {
+            on_hotkey();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn wait_for_hotkey(mut on_hotkey: impl FnMut()) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::BufRead;
+
+    println!("⌨️  Press Enter, then bring the browser to the front");
+    for line in std::io::stdin().lock().lines() {
+        line?;
+        std::thread::sleep(std::time::Duration::from_secs(3));
+        on_hotkey();
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use browser_info::ExtractionConfig;
+use browser_info::verify::verify_extraction;
+
+fn main() {
+    println!("🧪 Browser Info - URL extraction self-test");
+    println!("==========================================");
+    println!(
+        "A test page will open in your default browser. Leave it in front until the report appears.\n"
+    );
+
+    let report = match verify_extraction(&ExtractionConfig::default()) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("❌ Self-test could not run: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    println!("Browser:  {:?}", report.browser_type);
+    println!("Expected: {}\n", report.expected_url);
+    for method in &report.methods {
+        let mark = if method.exact { "✅" } else { "❌" };
+        println!(
+            "{mark} {:?} ({:?}): {:?} → {}",
+            method.source,
+            method.elapsed,
+            method.outcome,
+            method.extracted.as_deref().unwrap_or("-"),
+        );
+    }
+
+    if !report.passed() {
+        std::process::exit(1);
+    }
+}
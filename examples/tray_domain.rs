@@ -0,0 +1,113 @@
+//! Show the domain of the page in front as a tray icon tooltip
+//!
+//! On Windows the example adds a notification-area icon whose tooltip follows
+//! the active page. Other platforms have no tray API in this crate's
+//! dependencies, so the domain goes to the terminal title instead.
+//!
+//! ```sh
+//! cargo run --example tray_domain
+//! ```
+
+use browser_info::url_extraction::domain_of;
+use browser_info::watcher::{BrowserWatcher, WatchEventKind};
+use std::time::Duration;
+
+fn main() {
+    let watcher = BrowserWatcher::spawn(Duration::from_secs(1), |_| {});
+    let mut tray = Tray::new();
+    println!("🗂️  Showing the current domain (Ctrl+C to stop)");
+
+    for event in watcher.subscribe() {
+        let label = match event.kind {
+            WatchEventKind::Changed(info) => {
+                domain_of(&info.url).unwrap_or_else(|| format!("{:?}", info.browser_type))
+            }
+            WatchEventKind::FocusLost => "No browser".to_string(),
+            WatchEventKind::Paused { .. } => "Paused".to_string(),
+            WatchEventKind::SessionLocked => "Locked".to_string(),
+            _ => continue,
+        };
+        tray.show(&label);
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct Tray {
+    data: winapi::um::shellapi::NOTIFYICONDATAW,
+}
+
+#[cfg(target_os = "windows")]
+impl Tray {
+    fn new() -> Self {
+        use winapi::um::shellapi::{
+            NIF_ICON, NIF_TIP, NIM_ADD, NOTIFYICONDATAW, Shell_NotifyIconW,
+        };
+        use winapi::um::winuser::{CreateWindowExW, HWND_MESSAGE, IDI_APPLICATION, LoadIconW};
+
+        // The icon needs an owner window; a message-only STATIC window will do
+        let class: Vec<u16> = "STATIC\0".encode_utf16().collect();
+        let window = unsafe {
+            CreateWindowExW(
+                0,
+                class.as_ptr(),
+                std::ptr::null(),
+                0,
+                0,
+                0,
+                0,
+                0,
+                HWND_MESSAGE,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+
+        let mut data: NOTIFYICONDATAW = Default::default();
+        data.cbSize = std::mem::size_of::<NOTIFYICONDATAW>() as u32;
+        data.hWnd = window;
+        data.uID = 1;
+        data.uFlags = NIF_ICON | NIF_TIP;
+        data.hIcon = unsafe { LoadIconW(std::ptr::null_mut(), IDI_APPLICATION) };
+        unsafe { Shell_NotifyIconW(NIM_ADD, &mut data) };
+        Self { data }
+    }
+
+    fn show(&mut self, label: &str) {
+        use winapi::um::shellapi::{NIM_MODIFY, Shell_NotifyIconW};
+
+        // szTip holds 127 characters and the terminating NUL
+        let tip: Vec<u16> = label.encode_utf16().take(127).chain([0]).collect();
+        self.data.szTip = [0; 128];
+        self.data.szTip[..tip.len()].copy_from_slice(&tip);
+        unsafe { Shell_NotifyIconW(NIM_MODIFY, &mut self.data) };
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for Tray {
+    fn drop(&mut self) {
+        use winapi::um::shellapi::{NIM_DELETE, Shell_NotifyIconW};
+
+        unsafe { Shell_NotifyIconW(NIM_DELETE, &mut self.data) };
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+struct Tray;
+
+#[cfg(not(target_os = "windows"))]
+impl Tray {
+    fn new() -> Self {
+        Self
+    }
+
+    fn show(&mut self, label: &str) {
+        use std::io::Write;
+
+        // OSC 0: set the terminal window title
+        print!("\x1b]0;🌐 {label}\x07");
+        let _ = std::io::stdout().flush();
+        println!("🌐 {label}");
+    }
+}
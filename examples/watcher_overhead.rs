@@ -0,0 +1,107 @@
+//! CPU time and wakeups of an idle watcher, default options vs `low_power()`
+//!
+//! Keeps a browser window in front whose title never changes (a fixed window,
+//! so it also runs on headless machines) and reports what the watcher cost
+//! the process over the run. This is the figure behind the "Background agents"
+//! table in the README.
+//!
+//! ```sh
+//! cargo run --release --example watcher_overhead -- default 600
+//! cargo run --release --example watcher_overhead -- low-power 600
+//! ```
+//!
+//! CPU time and context switches are read from `/proc/self`, so the numbers
+//! are printed on Linux only; elsewhere measure the process with
+//! `powercfg /srumutil` (Windows) or `powermetrics --samplers tasks` (macOS).
+
+use browser_info::watcher::{BrowserWatcher, WatchOptions};
+use browser_info::window_provider::FixedWindow;
+use browser_info::{ExtractionConfig, WindowHandleInfo};
+use std::time::{Duration, Instant};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mode = args.next().unwrap_or_else(|| "default".to_string());
+    let seconds: u64 = args.next().and_then(|s| s.parse().ok()).unwrap_or(60);
+
+    let chrome = FixedWindow::new(WindowHandleInfo {
+        title: "Rust Programming Language - Google Chrome".to_string(),
+        app_name: "Google Chrome".to_string(),
+        process_path: "/opt/google/chrome/chrome".into(),
+        ..WindowHandleInfo::default()
+    });
+    // low_power() の設定にウィンドウだけ差し替える
+    let options = match mode.as_str() {
+        "low-power" => WatchOptions::low_power().config(
+            ExtractionConfig::default()
+                .allow_keyboard_simulation(false)
+                .window_provider(chrome),
+        ),
+        _ => WatchOptions::new().config(ExtractionConfig::default().window_provider(chrome)),
+    };
+
+    println!("⏱️  {mode} watcher for {seconds} s");
+    let before = usage();
+    let started = Instant::now();
+    let watcher = BrowserWatcher::spawn_with_options(options, |_| {});
+    std::thread::sleep(Duration::from_secs(seconds));
+    // Before the watcher's threads are joined and leave /proc/self/task
+    let after = usage();
+    let elapsed = started.elapsed();
+    drop(watcher);
+
+    match (before, after) {
+        (Some(before), Some(after)) => {
+            let cpu = after.cpu - before.cpu;
+            let minutes = elapsed.as_secs_f64() / 60.0;
+            println!(
+                "{mode}: CPU time {:?} ({:.4}% of one core)",
+                cpu,
+                cpu.as_secs_f64() / elapsed.as_secs_f64() * 100.0
+            );
+            println!(
+                "{mode}: {:.1} wakeups/min",
+                (after.switches - before.switches) as f64 / minutes
+            );
+        }
+        _ => println!("No /proc/self here; measure the process with the OS tools instead"),
+    }
+}
+
+struct Usage {
+    cpu: Duration,
+    /// Voluntary context switches, i.e. times the process slept and woke up
+    switches: u64,
+}
+
+#[cfg(target_os = "linux")]
+fn usage() -> Option<Usage> {
+    // utime, stime and the same for waited-for children (loginctl, gsettings),
+    // in clock ticks (USER_HZ, 100 on Linux)
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let mut ticks = 0;
+    for index in 11..=14 {
+        ticks += fields.get(index)?.parse::<u64>().ok()?;
+    }
+
+    // Summed over the threads, which is where the watcher runs
+    let mut switches = 0;
+    for task in std::fs::read_dir("/proc/self/task").ok()?.flatten() {
+        let status = std::fs::read_to_string(task.path().join("status")).ok()?;
+        switches += status
+            .lines()
+            .find_map(|line| line.strip_prefix("voluntary_ctxt_switches:"))
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .unwrap_or(0);
+    }
+    Some(Usage {
+        cpu: Duration::from_millis(ticks * 10),
+        switches,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn usage() -> Option<Usage> {
+    None
+}
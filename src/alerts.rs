@@ -0,0 +1,215 @@
+//! Tab-count and memory alerts ("you have 300 tabs open" nudges).
+//!
+//! A [`ResourceMonitor`] checks the open tabs ([`get_all_tabs`]) and the
+//! browsers' memory ([`browser_memory`]) on its own thread and reports when
+//! a threshold is crossed, in either direction. Each crossing is reported
+//! once; staying above a threshold does not repeat the alert.
+//!
+//! ```rust,no_run
+//! use browser_info::alerts::{AlertThresholds, ResourceAlert, ResourceMonitor};
+//!
+//! let thresholds = AlertThresholds::new()
+//!     .max_tabs(300)
+//!     .max_memory_bytes(8 * 1024 * 1024 * 1024);
+//! let monitor = ResourceMonitor::spawn(thresholds, |alert| match alert {
+//!     ResourceAlert::TooManyTabs { count, .. } => println!("🗂️ {count} tabs open"),
+//!     other => println!("{other:?}"),
+//! });
+//! ```
+//!
+//! Tab counts need a tab backend (DevTools on Windows, AppleScript on macOS);
+//! where none is available only memory is checked.
+
+use crate::resources::{BrowserMemory, browser_memory};
+use crate::{BrowserType, get_all_tabs};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// A threshold crossing
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ResourceAlert {
+    /// More tabs are open, across all browsers, than `limit`
+    TooManyTabs { count: usize, limit: usize },
+    /// The tab count is back at or below `limit`
+    TabsWithinLimit { count: usize, limit: usize },
+    /// A browser uses more than `limit` bytes of resident memory
+    HighMemory {
+        browser_type: BrowserType,
+        resident_bytes: u64,
+        limit: u64,
+    },
+    /// The browser's memory is back at or below `limit` (or it was closed)
+    MemoryWithinLimit {
+        browser_type: BrowserType,
+        resident_bytes: u64,
+        limit: u64,
+    },
+}
+
+/// What the monitor watches, and how often
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertThresholds {
+    max_tabs: Option<usize>,
+    max_memory_bytes: Option<u64>,
+    interval: Duration,
+}
+
+impl Default for AlertThresholds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlertThresholds {
+    /// No thresholds, checked once a minute
+    pub fn new() -> Self {
+        Self {
+            max_tabs: None,
+            max_memory_bytes: None,
+            interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Alert when more than `max_tabs` tabs are open across all browsers
+    pub fn max_tabs(mut self, max_tabs: usize) -> Self {
+        self.max_tabs = Some(max_tabs);
+        self
+    }
+
+    /// Alert when one browser uses more than this much resident memory
+    pub fn max_memory_bytes(mut self, max_memory_bytes: u64) -> Self {
+        self.max_memory_bytes = Some(max_memory_bytes);
+        self
+    }
+
+    /// Time between checks (enumerating tabs is not free; keep it in seconds)
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// Background checker; it stops when dropped
+#[derive(Debug)]
+pub struct ResourceMonitor {
+    stop: Option<SyncSender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ResourceMonitor {
+    pub fn spawn<F>(thresholds: AlertThresholds, mut on_alert: F) -> Self
+    where
+        F: FnMut(ResourceAlert) + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::sync_channel::<()>(0);
+        let thread = std::thread::Builder::new()
+            .name("browser-info-alerts".to_string())
+            .spawn(move || {
+                let mut state = AlertState::default();
+                loop {
+                    for alert in state.check(&thresholds) {
+                        on_alert(alert);
+                    }
+                    match stopped.recv_timeout(thresholds.interval) {
+                        Err(RecvTimeoutError::Timeout) => {}
+                        _ => return,
+                    }
+                }
+            })
+            .expect("failed to spawn alerts thread");
+
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for ResourceMonitor {
+    fn drop(&mut self) {
+        // 送信側を閉じると監視スレッドの待機が終わる
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Which thresholds are currently exceeded
+#[derive(Debug, Default)]
+struct AlertState {
+    too_many_tabs: bool,
+    /// Browsers above the memory threshold
+    high_memory: Vec<BrowserType>,
+    tabs_unavailable: bool,
+}
+
+impl AlertState {
+    fn check(&mut self, thresholds: &AlertThresholds) -> Vec<ResourceAlert> {
+        let mut alerts = Vec::new();
+
+        if let Some(limit) = thresholds.max_tabs {
+            match crate::blocking::block_on(get_all_tabs()) {
+                Ok(tabs) => {
+                    let count = tabs.len();
+                    if count > limit && !self.too_many_tabs {
+                        self.too_many_tabs = true;
+                        alerts.push(ResourceAlert::TooManyTabs { count, limit });
+                    } else if count <= limit && self.too_many_tabs {
+                        self.too_many_tabs = false;
+                        alerts.push(ResourceAlert::TabsWithinLimit { count, limit });
+                    }
+                }
+                Err(e) if !self.tabs_unavailable => {
+                    self.tabs_unavailable = true;
+                    log::warn!("Tab count unavailable, checking memory only: {e}");
+                }
+                Err(_) => {}
+            }
+        }
+
+        if let Some(limit) = thresholds.max_memory_bytes {
+            match browser_memory() {
+                Ok(browsers) => alerts.extend(self.check_memory(&browsers, limit)),
+                Err(e) => log::warn!("Browser memory unavailable: {e}"),
+            }
+        }
+        alerts
+    }
+
+    fn check_memory(&mut self, browsers: &[BrowserMemory], limit: u64) -> Vec<ResourceAlert> {
+        let mut alerts = Vec::new();
+        for browser in browsers {
+            if browser.resident_bytes > limit && !self.high_memory.contains(&browser.browser_type) {
+                self.high_memory.push(browser.browser_type.clone());
+                alerts.push(ResourceAlert::HighMemory {
+                    browser_type: browser.browser_type.clone(),
+                    resident_bytes: browser.resident_bytes,
+                    limit,
+                });
+            }
+        }
+        // 閉じられたブラウザは 0 バイトとして扱う
+        self.high_memory.retain(|browser_type| {
+            let resident_bytes = browsers
+                .iter()
+                .find(|browser| browser.browser_type == *browser_type)
+                .map_or(0, |browser| browser.resident_bytes);
+            if resident_bytes > limit {
+                return true;
+            }
+            alerts.push(ResourceAlert::MemoryWithinLimit {
+                browser_type: browser_type.clone(),
+                resident_bytes,
+                limit,
+            });
+            false
+        });
+        alerts
+    }
+}
@@ -0,0 +1,128 @@
+// ================================================================================================
+// Appearance hints - ダークモード判定
+// ================================================================================================
+
+use crate::process_runner::ProcessRunner;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Light or dark rendering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+impl ColorScheme {
+    pub fn is_dark(&self) -> bool {
+        *self == ColorScheme::Dark
+    }
+}
+
+/// Color scheme the operating system asks applications to use
+///
+/// Browsers follow this unless the user overrides it in the browser settings,
+/// so it is a good hint for the browser window's appearance. For the page's
+/// actual rendering use the DevTools path (`browser_color_scheme`, with the
+/// `devtools` feature).
+///
+/// `None` when the setting cannot be read.
+pub fn os_color_scheme() -> Option<ColorScheme> {
+    read_os_color_scheme()
+}
+
+#[cfg(target_os = "windows")]
+fn read_os_color_scheme() -> Option<ColorScheme> {
+    let output = ProcessRunner::new("reg")
+        .args([
+            "query",
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize",
+            "/v",
+            "AppsUseLightTheme",
+        ])
+        .run()
+        .ok()?;
+    if !output.success() {
+        return None;
+    }
+
+    // "    AppsUseLightTheme    REG_DWORD    0x0"
+    let value = output
+        .stdout
+        .lines()
+        .find(|line| line.contains("AppsUseLightTheme"))?
+        .split_whitespace()
+        .last()?;
+    Some(if value == "0x0" {
+        ColorScheme::Dark
+    } else {
+        ColorScheme::Light
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn read_os_color_scheme() -> Option<ColorScheme> {
+    // The key only exists in dark mode; `defaults` fails in light mode
+    let output = ProcessRunner::new("defaults")
+        .args(["read", "-g", "AppleInterfaceStyle"])
+        .run()
+        .ok()?;
+    Some(if output.success() && output.stdout.trim() == "Dark" {
+        ColorScheme::Dark
+    } else {
+        ColorScheme::Light
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn read_os_color_scheme() -> Option<ColorScheme> {
+    let gsettings = |key: &str| {
+        let output = ProcessRunner::new("gsettings")
+            .args(["get", "org.gnome.desktop.interface", key])
+            .run()
+            .ok()?;
+        output.success().then(|| output.stdout.to_lowercase())
+    };
+
+    // GNOME 42+ has an explicit preference; older desktops only a theme name
+    if let Some(scheme) = gsettings("color-scheme") {
+        if scheme.contains("prefer-dark") {
+            return Some(ColorScheme::Dark);
+        }
+        if scheme.contains("prefer-light") {
+            return Some(ColorScheme::Light);
+        }
+    }
+    gsettings("gtk-theme").map(|theme| {
+        if theme.contains("dark") {
+            ColorScheme::Dark
+        } else {
+            ColorScheme::Light
+        }
+    })
+}
+
+/// Color scheme of the active page, falling back to the OS theme
+///
+/// With DevTools reachable this evaluates `prefers-color-scheme` in the page,
+/// which also reflects the browser's own theme setting and any `Emulation`
+/// override. Otherwise [`os_color_scheme`] is returned.
+#[cfg(feature = "devtools")]
+pub async fn browser_color_scheme() -> Option<ColorScheme> {
+    #[cfg(target_os = "windows")]
+    {
+        use crate::platform::chrome_devtools::ChromeDevToolsExtractor;
+
+        if let Ok(scheme) = crate::runtime::on_tokio(ChromeDevToolsExtractor::color_scheme_on_port(
+            ChromeDevToolsExtractor::DEFAULT_PORT,
+        ))
+        .await
+        {
+            return Some(scheme);
+        }
+    }
+    os_color_scheme()
+}
@@ -0,0 +1,76 @@
+// ================================================================================================
+// Assistive technology detection - スクリーンリーダー検出
+// ================================================================================================
+//
+// Injected keystrokes and focus changes are read aloud by screen readers and
+// move their virtual cursor, so keyboard-based extraction is switched off while
+// one is running (see `ExtractionConfig::respect_assistive_technology`).
+
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How long a detection result is reused; extraction may run several times a second
+const CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Limit for the settings query, which runs on the extraction path
+#[cfg(not(target_os = "windows"))]
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Whether a screen reader (NVDA, JAWS, Narrator, VoiceOver, Orca) is running
+///
+/// Windows reports every screen reader through the system-wide
+/// `SPI_GETSCREENREADER` flag; macOS checks VoiceOver and Linux the GNOME
+/// screen-reader setting. The result is cached for a few seconds.
+pub fn screen_reader_active() -> bool {
+    static CACHE: Mutex<Option<(Instant, bool)>> = Mutex::new(None);
+
+    let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some((checked, active)) = *cache
+        && checked.elapsed() < CACHE_TTL
+    {
+        return active;
+    }
+    let active = detect_screen_reader();
+    *cache = Some((Instant::now(), active));
+    active
+}
+
+#[cfg(target_os = "windows")]
+fn detect_screen_reader() -> bool {
+    use winapi::shared::minwindef::{BOOL, FALSE};
+    use winapi::um::winuser::{SPI_GETSCREENREADER, SystemParametersInfoW};
+
+    let mut running: BOOL = FALSE;
+    let ok = unsafe {
+        SystemParametersInfoW(
+            SPI_GETSCREENREADER,
+            0,
+            &mut running as *mut BOOL as *mut _,
+            0,
+        )
+    };
+    ok != 0 && running != FALSE
+}
+
+#[cfg(target_os = "macos")]
+fn detect_screen_reader() -> bool {
+    // 1 while VoiceOver is on; the key is missing if it was never enabled
+    crate::process_runner::ProcessRunner::new("defaults")
+        .args(["read", "com.apple.universalaccess", "voiceOverOnOffKey"])
+        .timeout(QUERY_TIMEOUT)
+        .run()
+        .is_ok_and(|output| output.success() && output.stdout.trim() == "1")
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn detect_screen_reader() -> bool {
+    crate::process_runner::ProcessRunner::new("gsettings")
+        .args([
+            "get",
+            "org.gnome.desktop.a11y.applications",
+            "screen-reader-enabled",
+        ])
+        .timeout(QUERY_TIMEOUT)
+        .run()
+        .is_ok_and(|output| output.success() && output.stdout.trim() == "true")
+}
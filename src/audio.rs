@@ -0,0 +1,45 @@
+// ================================================================================================
+// Browser audio output - 音声出力状態
+// ================================================================================================
+
+use crate::WindowHandleInfo;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Audio output of a browser process
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BrowserAudioState {
+    /// Sound is being emitted right now (peak level above silence)
+    pub is_playing: bool,
+    /// The browser's audio session is muted in the OS mixer
+    pub muted: bool,
+    /// Session volume in the OS mixer, 0.0 – 1.0
+    pub volume: Option<f32>,
+    /// Friendly name of the output device, e.g. `Headphones (USB Audio)`
+    pub device: Option<String>,
+}
+
+/// Audio state of the browser owning `window`
+///
+/// Browsers play audio from helper processes, so sessions are matched by
+/// executable name rather than by the window's PID. Uses the Windows audio
+/// session API; `None` on other platforms or when the browser has no session.
+pub fn audio_state(window: &WindowHandleInfo) -> Option<BrowserAudioState> {
+    let executable = window
+        .process_path
+        .file_stem()?
+        .to_string_lossy()
+        .to_lowercase();
+
+    #[cfg(target_os = "windows")]
+    return crate::platform::windows::audio_state(&executable);
+
+    // TODO: macOS 14+ CoreAudio process objects (kAudioHardwarePropertyProcessObjectList)
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = executable;
+        None
+    }
+}
@@ -0,0 +1,86 @@
+//! Optional audit log of intrusive actions.
+//!
+//! When an audit log path is configured via
+//! [`ExtractionConfig::audit_log`](crate::ExtractionConfig::audit_log), every
+//! simulated keystroke sequence, clipboard access and external script run is
+//! appended to that file as one JSON object per line:
+//!
+//! ```text
+//! {"timestamp":"2026-01-01T12:00:00.123Z","action":"script_execution","method":"powershell_local_script","target_pid":4242}
+//! ```
+
+use crate::ExtractionConfig;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::SystemTime;
+
+/// Kind of side effect the crate performed on the user's machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AuditAction {
+    /// Keystrokes were sent to another application
+    KeyboardSimulation,
+    /// The system clipboard was read or overwritten
+    ClipboardAccess,
+    /// An external script or interpreter was started
+    ScriptExecution,
+}
+
+impl AuditAction {
+    /// Name used in the log file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::KeyboardSimulation => "keyboard_simulation",
+            AuditAction::ClipboardAccess => "clipboard_access",
+            AuditAction::ScriptExecution => "script_execution",
+        }
+    }
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AuditEntry {
+    /// RFC 3339 timestamp (UTC)
+    pub timestamp: String,
+    pub action: AuditAction,
+    /// Extraction method that caused the action
+    pub method: String,
+    /// Process the action was aimed at
+    pub target_pid: u64,
+}
+
+/// Append an entry for each action if an audit log is configured
+///
+/// Built-in backends call this before every intrusive step; third-party
+/// [`UrlExtractor`](crate::UrlExtractor)s should do the same. Write failures
+/// are reported but never abort the extraction itself.
+pub fn record(config: &ExtractionConfig, actions: &[AuditAction], method: &str, target_pid: u64) {
+    let Some(path) = &config.audit_log else {
+        return;
+    };
+
+    let timestamp = humantime::format_rfc3339_millis(SystemTime::now()).to_string();
+    let mut lines = String::new();
+    for action in actions {
+        // Written by hand so the audit log works in builds without serde
+        lines.push_str(&format!(
+            "{{\"timestamp\":\"{timestamp}\",\"action\":\"{}\",\"method\":{:?},\"target_pid\":{target_pid}}}\n",
+            action.as_str(),
+            method,
+        ));
+    }
+
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()));
+
+    if let Err(e) = result {
+        log::warn!("Failed to write audit log {}: {e}", path.display());
+    }
+}
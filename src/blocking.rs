@@ -0,0 +1,29 @@
+// ================================================================================================
+// Running async APIs from synchronous code - 同期コードからの非同期呼び出し
+// ================================================================================================
+
+/// Drive a future from synchronous code (watcher callbacks, monitor threads)
+#[cfg(feature = "tokio")]
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build runtime")
+        .block_on(future)
+}
+
+/// Without tokio nothing in this crate waits on I/O (AppleScript runs
+/// synchronously), so polling until ready is enough
+#[cfg(not(feature = "tokio"))]
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Waker};
+
+    let mut future = std::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
@@ -2,24 +2,96 @@
 // Logic to detect the active browser - ブラウザ検出ロジック
 // ================================================================================================
 
-use crate::{BrowserInfoError, BrowserType};
-use active_win_pos_rs::ActiveWindow;
+use crate::WindowHandleInfo;
+use crate::{BrowserInfoError, BrowserType, BrowserWindowKind, ExtractionConfig};
+use std::sync::OnceLock;
 
 /// Browser metadata extracted from the window
+///
+/// Every value is computed on first access and cached afterwards, so building
+/// a `BrowserMetadata` is free and callers only pay for what they read.
 pub struct BrowserMetadata {
-    pub version: Option<String>,
-    pub tabs_count: Option<u32>,
-    pub is_incognito: bool,
+    window: WindowHandleInfo,
+    browser_type: BrowserType,
+    version: OnceLock<Option<String>>,
+    tabs_count: OnceLock<Option<u32>>,
+    is_incognito: OnceLock<bool>,
+}
+
+impl BrowserMetadata {
+    /// Create a lazy metadata view for the given window
+    pub fn new(window: &WindowHandleInfo, browser_type: &BrowserType) -> Self {
+        Self {
+            window: window.clone(),
+            browser_type: browser_type.clone(),
+            version: OnceLock::new(),
+            tabs_count: OnceLock::new(),
+            is_incognito: OnceLock::new(),
+        }
+    }
+
+    /// Browser version (looked up on first call)
+    pub fn version(&self) -> Option<&str> {
+        self.version
+            .get_or_init(|| get_browser_version(&self.window, &self.browser_type))
+            .as_deref()
+    }
+
+    /// Number of open tabs (counted on first call)
+    pub fn tabs_count(&self) -> Option<u32> {
+        *self
+            .tabs_count
+            .get_or_init(|| count_tabs(&self.window, &self.browser_type))
+    }
+
+    /// Whether the window is a private/incognito window (detected on first call)
+    pub fn is_incognito(&self) -> bool {
+        *self
+            .is_incognito
+            .get_or_init(|| detect_incognito_mode(&self.window, &self.browser_type))
+    }
 }
 
 /// Classify the browser type from window information
-pub fn classify_browser(window: &ActiveWindow) -> Result<BrowserType, BrowserInfoError> {
+///
+/// Browsers disabled by the [machine policy](crate::policy) are reported as
+/// [`NotABrowser`](BrowserInfoError::NotABrowser).
+pub fn classify_browser(window: &WindowHandleInfo) -> Result<BrowserType, BrowserInfoError> {
+    let browser_type = classify_process(window)?;
+    if crate::policy::machine_policy()
+        .disabled_browsers
+        .contains(&browser_type)
+    {
+        return Err(BrowserInfoError::NotABrowser);
+    }
+    Ok(browser_type)
+}
+
+/// [`classify_browser`] that also honours the browsers disabled in `config`
+pub fn classify_browser_with_config(
+    window: &WindowHandleInfo,
+    config: &ExtractionConfig,
+) -> Result<BrowserType, BrowserInfoError> {
+    let browser_type = classify_browser(window)?;
+    if config.is_browser_disabled(&browser_type) {
+        return Err(BrowserInfoError::NotABrowser);
+    }
+    Ok(browser_type)
+}
+
+fn classify_process(window: &WindowHandleInfo) -> Result<BrowserType, BrowserInfoError> {
     let app_name = window.app_name.to_lowercase();
 
     let process_path = window.process_path.to_str().unwrap_or("").to_lowercase();
 
     // Detailed browser classification
-    if app_name.contains("chrome") && !app_name.contains("edge") {
+    // WebView2 hosts look like Edge but have no address bar, check them first
+    if is_webview2_host(&app_name, &process_path) {
+        Ok(BrowserType::Embedded)
+    } else if is_tor_browser(&app_name, &process_path) {
+        // Tor Browser ships a renamed Firefox, so it must win over the Firefox check
+        Ok(BrowserType::Tor)
+    } else if app_name.contains("chrome") && !app_name.contains("edge") {
         Ok(BrowserType::Chrome)
     } else if app_name.contains("firefox") {
         Ok(BrowserType::Firefox)
@@ -41,16 +113,84 @@ pub fn classify_browser(window: &ActiveWindow) -> Result<BrowserType, BrowserInf
     }
 }
 
+/// Classify the kind of browser window (regular, picture-in-picture, DevTools, extension popup)
+///
+/// See [`crate::window_kind`] for the per-platform signals.
+pub fn classify_window_kind(window: &WindowHandleInfo) -> BrowserWindowKind {
+    crate::window_kind::classify_window_kind(window)
+}
+
 /// Get additional browser metadata
+///
+/// Nothing is evaluated here; see [`BrowserMetadata`] for the lazy accessors.
 pub fn get_browser_metadata(
-    window: &ActiveWindow,
+    window: &WindowHandleInfo,
     browser_type: &BrowserType,
 ) -> Result<BrowserMetadata, BrowserInfoError> {
-    Ok(BrowserMetadata {
-        version: get_browser_version(window, browser_type),
-        tabs_count: count_tabs(window, browser_type),
-        is_incognito: detect_incognito_mode(window, browser_type),
-    })
+    Ok(BrowserMetadata::new(window, browser_type))
+}
+
+fn is_webview2_host(app_name: &str, process_path: &str) -> bool {
+    app_name.contains("msedgewebview2") || process_path.contains("msedgewebview2")
+}
+
+fn is_tor_browser(app_name: &str, process_path: &str) -> bool {
+    app_name.contains("tor browser")
+        || ["tor browser", "tor-browser", "torbrowser"]
+            .iter()
+            .any(|&indicator| process_path.contains(indicator))
+}
+
+/// DevTools port of a WebView2 host, if the host app enabled remote debugging
+///
+/// Host apps opt into CDP by passing `--remote-debugging-port=<port>` to the
+/// WebView2 browser process (usually through
+/// `WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS` in *their* environment). The switch
+/// is read from the command line of the `msedgewebview2.exe` process behind
+/// `process_id`; with `--remote-debugging-port=0` the port the browser picked
+/// is read from `DevToolsActivePort` in its user data folder. Always `None`
+/// outside Windows.
+pub fn webview2_debugging_port(process_id: u64) -> Option<u16> {
+    #[cfg(target_os = "windows")]
+    {
+        crate::platform::windows::webview2_command_lines(process_id)
+            .iter()
+            .find_map(|command_line| debugging_port_of(command_line))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = process_id;
+        None
+    }
+}
+
+/// Port from `--remote-debugging-port=`, falling back to the
+/// `DevToolsActivePort` file for port 0 or a missing switch
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn debugging_port_of(command_line: &str) -> Option<u16> {
+    let port = switch_value(command_line, "--remote-debugging-port=")
+        .and_then(|port| port.parse::<u16>().ok());
+    if let Some(port) = port.filter(|&port| port != 0) {
+        return Some(port);
+    }
+    let user_data_dir = switch_value(command_line, "--user-data-dir=")?;
+    let active_port =
+        std::fs::read_to_string(std::path::Path::new(&user_data_dir).join("DevToolsActivePort"))
+            .ok()?;
+    // 1行目がポート番号、2行目がブラウザの WebSocket パス
+    active_port.lines().next()?.trim().parse().ok()
+}
+
+/// Value of a `--name=value` switch; the value may be quoted and contain spaces
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn switch_value(command_line: &str, switch: &str) -> Option<String> {
+    let rest = &command_line[command_line.find(switch)? + switch.len()..];
+    let value = match rest.strip_prefix('"') {
+        Some(quoted) => &quoted[..quoted.find('"')?],
+        None => rest.split_whitespace().next()?,
+    };
+    Some(value.to_string())
 }
 
 fn is_browser_by_path(path: &str) -> bool {
@@ -74,18 +214,56 @@ fn detect_browser_from_path(path: &str) -> Result<BrowserType, BrowserInfoError>
     }
 }
 
-fn get_browser_version(_window: &ActiveWindow, _browser_type: &BrowserType) -> Option<String> {
+fn get_browser_version(_window: &WindowHandleInfo, _browser_type: &BrowserType) -> Option<String> {
     // TODO: Implement version detection(Not Essential)
     None
 }
 
-fn count_tabs(_window: &ActiveWindow, _browser_type: &BrowserType) -> Option<u32> {
+fn count_tabs(_window: &WindowHandleInfo, _browser_type: &BrowserType) -> Option<u32> {
     // TODO: Implement tab counting(Not Essential)
     None
 }
 
-fn detect_incognito_mode(window: &ActiveWindow, _browser_type: &BrowserType) -> bool {
+pub(crate) fn detect_incognito_mode(
+    window: &WindowHandleInfo,
+    _browser_type: &BrowserType,
+) -> bool {
     // Basic incognito detection from window title
     let title = window.title.to_lowercase();
     title.contains("incognito") || title.contains("private") || title.contains("inprivate")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webview2_port_comes_from_the_switch_or_devtools_active_port() {
+        assert_eq!(
+            debugging_port_of(
+                r#""msedgewebview2.exe" --type=browser --remote-debugging-port=9333"#
+            ),
+            Some(9333)
+        );
+
+        let user_data =
+            std::env::temp_dir().join(format!("browser info wv2 {}", std::process::id()));
+        std::fs::create_dir_all(&user_data).unwrap();
+        std::fs::write(
+            user_data.join("DevToolsActivePort"),
+            "51234\n/devtools/browser/x\n",
+        )
+        .unwrap();
+        let command_line = format!(
+            r#"msedgewebview2.exe --remote-debugging-port=0 --user-data-dir="{}" --flag"#,
+            user_data.display()
+        );
+        assert_eq!(debugging_port_of(&command_line), Some(51234));
+        std::fs::remove_dir_all(&user_data).unwrap();
+
+        assert_eq!(
+            debugging_port_of("msedgewebview2.exe --type=renderer"),
+            None
+        );
+    }
+}
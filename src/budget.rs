@@ -0,0 +1,263 @@
+//! Per-domain time budgets.
+//!
+//! A [`BudgetTracker`] wraps a [`VisitStore`]: every visit is stored as usual
+//! and then checked against the configured budgets. When a visit pushes a
+//! domain over its limit for the current period, a [`BudgetExceeded`] is
+//! returned and handed to the registered callbacks (for example a
+//! [`webhook`], with the `webhook` feature).
+//!
+//! Usage is counted from the visits in the store, so the counters survive
+//! restarts whenever the store is persistent ([`JsonlStore`](crate::storage::JsonlStore)).
+//! A budget fires once per period: only for the visit that crosses the limit.
+//!
+//! ```rust
+//! use browser_info::budget::{BudgetTracker, budget};
+//! use browser_info::storage::{MemoryStore, Visit};
+//! use browser_info::BrowserType;
+//! use std::time::{Duration, SystemTime};
+//!
+//! let mut tracker = BudgetTracker::new(MemoryStore::default())
+//!     .budget(budget("youtube.com", Duration::from_secs(3600)))
+//!     .on_exceeded(|exceeded| println!("⏰ {} over budget", exceeded.domain));
+//!
+//! let visit = Visit::new(
+//!     "https://www.youtube.com/watch?v=1",
+//!     "Video - YouTube",
+//!     BrowserType::Chrome,
+//!     SystemTime::now() - Duration::from_secs(3700),
+//!     Duration::from_secs(3650),
+//! );
+//! let exceeded = tracker.record(&visit)?;
+//! # // 日付の境界をまたぐと前日の分が数えられないので、その場合は検証しない
+//! # if !exceeded.is_empty() {
+//! assert_eq!(exceeded[0].domain, "youtube.com");
+//! # }
+//! # Ok::<(), browser_info::BrowserInfoError>(())
+//! ```
+//!
+//! This module is only available with the `storage` feature enabled.
+
+use crate::BrowserInfoError;
+use crate::storage::{Bucket, Visit, VisitStore};
+use crate::url_extraction::domain_matches;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Time allowed on a domain (and its subdomains) per period
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Budget {
+    domain: String,
+    limit: Duration,
+    period: Bucket,
+}
+
+/// `limit` per UTC day on `domain`, e.g. `budget("youtube.com", 1h)`
+pub fn budget(domain: impl Into<String>, limit: Duration) -> Budget {
+    Budget {
+        domain: domain.into().trim_start_matches("www.").to_lowercase(),
+        limit,
+        period: Bucket::Day,
+    }
+}
+
+impl Budget {
+    /// Count per UTC hour instead of per UTC day
+    pub fn per_hour(mut self) -> Self {
+        self.period = Bucket::Hour;
+        self
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    pub fn limit(&self) -> Duration {
+        self.limit
+    }
+
+    /// Whether visits to `domain` count against this budget
+    pub fn covers(&self, domain: &str) -> bool {
+        domain_matches(domain, &self.domain)
+    }
+
+    /// Start and end of the period containing `time`
+    fn period_of(&self, time: SystemTime) -> (SystemTime, SystemTime) {
+        let size = match self.period {
+            Bucket::Hour => 3600,
+            _ => 86_400,
+        };
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let start = UNIX_EPOCH + Duration::from_secs(secs - secs % size);
+        (start, start + Duration::from_secs(size))
+    }
+}
+
+/// A domain went over its budget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BudgetExceeded {
+    /// The budget's domain (not the subdomain that was visited)
+    pub domain: String,
+    /// Milliseconds when serialized
+    #[serde(with = "crate::serde_time::millis")]
+    #[cfg_attr(feature = "schemars", schemars(with = "u64"))]
+    pub limit: Duration,
+    /// Time spent in the period so far; milliseconds when serialized
+    #[serde(with = "crate::serde_time::millis")]
+    #[cfg_attr(feature = "schemars", schemars(with = "u64"))]
+    pub used: Duration,
+    #[serde(with = "crate::serde_time::rfc3339")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub period_start: SystemTime,
+}
+
+type Callback = Box<dyn FnMut(&BudgetExceeded) + Send>;
+
+/// Visit store that checks budgets on every append
+pub struct BudgetTracker<S: VisitStore> {
+    store: S,
+    budgets: Vec<Budget>,
+    callbacks: Vec<Callback>,
+}
+
+impl<S: VisitStore> BudgetTracker<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            budgets: Vec::new(),
+            callbacks: Vec::new(),
+        }
+    }
+
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budgets.push(budget);
+        self
+    }
+
+    /// Call `callback` for every exceeded budget
+    pub fn on_exceeded(mut self, callback: impl FnMut(&BudgetExceeded) + Send + 'static) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Store `visit` and report the budgets it pushed over their limit
+    pub fn record(&mut self, visit: &Visit) -> Result<Vec<BudgetExceeded>, BrowserInfoError> {
+        self.store.append(visit)?;
+
+        let mut exceeded = Vec::new();
+        for budget in self.budgets.iter().filter(|b| b.covers(&visit.domain)) {
+            let (start, end) = budget.period_of(visit.end());
+            let used = self.used(budget, start, end)?;
+            let this_visit = clipped(visit, start, end);
+            // 今回の訪問で上限を超えた場合だけ通知する（再起動後の重複を防ぐ）
+            if used >= budget.limit && used.saturating_sub(this_visit) < budget.limit {
+                exceeded.push(BudgetExceeded {
+                    domain: budget.domain.clone(),
+                    limit: budget.limit,
+                    used,
+                    period_start: start,
+                });
+            }
+        }
+
+        for event in &exceeded {
+            log::info!("{event}");
+            for callback in &mut self.callbacks {
+                callback(event);
+            }
+        }
+        Ok(exceeded)
+    }
+
+    /// Time spent on the budget's domain in the period containing `at`
+    pub fn usage(&self, budget: &Budget, at: SystemTime) -> Result<Duration, BrowserInfoError> {
+        let (start, end) = budget.period_of(at);
+        self.used(budget, start, end)
+    }
+
+    fn used(
+        &self,
+        budget: &Budget,
+        start: SystemTime,
+        end: SystemTime,
+    ) -> Result<Duration, BrowserInfoError> {
+        Ok(self
+            .store
+            .visits(start..end)?
+            .iter()
+            .filter(|visit| budget.covers(&visit.domain))
+            .map(|visit| clipped(visit, start, end))
+            .sum())
+    }
+}
+
+impl<S: VisitStore> VisitStore for BudgetTracker<S> {
+    fn append(&mut self, visit: &Visit) -> Result<(), BrowserInfoError> {
+        self.record(visit).map(|_| ())
+    }
+
+    fn visits(&self, range: std::ops::Range<SystemTime>) -> Result<Vec<Visit>, BrowserInfoError> {
+        self.store.visits(range)
+    }
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} of {} used",
+            self.domain,
+            humantime::format_duration(Duration::from_secs(self.used.as_secs())),
+            humantime::format_duration(self.limit)
+        )
+    }
+}
+
+/// Part of `visit` inside `start..end`
+fn clipped(visit: &Visit, start: SystemTime, end: SystemTime) -> Duration {
+    let from = visit.start.max(start);
+    let to = visit.end().min(end);
+    to.duration_since(from).unwrap_or_default()
+}
+
+/// Callback POSTing each [`BudgetExceeded`] as JSON to `url`
+///
+/// Requests run on a background thread so `record` never waits for the
+/// network; failures are logged and dropped.
+#[cfg(feature = "webhook")]
+pub fn webhook(url: impl Into<String>) -> impl FnMut(&BudgetExceeded) + Send + 'static {
+    let url = url.into();
+    move |exceeded: &BudgetExceeded| {
+        let url = url.clone();
+        let exceeded = exceeded.clone();
+        std::thread::spawn(move || {
+            let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            else {
+                return;
+            };
+            let result = runtime.block_on(async {
+                reqwest::Client::builder()
+                    .timeout(Duration::from_secs(10))
+                    .build()?
+                    .post(&url)
+                    .json(&exceeded)
+                    .send()
+                    .await?
+                    .error_for_status()
+            });
+            if let Err(e) = result {
+                log::warn!("Budget webhook to {url} failed: {e}");
+            }
+        });
+    }
+}
@@ -0,0 +1,123 @@
+//! Aborting an extraction that is still running.
+//!
+//! Async extraction can be cancelled in two ways:
+//! - drop the future: DevTools sockets are closed and nothing is left
+//!   running, but a native step (PowerShell / osascript) that is already
+//!   executing inside a poll finishes first
+//! - pass a [`CancellationToken`] to [`get_browser_info_cancellable`]: calling
+//!   [`CancellationToken::cancel`] from any thread kills the running script
+//!   child right away and the call returns [`BrowserInfoError::Cancelled`]
+//!
+//! ```rust,no_run
+//! use browser_info::cancel::{CancellationToken, get_browser_info_cancellable};
+//!
+//! # async fn run() {
+//! let token = CancellationToken::new();
+//! let on_context_switch = token.clone();
+//! std::thread::spawn(move || {
+//!     std::thread::sleep(std::time::Duration::from_millis(200));
+//!     on_context_switch.cancel();
+//! });
+//! match get_browser_info_cancellable(&token).await {
+//!     Ok(info) => println!("{}", info.url),
+//!     Err(browser_info::BrowserInfoError::Cancelled) => println!("🛑 cancelled"),
+//!     Err(e) => println!("{e}"),
+//! }
+//! # }
+//! ```
+
+use crate::{BrowserInfo, BrowserInfoError, ExtractionConfig};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Shared flag that aborts the extractions it was passed to
+///
+/// Clones share the flag; a cancelled token stays cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<TokenState>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    /// Extractions waiting on I/O, woken so they notice the cancellation
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort every extraction using this token
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Release);
+        let wakers = std::mem::take(&mut *self.inner.wakers.lock().unwrap());
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    fn register(&self, waker: &Waker) {
+        let mut wakers = self.inner.wakers.lock().unwrap();
+        if !wakers.iter().any(|known| known.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+}
+
+/// [`get_browser_info`](crate::get_browser_info) that stops when `token` is cancelled
+pub async fn get_browser_info_cancellable(
+    token: &CancellationToken,
+) -> Result<BrowserInfo, BrowserInfoError> {
+    get_browser_info_with_config_cancellable(&ExtractionConfig::default(), token).await
+}
+
+/// [`get_browser_info_with_config`](crate::get_browser_info_with_config) that
+/// stops when `token` is cancelled
+pub async fn get_browser_info_with_config_cancellable(
+    config: &ExtractionConfig,
+    token: &CancellationToken,
+) -> Result<BrowserInfo, BrowserInfoError> {
+    Cancellable {
+        future: Box::pin(crate::get_browser_info_with_config(config)),
+        token,
+    }
+    .await
+}
+
+/// Polls `future` with `token` installed for the script children it starts
+struct Cancellable<'a, F> {
+    future: Pin<Box<F>>,
+    token: &'a CancellationToken,
+}
+
+impl<F> Future for Cancellable<'_, F>
+where
+    F: Future<Output = Result<BrowserInfo, BrowserInfoError>>,
+{
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let token = self.token;
+        if token.is_cancelled() {
+            return Poll::Ready(Err(BrowserInfoError::Cancelled));
+        }
+        token.register(cx.waker());
+        let future = self.future.as_mut();
+        // ポーリング中に同じスレッドで起動される子プロセスだけがトークンを見る
+        match crate::process_runner::with_cancellation(token, || future.poll(cx)) {
+            // 途中の手段が打ち切られてもタイトル推測で成功しうるので結果を捨てる
+            Poll::Ready(_) if token.is_cancelled() => Poll::Ready(Err(BrowserInfoError::Cancelled)),
+            poll => poll,
+        }
+    }
+}
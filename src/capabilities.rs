@@ -0,0 +1,351 @@
+// ================================================================================================
+// Per-browser capability matrix - ブラウザ別の対応状況
+// ================================================================================================
+
+use crate::{BrowserInfoError, BrowserType, ExtractionConfig};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How the URL can be obtained for a browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum UrlCapability {
+    /// Read from the address bar / browser automation
+    Exact,
+    /// Only guessed from the window title, and only with
+    /// [`allow_title_heuristics`](crate::ExtractionConfig::allow_title_heuristics)
+    Heuristic,
+    /// Not available
+    None,
+}
+
+/// How changes are noticed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EventCapability {
+    /// The browser notifies us
+    Push,
+    /// Changes are found by polling
+    Poll,
+}
+
+/// What this crate can do for a browser on the current platform
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BrowserCapabilities {
+    pub url: UrlCapability,
+    pub tabs: bool,
+    pub incognito: bool,
+    pub events: EventCapability,
+    /// A screen reader is running, so keyboard-based methods are switched off
+    /// (with the default [`ExtractionConfig`])
+    pub screen_reader_active: bool,
+    /// Short human-readable explanation suitable for UIs
+    pub note: String,
+}
+
+impl BrowserType {
+    /// Capabilities of this browser with the current platform and enabled features
+    ///
+    /// ```rust
+    /// use browser_info::{BrowserType, UrlCapability};
+    ///
+    /// let caps = BrowserType::Chrome.capabilities();
+    /// if caps.url == UrlCapability::Heuristic {
+    ///     println!("Chrome: {}", caps.note);
+    /// }
+    /// ```
+    pub fn capabilities(&self) -> BrowserCapabilities {
+        let (mut url, note) = url_capability(self);
+        let mut note = note.to_string();
+
+        let screen_reader_active = crate::assistive::screen_reader_active();
+        if screen_reader_active && url != UrlCapability::None {
+            if keyboard_only(self) {
+                url = UrlCapability::Heuristic;
+            }
+            note.push_str("; keyboard methods are off while a screen reader is running");
+        }
+
+        BrowserCapabilities {
+            url,
+            // Tab enumeration is not implemented yet on any backend
+            tabs: false,
+            incognito: detects_incognito_from_title(self),
+            events: EventCapability::Poll,
+            screen_reader_active,
+            note,
+        }
+    }
+}
+
+fn detects_incognito_from_title(browser_type: &BrowserType) -> bool {
+    // Only browsers that mark private windows in their title
+    matches!(
+        browser_type,
+        BrowserType::Chrome | BrowserType::Edge | BrowserType::Firefox | BrowserType::Brave
+    )
+}
+
+/// Exact URLs come only from simulated shortcuts
+fn keyboard_only(browser_type: &BrowserType) -> bool {
+    // Windows は UI Automation、その他は AppleScript で読める
+    cfg!(target_os = "macos") && *browser_type == BrowserType::Firefox
+}
+
+#[cfg(target_os = "windows")]
+fn url_capability(browser_type: &BrowserType) -> (UrlCapability, &'static str) {
+    match browser_type {
+        BrowserType::Tor => (UrlCapability::None, "Refused unless explicitly allowed"),
+        BrowserType::Embedded => (
+            UrlCapability::None,
+            "Only via DevTools when the host app enables remote debugging",
+        ),
+        BrowserType::Unknown(_) => (UrlCapability::Heuristic, "Guessed from the window title"),
+        BrowserType::Firefox => (
+            UrlCapability::Exact,
+            "Page document via UI Automation (also in fullscreen), then the address bar",
+        ),
+        _ => (
+            UrlCapability::Exact,
+            "Address bar via UI Automation or MSAA, falling back to PowerShell (Ctrl+L/Ctrl+C), ~0.5 s",
+        ),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn url_capability(browser_type: &BrowserType) -> (UrlCapability, &'static str) {
+    match browser_type {
+        BrowserType::Chrome | BrowserType::Safari | BrowserType::Edge | BrowserType::Brave => {
+            (UrlCapability::Exact, "AppleScript, ~50 ms")
+        }
+        BrowserType::Firefox => (
+            UrlCapability::Exact,
+            "Address bar via Cmd+L/Cmd+C keyboard simulation",
+        ),
+        BrowserType::Tor => (UrlCapability::None, "Refused unless explicitly allowed"),
+        BrowserType::Embedded => (UrlCapability::None, "Embedded web views are Windows-only"),
+        _ => (UrlCapability::Heuristic, "Guessed from the window title"),
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn url_capability(browser_type: &BrowserType) -> (UrlCapability, &'static str) {
+    match browser_type {
+        BrowserType::Tor => (UrlCapability::None, "Refused unless explicitly allowed"),
+        BrowserType::Embedded => (UrlCapability::None, "Embedded web views are Windows-only"),
+        _ => (
+            UrlCapability::Heuristic,
+            "Native extraction not implemented on this platform; guessed from the window title",
+        ),
+    }
+}
+
+/// Whether one backend can be used on this machine right now
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BackendStatus {
+    Available,
+    /// Present but not usable; says why and, where possible, how to fix it
+    Unavailable(String),
+    /// Not used on this platform or with the enabled features
+    NotApplicable,
+}
+
+impl BackendStatus {
+    pub fn is_available(&self) -> bool {
+        *self == BackendStatus::Available
+    }
+
+    fn from_result<T>(result: Result<T, BrowserInfoError>, hint: &str) -> Self {
+        match result {
+            Ok(_) => BackendStatus::Available,
+            Err(e) => BackendStatus::Unavailable(format!("{e}; {hint}")),
+        }
+    }
+}
+
+/// Linux display server of the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DisplayServer {
+    X11,
+    /// Only XWayland windows can be looked up; native Wayland windows are invisible
+    Wayland,
+}
+
+/// Which backends work on this machine, from [`probe_capabilities`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CapabilityReport {
+    /// Looking up the focused window (every extraction starts here)
+    pub window_lookup: BackendStatus,
+    /// `None` outside Linux, or without a graphical session
+    pub display_server: Option<DisplayServer>,
+    /// Windows PowerShell in full language mode (Windows)
+    pub powershell: BackendStatus,
+    /// `osascript` allowed to control System Events (macOS)
+    pub osascript: BackendStatus,
+    /// Accessibility permission, needed for simulated shortcuts (macOS)
+    pub accessibility: BackendStatus,
+    /// A DevTools port answering on the configured endpoint (Windows, `devtools` feature)
+    pub devtools: BackendStatus,
+    /// Simulated Ctrl+L / Ctrl+C with the default [`ExtractionConfig`]
+    pub keyboard_simulation: BackendStatus,
+}
+
+impl CapabilityReport {
+    /// `"<backend>: <reason>"` for every unavailable backend, for setup screens
+    pub fn issues(&self) -> Vec<String> {
+        [
+            ("window lookup", &self.window_lookup),
+            ("PowerShell", &self.powershell),
+            ("osascript", &self.osascript),
+            ("accessibility", &self.accessibility),
+            ("DevTools", &self.devtools),
+            ("keyboard simulation", &self.keyboard_simulation),
+        ]
+        .into_iter()
+        .filter_map(|(backend, status)| match status {
+            BackendStatus::Unavailable(reason) => Some(format!("{backend}: {reason}")),
+            _ => None,
+        })
+        .collect()
+    }
+}
+
+/// Check which backends are usable here, so apps can guide users through
+/// setup before the first extraction fails
+///
+/// Starts a PowerShell (Windows) or `osascript` (macOS) child and may take a
+/// second. On macOS the first run can show the system's Automation prompt
+/// for System Events, which is the permission extraction needs anyway.
+///
+/// ```rust,no_run
+/// # async fn run() {
+/// let report = browser_info::capabilities::probe_capabilities().await;
+/// for issue in report.issues() {
+///     println!("⚠️ {issue}");
+/// }
+/// # }
+/// ```
+pub async fn probe_capabilities() -> CapabilityReport {
+    let accessibility = probe_accessibility();
+    CapabilityReport {
+        window_lookup: BackendStatus::from_result(
+            crate::window_provider::active_window(),
+            "focus a window of the desktop session and try again",
+        ),
+        display_server: display_server(),
+        powershell: probe_powershell(),
+        osascript: probe_osascript(),
+        keyboard_simulation: keyboard_simulation(&accessibility),
+        accessibility,
+        devtools: probe_devtools().await,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn probe_powershell() -> BackendStatus {
+    BackendStatus::from_result(
+        crate::platform::windows::probe_powershell(),
+        "UI Automation still works, but the keyboard fallback does not",
+    )
+}
+
+#[cfg(not(target_os = "windows"))]
+fn probe_powershell() -> BackendStatus {
+    BackendStatus::NotApplicable
+}
+
+#[cfg(target_os = "macos")]
+fn probe_osascript() -> BackendStatus {
+    BackendStatus::from_result(
+        crate::platform::macos::probe_osascript(),
+        "allow it under System Settings > Privacy & Security > Automation",
+    )
+}
+
+#[cfg(not(target_os = "macos"))]
+fn probe_osascript() -> BackendStatus {
+    BackendStatus::NotApplicable
+}
+
+#[cfg(target_os = "macos")]
+fn probe_accessibility() -> BackendStatus {
+    if crate::platform::macos::accessibility_trusted() {
+        BackendStatus::Available
+    } else {
+        BackendStatus::Unavailable(
+            "not granted; add the app under System Settings > Privacy & Security > Accessibility"
+                .to_string(),
+        )
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn probe_accessibility() -> BackendStatus {
+    BackendStatus::NotApplicable
+}
+
+#[cfg(all(feature = "devtools", target_os = "windows"))]
+async fn probe_devtools() -> BackendStatus {
+    use crate::platform::chrome_devtools::ChromeDevToolsExtractor;
+
+    let port = ChromeDevToolsExtractor::DEFAULT_PORT;
+    if crate::runtime::on_tokio(ChromeDevToolsExtractor::is_available_on_port(port)).await {
+        BackendStatus::Available
+    } else {
+        BackendStatus::Unavailable(format!(
+            "nothing answers on port {port}; start the browser with --remote-debugging-port={port}"
+        ))
+    }
+}
+
+#[cfg(not(all(feature = "devtools", target_os = "windows")))]
+async fn probe_devtools() -> BackendStatus {
+    BackendStatus::NotApplicable
+}
+
+fn keyboard_simulation(accessibility: &BackendStatus) -> BackendStatus {
+    // Linux にはキー入力による抽出がない
+    if cfg!(not(any(target_os = "windows", target_os = "macos"))) {
+        return BackendStatus::NotApplicable;
+    }
+    if !ExtractionConfig::default()
+        .effective()
+        .keyboard_simulation_allowed()
+    {
+        let reason = if crate::assistive::screen_reader_active() {
+            "off while a screen reader is running"
+        } else {
+            "disabled by the machine policy"
+        };
+        return BackendStatus::Unavailable(reason.to_string());
+    }
+    if let BackendStatus::Unavailable(reason) = accessibility {
+        return BackendStatus::Unavailable(format!("accessibility permission {reason}"));
+    }
+    BackendStatus::Available
+}
+
+#[cfg(target_os = "linux")]
+fn display_server() -> Option<DisplayServer> {
+    let session_type = std::env::var("XDG_SESSION_TYPE").unwrap_or_default();
+    if session_type == "wayland" || std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        Some(DisplayServer::Wayland)
+    } else if session_type == "x11" || std::env::var_os("DISPLAY").is_some() {
+        Some(DisplayServer::X11)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn display_server() -> Option<DisplayServer> {
+    None
+}
@@ -0,0 +1,459 @@
+// ================================================================================================
+// Extraction configuration - 抽出設定
+// ================================================================================================
+
+use crate::shortcut::{self, KeyChord};
+use crate::strategy::StrategyOrder;
+use crate::window_provider::{InjectedProvider, WindowProvider};
+use crate::{BrowserInfoError, BrowserType};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How much of an extracted URL is handed back to the caller
+///
+/// Variants are ordered from least to most restrictive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum UrlPrivacy {
+    /// The URL exactly as displayed
+    #[default]
+    Full,
+    /// Query string and fragment removed (they often carry tokens or personal data)
+    Redacted,
+    /// Only scheme and host, e.g. `https://github.com`
+    DomainOnly,
+}
+
+impl UrlPrivacy {
+    /// Reduce `url` according to this privacy level
+    pub fn apply(&self, url: &str) -> String {
+        match self {
+            UrlPrivacy::Full => url.to_string(),
+            UrlPrivacy::Redacted => {
+                let end = url.find(['?', '#']).unwrap_or(url.len());
+                url[..end].to_string()
+            }
+            UrlPrivacy::DomainOnly => match url.split_once("://") {
+                Some((scheme, rest)) => {
+                    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+                    format!("{scheme}://{}", &rest[..end])
+                }
+                // Address bar value without its scheme: keep only the host
+                None if !crate::url_extraction::has_scheme(url) => {
+                    let end = url.find(['/', '?', '#']).unwrap_or(url.len());
+                    url[..end].to_string()
+                }
+                // about:blank, data:... have no host; keep only the scheme
+                None => url.split(':').next().unwrap_or_default().to_string() + ":",
+            },
+        }
+    }
+}
+
+/// Ready-made [`ExtractionConfig`]s for common needs
+///
+/// Each preset is a starting point; the usual setters still apply on top:
+///
+/// ```rust
+/// use browser_info::{ExtractionConfig, Preset, UrlPrivacy};
+///
+/// let config = ExtractionConfig::preset(Preset::Stealth).url_privacy(UrlPrivacy::Redacted);
+/// assert!(config.is_passive());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Preset {
+    /// The most reliable URL: DevTools first, keyboard extraction allowed but
+    /// skipped while the user types in the address bar, 10 s limit
+    Accuracy,
+    /// Answers within 1 s: no keyboard extraction (each run starts a script
+    /// child), learned method order
+    LowLatency,
+    /// Never touches keyboard or clipboard ([`passive`](ExtractionConfig::passive)),
+    /// 3 s limit
+    Stealth,
+    /// Domain only, no Tor Browser, and no Ctrl+C, which would leave the URL
+    /// in the clipboard history
+    Privacy,
+}
+
+/// Caller-controlled switches for the extraction pipeline
+///
+/// Setters consume and return the config so they can be chained:
+///
+/// ```rust
+/// use browser_info::{ExtractionConfig, UrlPrivacy};
+///
+/// let config = ExtractionConfig::new()
+///     .allow_tor(true)
+///     .url_privacy(UrlPrivacy::Redacted);
+/// ```
+///
+/// A machine-wide [`MachinePolicy`](crate::policy::MachinePolicy) can tighten
+/// these settings; see [`ExtractionConfig::effective`].
+#[derive(Debug, Clone)]
+pub struct ExtractionConfig {
+    pub(crate) allow_tor: bool,
+    pub(crate) url_privacy: UrlPrivacy,
+    pub(crate) allow_keyboard_simulation: bool,
+    pub(crate) audit_log: Option<PathBuf>,
+    pub(crate) detect_focus_target: bool,
+    pub(crate) detect_page_language: bool,
+    pub(crate) script_dir: Option<PathBuf>,
+    pub(crate) detect_audio: bool,
+    pub(crate) detect_performance: bool,
+    pub(crate) detect_metadata: bool,
+    pub(crate) address_bar_shortcuts: Vec<(BrowserType, Vec<KeyChord>)>,
+    pub(crate) respect_assistive_technology: bool,
+    pub(crate) passive: bool,
+    pub(crate) strategy_order: StrategyOrder,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) allow_title_heuristics: bool,
+    pub(crate) disabled_browsers: Vec<BrowserType>,
+    pub(crate) window_provider: Option<InjectedProvider>,
+}
+
+impl From<Preset> for ExtractionConfig {
+    fn from(preset: Preset) -> Self {
+        Self::preset(preset)
+    }
+}
+
+impl Default for ExtractionConfig {
+    fn default() -> Self {
+        Self {
+            allow_tor: false,
+            url_privacy: UrlPrivacy::Full,
+            allow_keyboard_simulation: true,
+            audit_log: None,
+            detect_focus_target: false,
+            detect_page_language: false,
+            script_dir: None,
+            detect_audio: false,
+            detect_performance: false,
+            detect_metadata: false,
+            address_bar_shortcuts: Vec::new(),
+            respect_assistive_technology: true,
+            passive: false,
+            strategy_order: StrategyOrder::Learned,
+            timeout: None,
+            allow_title_heuristics: false,
+            disabled_browsers: Vec::new(),
+            window_provider: None,
+        }
+    }
+}
+
+impl ExtractionConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a [`Preset`]
+    pub fn preset(preset: Preset) -> Self {
+        let config = Self::default();
+        match preset {
+            Preset::Accuracy => config
+                .strategy_order(StrategyOrder::DevToolsFirst)
+                .detect_focus_target(true)
+                .timeout(Duration::from_secs(10)),
+            Preset::LowLatency => config
+                .allow_keyboard_simulation(false)
+                .timeout(Duration::from_secs(1)),
+            Preset::Stealth => config.passive(true).timeout(Duration::from_secs(3)),
+            Preset::Privacy => config
+                .url_privacy(UrlPrivacy::DomainOnly)
+                .allow_tor(false)
+                .allow_keyboard_simulation(false),
+        }
+    }
+
+    /// Treat `browser_type` as if it were not a browser
+    ///
+    /// Its windows are reported as [`NotABrowser`](BrowserInfoError::NotABrowser),
+    /// so nothing is extracted and a watcher using this config reports
+    /// `FocusLost` while it is in front. For e.g. Safari where corporate
+    /// policy forbids AppleScript automation; administrators can do the same
+    /// machine-wide with the `disabled_browsers` [policy](crate::policy) key.
+    ///
+    /// ```rust
+    /// use browser_info::{BrowserType, ExtractionConfig};
+    ///
+    /// let config = ExtractionConfig::new().disable_browser(BrowserType::Safari);
+    /// assert!(config.is_browser_disabled(&BrowserType::Safari));
+    /// assert!(!config.is_browser_disabled(&BrowserType::Chrome));
+    /// ```
+    pub fn disable_browser(mut self, browser_type: BrowserType) -> Self {
+        if !self.disabled_browsers.contains(&browser_type) {
+            self.disabled_browsers.push(browser_type);
+        }
+        self
+    }
+
+    pub fn is_browser_disabled(&self, browser_type: &BrowserType) -> bool {
+        self.disabled_browsers.contains(browser_type)
+    }
+
+    /// Look up the focused window with `provider` for calls made with this config
+    ///
+    /// Overrides the process-wide [`set_window_provider`](crate::window_provider::set_window_provider)
+    /// for the `*_with_config` functions and sessions built from this config;
+    /// see [`FixedWindow`](crate::window_provider::FixedWindow).
+    pub fn window_provider(mut self, provider: impl WindowProvider + 'static) -> Self {
+        self.window_provider = Some(InjectedProvider(std::sync::Arc::new(provider)));
+        self
+    }
+
+    /// Allow URL extraction from Tor Browser (refused by default)
+    pub fn allow_tor(mut self, allow: bool) -> Self {
+        self.allow_tor = allow;
+        self
+    }
+
+    /// How much of the URL to return (full by default)
+    pub fn url_privacy(mut self, privacy: UrlPrivacy) -> Self {
+        self.url_privacy = privacy;
+        self
+    }
+
+    /// Allow simulated address-bar shortcuts (Ctrl+L / Cmd+L, enabled by default)
+    pub fn allow_keyboard_simulation(mut self, allow: bool) -> Self {
+        self.allow_keyboard_simulation = allow;
+        self
+    }
+
+    /// Never simulate keystrokes or touch the clipboard, whatever else is configured
+    ///
+    /// For screen recorders and accessibility tools that cannot tolerate the
+    /// Ctrl+L / Ctrl+C side effects. Only UI Automation, MSAA, read-only
+    /// AppleScript, DevTools and title guessing are used; when none of them
+    /// finds the URL the call fails with
+    /// [`ExtractionRefused`](BrowserInfoError::ExtractionRefused) instead of
+    /// falling back to the keyboard.
+    ///
+    /// ```rust
+    /// use browser_info::ExtractionConfig;
+    ///
+    /// let config = ExtractionConfig::new()
+    ///     .passive(true)
+    ///     .allow_keyboard_simulation(true);
+    /// assert!(config.is_passive());
+    /// assert!(!config.effective().keyboard_simulation_allowed());
+    /// ```
+    pub fn passive(mut self, passive: bool) -> Self {
+        self.passive = passive;
+        self
+    }
+
+    pub fn is_passive(&self) -> bool {
+        self.passive
+    }
+
+    /// Whether keyboard extraction may run under this config
+    ///
+    /// Check it on an [`effective`](Self::effective) config; machine policy,
+    /// passive mode and screen readers are only applied there.
+    pub fn keyboard_simulation_allowed(&self) -> bool {
+        self.allow_keyboard_simulation
+    }
+
+    /// Turn keyboard simulation off while a screen reader is running (enabled by default)
+    ///
+    /// Injected shortcuts are announced by the screen reader and move its
+    /// cursor. See [`screen_reader_active`](crate::assistive::screen_reader_active).
+    pub fn respect_assistive_technology(mut self, respect: bool) -> Self {
+        self.respect_assistive_technology = respect;
+        self
+    }
+
+    /// Shortcuts that focus the address bar of `browser_type`, tried in order
+    ///
+    /// For users who remapped the shortcut (e.g. custom Vivaldi keys). Replaces
+    /// the [defaults](crate::shortcut::default_shortcuts) for that browser.
+    ///
+    /// ```rust
+    /// use browser_info::shortcut::KeyChord;
+    /// use browser_info::{BrowserType, ExtractionConfig};
+    ///
+    /// let config = ExtractionConfig::new()
+    ///     .address_bar_shortcuts(BrowserType::Vivaldi, [KeyChord::alt('l'), KeyChord::function(6)]);
+    /// assert_eq!(config.address_bar_shortcuts_for(&BrowserType::Vivaldi)[0], KeyChord::alt('l'));
+    /// ```
+    pub fn address_bar_shortcuts(
+        mut self,
+        browser_type: BrowserType,
+        chords: impl IntoIterator<Item = KeyChord>,
+    ) -> Self {
+        self.address_bar_shortcuts
+            .retain(|(configured, _)| *configured != browser_type);
+        self.address_bar_shortcuts
+            .push((browser_type, chords.into_iter().collect()));
+        self
+    }
+
+    /// Shortcuts keyboard extraction uses for `browser_type`
+    pub fn address_bar_shortcuts_for(&self, browser_type: &BrowserType) -> Vec<KeyChord> {
+        self.address_bar_shortcuts
+            .iter()
+            .find(|(configured, _)| configured == browser_type)
+            .map(|(_, chords)| chords.clone())
+            .unwrap_or_else(|| shortcut::default_shortcuts(browser_type))
+    }
+
+    /// Append every keystroke simulation, clipboard access and script run to `path`
+    ///
+    /// See the [`audit`](crate::audit) module for the file format.
+    pub fn audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_log = Some(path.into());
+        self
+    }
+
+    /// Base directory for temporary script files (the system temp dir by default)
+    ///
+    /// Bundled PowerShell / AppleScript files are written to a per-process
+    /// subdirectory that only the current user can access and deleted after
+    /// each run. Packaged apps can point this at their own cache directory.
+    ///
+    /// A relative `dir` is resolved against the working directory right away,
+    /// so later `chdir` calls by the host don't move it.
+    pub fn script_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        self.script_dir = Some(std::path::absolute(&dir).unwrap_or(dir));
+        self
+    }
+
+    /// Report where keyboard focus is (address bar, page, DevTools, find bar)
+    ///
+    /// Costs one accessibility query per extraction, so it is off by default.
+    /// When enabled, keyboard-based extraction is also skipped while the user is
+    /// typing in the address bar, since Ctrl+L / Ctrl+C would clobber the input.
+    pub fn detect_focus_target(mut self, detect: bool) -> Self {
+        self.detect_focus_target = detect;
+        self
+    }
+
+    /// Report the page language
+    ///
+    /// Read from `document.documentElement.lang` when the page came through
+    /// DevTools, otherwise guessed from the title's writing system (see
+    /// [`language_from_title`](crate::site_heuristics::language_from_title)).
+    pub fn detect_page_language(mut self, detect: bool) -> Self {
+        self.detect_page_language = detect;
+        self
+    }
+
+    /// Report the browser's audio output (playing, muted, volume, device)
+    ///
+    /// Windows only; costs one PowerShell run per extraction.
+    pub fn detect_audio(mut self, detect: bool) -> Self {
+        self.detect_audio = detect;
+        self
+    }
+
+    /// Report the page's load timings (see [`PagePerformance`](crate::PagePerformance))
+    ///
+    /// Only pages read through DevTools have timings; costs one extra CDP
+    /// round trip, which can wait up to half a second for the LCP entry.
+    pub fn detect_performance(mut self, detect: bool) -> Self {
+        self.detect_performance = detect;
+        self
+    }
+
+    /// Report [`BrowserInfo::version`](crate::BrowserInfo::version) and
+    /// [`BrowserInfo::tabs_count`](crate::BrowserInfo::tabs_count) for natively
+    /// extracted pages
+    ///
+    /// Both are looked up per extraction, so they stay `None` unless asked
+    /// for; [`get_browser_metadata`](crate::browser_detection::get_browser_metadata)
+    /// reads them on demand instead. Pages read through DevTools always carry them.
+    pub fn detect_metadata(mut self, detect: bool) -> Self {
+        self.detect_metadata = detect;
+        self
+    }
+
+    /// Guess the URL from the window title when no accurate method worked (off by default)
+    ///
+    /// The guess comes from a small built-in site map (a "GitHub" title gives
+    /// `https://github.com`) and is often wrong. Such URLs are reported with
+    /// [`UrlSource::TitleHeuristic`](crate::UrlSource::TitleHeuristic) and
+    /// [`UrlConfidence::Guessed`](crate::UrlConfidence::Guessed). While this
+    /// is off, extraction fails with
+    /// [`UrlExtractionFailed`](BrowserInfoError::UrlExtractionFailed) instead.
+    pub fn allow_title_heuristics(mut self, allow: bool) -> Self {
+        self.allow_title_heuristics = allow;
+        self
+    }
+
+    /// Where DevTools is tried relative to the native methods (learned by default)
+    ///
+    /// Only the async API ([`get_browser_info_with_config`](crate::get_browser_info_with_config))
+    /// has a DevTools step.
+    pub fn strategy_order(mut self, order: StrategyOrder) -> Self {
+        self.strategy_order = order;
+        self
+    }
+
+    /// Fail with [`Timeout`](BrowserInfoError::Timeout) after `timeout` (no limit by default)
+    ///
+    /// PowerShell / osascript children still running at that point are
+    /// killed. DevTools requests keep their own 3 s limit.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// This config with the machine policy applied on top
+    ///
+    /// Policies can only make settings stricter, never looser. Keyboard
+    /// simulation is also turned off here while a screen reader is running,
+    /// unless [`respect_assistive_technology`](Self::respect_assistive_technology)
+    /// is disabled, and always in [`passive`](Self::passive) mode.
+    pub fn effective(&self) -> ExtractionConfig {
+        let mut config = crate::policy::machine_policy().apply(self.clone());
+        if config.passive {
+            config.allow_keyboard_simulation = false;
+        }
+        if config.respect_assistive_technology
+            && config.allow_keyboard_simulation
+            && crate::assistive::screen_reader_active()
+        {
+            config.allow_keyboard_simulation = false;
+        }
+        config
+    }
+
+    /// Fail early when this browser must not be touched under the current settings
+    pub fn check_browser(&self, browser_type: &BrowserType) -> Result<(), BrowserInfoError> {
+        if self.is_browser_disabled(browser_type) {
+            return Err(BrowserInfoError::NotABrowser);
+        }
+        if *browser_type == BrowserType::Tor && !self.allow_tor {
+            return Err(BrowserInfoError::ExtractionRefused(
+                "Tor Browser URLs are not extracted unless explicitly allowed".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// `browser-info` directory under the user's configuration directory
+/// (`%APPDATA%`, `~/Library/Application Support` or `$XDG_CONFIG_HOME`)
+#[cfg(feature = "serde")]
+pub(crate) fn data_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let dir = std::env::var_os("APPDATA").map(PathBuf::from);
+    #[cfg(target_os = "macos")]
+    let dir = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .map(|home| home.join("Library/Application Support"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    dir.map(|dir| dir.join("browser-info"))
+}
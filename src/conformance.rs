@@ -0,0 +1,311 @@
+//! Cross-platform conformance suite for URL extraction backends.
+//!
+//! Every backend (the built-in [`NativeExtractor`](crate::NativeExtractor) or a
+//! third-party [`UrlExtractor`]) should behave the same way for the standard
+//! [`Scenario`] list. Run the suite against synthetic fixtures or against live
+//! windows prepared by a tester, and compare the resulting [`ConformanceMatrix`]
+//! across platforms.
+//!
+//! This module is only available with the `conformance` feature enabled.
+//!
+//! ```rust
+//! use browser_info::conformance::{self, Scenario, ScenarioCase};
+//! use browser_info::{BrowserInfoError, BrowserType, UrlExtractor, WindowHandleInfo};
+//!
+//! struct TitleEcho;
+//!
+//! impl UrlExtractor for TitleEcho {
+//!     fn name(&self) -> &str {
+//!         "title-echo"
+//!     }
+//!
+//!     fn extract_url(&self, _: &WindowHandleInfo, _: &BrowserType) -> Result<String, BrowserInfoError> {
+//!         Err(BrowserInfoError::UrlExtractionFailed("not implemented".to_string()))
+//!     }
+//! }
+//!
+//! let cases: Vec<ScenarioCase> = Scenario::ALL
+//!     .iter()
+//!     .map(|scenario| ScenarioCase::fixture(*scenario, BrowserType::Chrome))
+//!     .collect();
+//!
+//! let matrix = conformance::run(&TitleEcho, &cases);
+//! println!("{matrix}");
+//! assert!(!matrix.is_conformant());
+//! ```
+
+use crate::{
+    BrowserInfoError, BrowserType, ExtractionConfig, UrlExtractor, WindowHandleInfo,
+    WindowPosition, browser_detection,
+};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Standard situations every backend is expected to handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Scenario {
+    /// Regular web page in a normal window
+    NormalPage,
+    /// Page in a private/incognito window
+    Incognito,
+    /// Browser-internal page (`chrome://settings`, `about:preferences`, ...)
+    InternalPage,
+    /// Installed web app window without an address bar
+    Pwa,
+    /// Page in a fullscreen window (address bar hidden)
+    Fullscreen,
+    /// Second window of a browser that already has another window open
+    MultipleWindows,
+}
+
+impl Scenario {
+    /// All scenarios, in report order
+    pub const ALL: [Scenario; 6] = [
+        Scenario::NormalPage,
+        Scenario::Incognito,
+        Scenario::InternalPage,
+        Scenario::Pwa,
+        Scenario::Fullscreen,
+        Scenario::MultipleWindows,
+    ];
+
+    /// Instructions for a tester preparing this scenario on a live machine
+    pub fn description(&self) -> &'static str {
+        match self {
+            Scenario::NormalPage => "Open https://example.com/ in a normal window",
+            Scenario::Incognito => "Open https://example.com/ in a private/incognito window",
+            Scenario::InternalPage => "Open the browser's settings page",
+            Scenario::Pwa => "Focus an installed web app (PWA) window",
+            Scenario::Fullscreen => "Open https://example.com/ and switch to fullscreen (F11)",
+            Scenario::MultipleWindows => {
+                "Open two windows and focus the one showing https://example.com/"
+            }
+        }
+    }
+}
+
+/// One scenario bound to a window and its expected result
+#[derive(Debug, Clone)]
+pub struct ScenarioCase {
+    pub scenario: Scenario,
+    pub window: WindowHandleInfo,
+    pub browser_type: BrowserType,
+    /// `None` means the backend is expected to fail cleanly with an error
+    pub expected_url: Option<String>,
+}
+
+impl ScenarioCase {
+    const FIXTURE_URL: &'static str = "https://example.com/";
+
+    /// Build a synthetic case that does not require a running browser
+    pub fn fixture(scenario: Scenario, browser_type: BrowserType) -> Self {
+        let (app_name, process_path) = fixture_process(&browser_type);
+        let mut window = WindowHandleInfo {
+            title: format!("Example Domain - {app_name}"),
+            process_path: PathBuf::from(process_path),
+            app_name: app_name.to_string(),
+            window_id: "1".to_string(),
+            process_id: 4242,
+            position: WindowPosition::new(100.0, 100.0, 1280.0, 800.0),
+        };
+        let mut expected_url = Some(Self::FIXTURE_URL.to_string());
+
+        match scenario {
+            Scenario::NormalPage => {}
+            Scenario::Incognito => {
+                window.title = format!("Example Domain - {app_name} (Incognito)");
+            }
+            Scenario::InternalPage => {
+                window.title = format!("Settings - {app_name}");
+                expected_url = Some(internal_settings_url(&browser_type).to_string());
+            }
+            Scenario::Pwa => {
+                window.title = "Example App".to_string();
+                expected_url = None;
+            }
+            Scenario::Fullscreen => {
+                window.position = WindowPosition::new(0.0, 0.0, 1920.0, 1080.0);
+            }
+            Scenario::MultipleWindows => {
+                window.window_id = "2".to_string();
+                window.position = WindowPosition::new(400.0, 300.0, 1024.0, 768.0);
+            }
+        }
+
+        Self {
+            scenario,
+            window,
+            browser_type,
+            expected_url,
+        }
+    }
+
+    /// Capture the currently active window for a tester-prepared scenario
+    pub fn live(
+        scenario: Scenario,
+        expected_url: Option<String>,
+    ) -> Result<Self, BrowserInfoError> {
+        Self::live_with_config(scenario, expected_url, &ExtractionConfig::default())
+    }
+
+    /// [`live`](Self::live) through the window provider of `config`
+    pub fn live_with_config(
+        scenario: Scenario,
+        expected_url: Option<String>,
+        config: &ExtractionConfig,
+    ) -> Result<Self, BrowserInfoError> {
+        let window = crate::window_provider::active_window_for(config)?;
+        let browser_type = browser_detection::classify_browser_with_config(&window, config)?;
+
+        Ok(Self {
+            scenario,
+            window,
+            browser_type,
+            expected_url,
+        })
+    }
+}
+
+/// Result of running one scenario
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Outcome {
+    /// Backend returned the expected URL (or failed where failure was expected)
+    Pass,
+    /// Backend returned a different URL than expected
+    Mismatch { expected: String, actual: String },
+    /// Backend failed where a URL was expected
+    Error(String),
+    /// Backend returned a URL where a clean failure was expected
+    UnexpectedUrl(String),
+    /// The window was not classified as the expected browser
+    Misclassified(Option<BrowserType>),
+}
+
+impl Outcome {
+    pub fn is_pass(&self) -> bool {
+        matches!(self, Outcome::Pass)
+    }
+}
+
+/// One row of the conformance matrix
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ScenarioResult {
+    pub scenario: Scenario,
+    pub browser_type: BrowserType,
+    pub outcome: Outcome,
+}
+
+/// Structured results of a backend across all scenarios
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ConformanceMatrix {
+    pub backend: String,
+    pub platform: String,
+    pub results: Vec<ScenarioResult>,
+}
+
+impl ConformanceMatrix {
+    /// Number of passing scenarios
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.outcome.is_pass()).count()
+    }
+
+    /// `true` when every scenario passed
+    pub fn is_conformant(&self) -> bool {
+        self.passed() == self.results.len()
+    }
+}
+
+impl fmt::Display for ConformanceMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} on {}: {}/{} passed",
+            self.backend,
+            self.platform,
+            self.passed(),
+            self.results.len()
+        )?;
+        for result in &self.results {
+            writeln!(
+                f,
+                "  {:<16} {:<10} {:?}",
+                format!("{:?}", result.scenario),
+                format!("{:?}", result.browser_type),
+                result.outcome
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Run `extractor` against every case and collect the matrix
+pub fn run(extractor: &dyn UrlExtractor, cases: &[ScenarioCase]) -> ConformanceMatrix {
+    let results = cases
+        .iter()
+        .map(|case| ScenarioResult {
+            scenario: case.scenario,
+            browser_type: case.browser_type.clone(),
+            outcome: run_case(extractor, case),
+        })
+        .collect();
+
+    ConformanceMatrix {
+        backend: extractor.name().to_string(),
+        platform: std::env::consts::OS.to_string(),
+        results,
+    }
+}
+
+fn run_case(extractor: &dyn UrlExtractor, case: &ScenarioCase) -> Outcome {
+    match browser_detection::classify_browser(&case.window) {
+        Ok(detected) if detected == case.browser_type => {}
+        Ok(detected) => return Outcome::Misclassified(Some(detected)),
+        Err(_) => return Outcome::Misclassified(None),
+    }
+
+    match (
+        extractor.extract_url(&case.window, &case.browser_type),
+        &case.expected_url,
+    ) {
+        (Ok(actual), Some(expected)) if &actual == expected => Outcome::Pass,
+        (Ok(actual), Some(expected)) => Outcome::Mismatch {
+            expected: expected.clone(),
+            actual,
+        },
+        (Ok(actual), None) => Outcome::UnexpectedUrl(actual),
+        (Err(e), Some(_)) => Outcome::Error(e.to_string()),
+        (Err(_), None) => Outcome::Pass,
+    }
+}
+
+fn fixture_process(browser_type: &BrowserType) -> (&'static str, &'static str) {
+    match browser_type {
+        BrowserType::Chrome => ("Google Chrome", "/opt/google/chrome/chrome"),
+        BrowserType::Firefox => ("Firefox", "/usr/lib/firefox/firefox"),
+        BrowserType::Edge => ("Microsoft Edge", "/opt/microsoft/msedge/msedge"),
+        BrowserType::Safari => ("Safari", "/Applications/Safari.app"),
+        BrowserType::Brave => ("Brave Browser", "/opt/brave.com/brave/brave"),
+        BrowserType::Opera => ("Opera", "/usr/lib/x86_64-linux-gnu/opera/opera"),
+        BrowserType::Vivaldi => ("Vivaldi", "/opt/vivaldi/vivaldi"),
+        BrowserType::Tor => ("Tor Browser", "/opt/tor-browser/Browser/firefox.real"),
+        BrowserType::Embedded => ("msedgewebview2", "C:\\Program Files\\msedgewebview2.exe"),
+        BrowserType::Unknown(_) => ("Browser", "/usr/bin/browser"),
+    }
+}
+
+fn internal_settings_url(browser_type: &BrowserType) -> &'static str {
+    match browser_type {
+        BrowserType::Firefox | BrowserType::Tor => "about:preferences",
+        BrowserType::Edge => "edge://settings/",
+        BrowserType::Brave => "brave://settings/",
+        BrowserType::Opera => "opera://settings/",
+        BrowserType::Vivaldi => "vivaldi://settings/",
+        _ => "chrome://settings/",
+    }
+}
@@ -0,0 +1,345 @@
+//! Optional write operations on the active browser (`control` feature).
+//!
+//! A deliberately small surface for automation tools: navigate, reload, open
+//! a tab, and bring a tab or browser window to the front. On Windows the commands go through the Chrome DevTools Protocol (needs
+//! the `devtools` feature and a browser started with `--remote-debugging-port`);
+//! on macOS they are AppleScript calls to the frontmost browser.
+//!
+//! Only `http`, `https`, `file` and `about` URLs are accepted, so a caller
+//! cannot be tricked into running `javascript:` in the user's tab. Browsers
+//! refused by [`ExtractionConfig::check_browser`] under the
+//! [machine policy](crate::policy) are not controlled either.
+
+use crate::{BrowserInfoError, BrowserType, ExtractionConfig};
+
+/// Load `url` in the active tab
+pub async fn navigate_active_tab(url: &str) -> Result<(), BrowserInfoError> {
+    let url = checked_url(url)?;
+
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    return devtools::command("Page.navigate", serde_json::json!({ "url": url })).await;
+
+    #[cfg(target_os = "macos")]
+    return applescript::run(|app| match app {
+        "Safari" => format!(
+            r#"tell application "Safari" to set URL of current tab of front window to "{}""#,
+            applescript::escape(url)
+        ),
+        app => format!(
+            r#"tell application "{app}" to set URL of active tab of front window to "{}""#,
+            applescript::escape(url)
+        ),
+    });
+
+    #[allow(unreachable_code)]
+    {
+        let _ = url;
+        Err(unsupported())
+    }
+}
+
+/// Reload the active tab
+pub async fn reload() -> Result<(), BrowserInfoError> {
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    return devtools::command("Page.reload", serde_json::json!({})).await;
+
+    #[cfg(target_os = "macos")]
+    return applescript::run(|app| {
+        match app {
+        "Safari" => r#"tell application "Safari" to tell current tab of front window to set URL to (get URL)"#
+            .to_string(),
+        app => format!(r#"tell application "{app}" to reload active tab of front window"#),
+    }
+    });
+
+    #[allow(unreachable_code)]
+    Err(unsupported())
+}
+
+/// Open `url` in a new tab of the front window
+pub async fn open_new_tab(url: &str) -> Result<(), BrowserInfoError> {
+    let url = checked_url(url)?;
+
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    return devtools::on_default_port(url, |port, url| async move {
+        crate::ChromeDevToolsExtractor::open_tab_on_port(port, &url).await
+    })
+    .await;
+
+    #[cfg(target_os = "macos")]
+    return applescript::run(|app| match app {
+        "Safari" => format!(
+            r#"tell application "Safari" to tell front window to set current tab to (make new tab with properties {{URL:"{}"}})"#,
+            applescript::escape(url)
+        ),
+        app => format!(
+            r#"tell application "{app}" to tell front window to make new tab with properties {{URL:"{}"}}"#,
+            applescript::escape(url)
+        ),
+    });
+
+    #[allow(unreachable_code)]
+    {
+        let _ = url;
+        Err(unsupported())
+    }
+}
+
+/// Activate the tab with `tab_id` and bring its window to the front
+///
+/// Tab ids are DevTools target ids on Windows and Chromium AppleScript tab ids
+/// on macOS (Safari tabs have no id and are not supported).
+pub async fn focus_tab(tab_id: &str) -> Result<(), BrowserInfoError> {
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    return devtools::on_default_port(tab_id, |port, tab_id| async move {
+        crate::ChromeDevToolsExtractor::activate_tab_on_port(port, &tab_id).await
+    })
+    .await;
+
+    #[cfg(target_os = "macos")]
+    return applescript::run(|app| {
+        applescript::with_tab(
+            app,
+            tab_id,
+            "set active tab index of w to i\nset index of w to 1\nactivate",
+        )
+    });
+
+    #[allow(unreachable_code)]
+    {
+        let _ = tab_id;
+        Err(unsupported())
+    }
+}
+
+/// Close the tab with `tab_id` (ids as in [`focus_tab`])
+pub async fn close_tab(tab_id: &str) -> Result<(), BrowserInfoError> {
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    return devtools::on_default_port(tab_id, |port, tab_id| async move {
+        crate::ChromeDevToolsExtractor::close_tab_on_port(port, &tab_id).await
+    })
+    .await;
+
+    #[cfg(target_os = "macos")]
+    return applescript::run(|app| applescript::with_tab(app, tab_id, "close t"));
+
+    #[allow(unreachable_code)]
+    {
+        let _ = tab_id;
+        Err(unsupported())
+    }
+}
+
+/// Mute or unmute the media elements of the tab with `tab_id`
+///
+/// Browsers expose no tab-level mute to automation, so this sets `muted` on
+/// every `<audio>`/`<video>` of the page; media created later (or inside
+/// cross-origin iframes) is not affected. On macOS the browser must allow
+/// "JavaScript from Apple Events".
+pub async fn set_tab_muted(tab_id: &str, muted: bool) -> Result<(), BrowserInfoError> {
+    let expression =
+        format!("document.querySelectorAll('audio, video').forEach(m => m.muted = {muted})");
+
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    return devtools::on_default_port(tab_id, |port, tab_id| async move {
+        crate::ChromeDevToolsExtractor::evaluate_in_tab_on_port(port, &tab_id, &expression)
+            .await
+            .map(|_| ())
+    })
+    .await;
+
+    #[cfg(target_os = "macos")]
+    return applescript::run(|app| {
+        applescript::with_tab(
+            app,
+            tab_id,
+            &format!(
+                r#"execute t javascript "{}""#,
+                applescript::escape(&expression)
+            ),
+        )
+    });
+
+    #[allow(unreachable_code)]
+    {
+        let _ = (tab_id, expression);
+        Err(unsupported())
+    }
+}
+
+/// Bring the main window of the browser process `process_id` to the front
+///
+/// Pass [`BrowserInfo::process_id`](crate::BrowserInfo::process_id). Windows
+/// may only flash the taskbar button when the calling process is not allowed
+/// to steal the focus.
+pub fn focus_browser_window(process_id: u64) -> Result<(), BrowserInfoError> {
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    if let Some(window) = crate::window_provider::all_windows()?
+        .into_iter()
+        .find(|window| window.process_id == process_id)
+    {
+        check_allowed(&crate::browser_detection::classify_browser(&window)?)?;
+    }
+
+    #[cfg(target_os = "windows")]
+    return crate::platform::windows::focus_process_window(process_id);
+
+    #[cfg(target_os = "macos")]
+    return applescript::raise_process(process_id);
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        let _ = process_id;
+        Err(unsupported())
+    }
+}
+
+/// [`navigate_active_tab`] for synchronous callers such as watcher callbacks
+pub(crate) fn navigate_active_tab_blocking(url: &str) -> Result<(), BrowserInfoError> {
+    crate::blocking::block_on(navigate_active_tab(url))
+}
+
+/// Refuse browsers the read API would refuse too
+#[allow(dead_code)]
+fn check_allowed(browser_type: &BrowserType) -> Result<(), BrowserInfoError> {
+    ExtractionConfig::default()
+        .effective()
+        .check_browser(browser_type)
+}
+
+fn checked_url(url: &str) -> Result<&str, BrowserInfoError> {
+    let url = url.trim();
+    let scheme = url.split_once(':').map(|(scheme, _)| scheme.to_lowercase());
+    match scheme.as_deref() {
+        Some("http" | "https" | "file" | "about") => Ok(url),
+        _ => Err(BrowserInfoError::InvalidUrl(format!(
+            "Refusing to navigate to {url}"
+        ))),
+    }
+}
+
+#[allow(dead_code)]
+fn unsupported() -> BrowserInfoError {
+    BrowserInfoError::PlatformError(
+        "Browser control needs the devtools feature on Windows, or macOS".to_string(),
+    )
+}
+
+#[cfg(all(feature = "devtools", target_os = "windows"))]
+mod devtools {
+    use crate::{BrowserInfoError, ChromeDevToolsExtractor};
+    use serde_json::Value;
+    use std::future::Future;
+
+    /// Send a command to the active page over the default DevTools port
+    pub(super) async fn command(method: &str, params: Value) -> Result<(), BrowserInfoError> {
+        on_default_port(method, |port, method| async move {
+            ChromeDevToolsExtractor::send_command_on_port(port, &method, params)
+                .await
+                .map(|_| ())
+        })
+        .await
+    }
+
+    /// Run `request` against the default port with an owned copy of `argument`,
+    /// so it can move to the crate's I/O runtime when the caller is not on tokio
+    ///
+    /// The browser behind the port is checked against the settings first.
+    pub(super) async fn on_default_port<F, Fut>(
+        argument: &str,
+        request: F,
+    ) -> Result<(), BrowserInfoError>
+    where
+        F: FnOnce(u16, String) -> Fut,
+        Fut: Future<Output = Result<(), BrowserInfoError>> + Send + 'static,
+    {
+        let port = ChromeDevToolsExtractor::DEFAULT_PORT;
+        let browser_type =
+            crate::runtime::on_tokio(ChromeDevToolsExtractor::browser_type_on_port(port)).await?;
+        super::check_allowed(&browser_type)?;
+        crate::runtime::on_tokio(request(port, argument.to_string())).await
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod applescript {
+    use crate::process_runner::ProcessRunner;
+    use crate::{BrowserInfoError, BrowserType, browser_detection};
+
+    /// Run the script built for the frontmost browser's AppleScript application name
+    pub(super) fn run(script: impl Fn(&str) -> String) -> Result<(), BrowserInfoError> {
+        let window = crate::window_provider::active_window()?;
+        let browser_type = browser_detection::classify_browser(&window)?;
+        super::check_allowed(&browser_type)?;
+        let app = match browser_type {
+            BrowserType::Chrome => "Google Chrome",
+            BrowserType::Edge => "Microsoft Edge",
+            BrowserType::Brave => "Brave Browser",
+            BrowserType::Vivaldi => "Vivaldi",
+            BrowserType::Safari => "Safari",
+            other => {
+                return Err(BrowserInfoError::PlatformError(format!(
+                    "{other:?} cannot be controlled with AppleScript"
+                )));
+            }
+        };
+
+        ProcessRunner::new("osascript")
+            .arg("-e")
+            .arg(script(app))
+            .run()?
+            .into_stdout("AppleScript")
+            .map(|_| ())
+    }
+
+    /// Script running `action` on the Chromium tab `t` (index `i` in window `w`) with `tab_id`
+    pub(super) fn with_tab(app: &str, tab_id: &str, action: &str) -> String {
+        if app == "Safari" {
+            return r#"error "Safari tabs have no id""#.to_string();
+        }
+        format!(
+            r#"tell application "{app}"
+                repeat with w in windows
+                    set i to 0
+                    repeat with t in tabs of w
+                        set i to i + 1
+                        if (id of t as text) is "{}" then
+                            {action}
+                            return
+                        end if
+                    end repeat
+                end repeat
+                error "No tab with this id"
+            end tell"#,
+            escape(tab_id)
+        )
+    }
+
+    /// Make the process frontmost and raise its front window (System Events)
+    pub(super) fn raise_process(process_id: u64) -> Result<(), BrowserInfoError> {
+        let script = format!(
+            r#"tell application "System Events"
+                set p to first process whose unix id is {process_id}
+                set frontmost of p to true
+                try
+                    perform action "AXRaise" of window 1 of p
+                end try
+            end tell"#
+        );
+        match ProcessRunner::new("osascript")
+            .arg("-e")
+            .arg(script)
+            .run()?
+            .into_stdout("AppleScript")
+        {
+            Ok(_) => Ok(()),
+            Err(BrowserInfoError::PlatformError(_)) => Err(BrowserInfoError::WindowNotFound),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Quote a value for use inside an AppleScript string literal
+    pub(super) fn escape(value: &str) -> String {
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+}
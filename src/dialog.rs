@@ -0,0 +1,88 @@
+// ================================================================================================
+// Native dialogs over the browser - ダイアログ検出
+// ================================================================================================
+
+use crate::WindowHandleInfo;
+use crate::window_kind::window_traits;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Native modal dialog owned by the browser window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum DialogKind {
+    /// Print dialog
+    Print,
+    /// Open / save / upload file picker
+    FilePicker,
+    /// Credential or sign-in prompt (HTTP auth, Windows Security, ...)
+    Authentication,
+    /// Any other modal dialog
+    Other,
+}
+
+impl DialogKind {
+    /// Guess the dialog kind from its window title
+    ///
+    /// ```rust
+    /// use browser_info::DialogKind;
+    ///
+    /// assert_eq!(DialogKind::from_title("Save As"), DialogKind::FilePicker);
+    /// assert_eq!(DialogKind::from_title("印刷"), DialogKind::Print);
+    /// assert_eq!(DialogKind::from_title("Leave site?"), DialogKind::Other);
+    /// ```
+    pub fn from_title(title: &str) -> Self {
+        let title = title.trim().to_lowercase();
+        let has = |markers: &[&str]| markers.iter().any(|marker| title.contains(marker));
+
+        if has(&["print", "印刷"]) {
+            DialogKind::Print
+        } else if has(&[
+            "open",
+            "save",
+            "upload",
+            "select",
+            "choose",
+            "ファイルを開く",
+            "名前を付けて保存",
+            "アップロード",
+        ]) {
+            DialogKind::FilePicker
+        } else if has(&[
+            "sign in",
+            "log in",
+            "authentication",
+            "credential",
+            "windows security",
+            "password",
+            "認証",
+            "サインイン",
+        ]) {
+            DialogKind::Authentication
+        } else {
+            DialogKind::Other
+        }
+    }
+}
+
+/// Modal dialog currently blocking `window`, if any
+///
+/// Catches both the dialog itself being focused (recognized through
+/// [`window_traits`]) and a dialog attached to the focused browser window: an
+/// owned modal popup on Windows, a sheet on macOS.
+pub fn blocking_dialog(window: &WindowHandleInfo) -> Option<DialogKind> {
+    if window_traits(window).dialog {
+        return Some(DialogKind::from_title(&window.title));
+    }
+
+    #[cfg(target_os = "windows")]
+    return crate::platform::windows::owned_dialog_title(window)
+        .map(|title| DialogKind::from_title(&title));
+
+    #[cfg(target_os = "macos")]
+    return crate::platform::macos::sheet_title().map(|title| DialogKind::from_title(&title));
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    None
+}
@@ -0,0 +1,63 @@
+// ================================================================================================
+// BrowserInfo formatting - 一行表示
+// ================================================================================================
+
+use crate::{BrowserInfo, BrowserType, UrlPrivacy};
+use std::fmt;
+
+/// Separator between the parts of the compact form
+const SEPARATOR: &str = " • ";
+
+impl BrowserInfo {
+    /// One line for logs and CLI output, e.g. `Chrome • github.com/rust-lang/rust • 1920x1080`
+    ///
+    /// The URL is shown without its scheme, exactly as far as the
+    /// [`UrlPrivacy`] of the extraction allowed. Use
+    /// [`format_compact_with`](Self::format_compact_with) to cut it down
+    /// further for a log that must not keep paths or query strings.
+    ///
+    /// ```rust,no_run
+    /// let info = browser_info::get_active_browser_info()?;
+    /// println!("{}", info.format_compact());
+    /// // Same as `println!("{info}")`
+    /// # Ok::<(), browser_info::BrowserInfoError>(())
+    /// ```
+    pub fn format_compact(&self) -> String {
+        self.format_compact_with(UrlPrivacy::Full)
+    }
+
+    /// [`format_compact`](Self::format_compact) with the URL reduced to `privacy`
+    pub fn format_compact_with(&self, privacy: UrlPrivacy) -> String {
+        let mut parts = vec![browser_label(&self.browser_type)];
+
+        let url = privacy.apply(&self.url);
+        let url = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+        if !url.is_empty() {
+            parts.push(url.trim_end_matches('/').to_string());
+        } else if self.not_responding {
+            parts.push("not responding".to_string());
+        }
+
+        let position = &self.window_position;
+        if position.width > 0.0 && position.height > 0.0 {
+            parts.push(format!("{}x{}", position.width, position.height));
+        }
+        if self.is_incognito {
+            parts.push("incognito".to_string());
+        }
+        parts.join(SEPARATOR)
+    }
+}
+
+impl fmt::Display for BrowserInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format_compact())
+    }
+}
+
+fn browser_label(browser_type: &BrowserType) -> String {
+    match browser_type {
+        BrowserType::Unknown(name) => name.clone(),
+        known => format!("{known:?}"),
+    }
+}
@@ -50,6 +50,38 @@ pub enum BrowserInfoError {
     #[error("Chrome DevTools not available")]
     ChromeDevToolsNotAvailable,
 
+    /// The DevTools server refused the client (HTTP 403); the message says how to allow it
+    #[error("DevTools refused the connection: {0}")]
+    DevToolsForbidden(String),
+
+    /// Extraction refused by privacy settings (e.g. Tor Browser)
+    #[error("URL extraction refused: {0}")]
+    ExtractionRefused(String),
+
+    /// A native dialog (print, file picker, sign-in prompt) is open over the browser
+    #[error("A {0:?} dialog is open over the browser")]
+    BlockedByDialog(crate::DialogKind),
+
+    /// A helper process (PowerShell, osascript, xprop) wrote more than this many bytes
+    #[error("Helper process output exceeded {0} bytes")]
+    OutputLimitExceeded(usize),
+
+    /// This many helper processes were already running
+    #[error("Too many helper processes running (limit {0})")]
+    TooManyProcesses(usize),
+
+    /// The caller cancelled the operation
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    /// The browser stopped processing input (shown as "Not Responding")
+    #[error("The browser is not responding")]
+    BrowserNotResponding,
+
+    /// Reading or writing the visit store failed
+    #[error("Storage error: {0}")]
+    StorageError(String),
+
     /// Other error
     #[error("Other error: {0}")]
     Other(String),
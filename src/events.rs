@@ -0,0 +1,186 @@
+//! Async stream of browser events.
+//!
+//! [`browser_events`] runs a [`BrowserWatcher`] and turns its samples into
+//! [`BrowserEvent`]s that async code can await:
+//!
+//! ```rust,no_run
+//! use browser_info::events::{BrowserEventKind, browser_events};
+//! use futures_util::StreamExt;
+//! use std::time::Duration;
+//!
+//! # async fn run() {
+//! let mut events = browser_events(Duration::from_secs(1));
+//! while let Some(event) = events.next().await {
+//!     match event.kind {
+//!         BrowserEventKind::UrlChanged { info, .. } => println!("🌐 {}", info.url),
+//!         BrowserEventKind::BrowserClosed { browser_type, .. } => {
+//!             println!("👋 {browser_type:?} closed")
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! The watcher thread stops when the stream is dropped.
+
+use crate::responsiveness::is_process_running;
+use crate::watcher::{BrowserWatcher, WatchEvent, WatchEventKind, WatchOptions};
+use crate::{BrowserInfo, BrowserType};
+use futures_util::Stream;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Something that happened to the focused browser
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BrowserEvent {
+    /// When the watcher sampled the change
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_time::rfc3339"))]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub timestamp: SystemTime,
+    pub kind: BrowserEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "data", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[allow(clippy::large_enum_variant)]
+pub enum BrowserEventKind {
+    /// A browser window got the focus (from another app or another browser)
+    FocusGained(BrowserInfo),
+    /// The focus moved away from the browser, which is still running; also
+    /// sent when the session locks, the machine sleeps or the watcher is
+    /// paused, so that time is not charged to the page
+    FocusLost,
+    /// The focused browser shows another URL
+    UrlChanged {
+        info: BrowserInfo,
+        previous_url: Option<String>,
+    },
+    /// Same URL, new title (e.g. an unread counter or a single-page app)
+    TitleChanged(BrowserInfo),
+    /// The browser that had the focus exited
+    BrowserClosed {
+        browser_type: BrowserType,
+        process_id: u64,
+    },
+}
+
+/// Stream of [`BrowserEvent`]s, backed by a [`BrowserWatcher`]
+#[derive(Debug)]
+pub struct BrowserEventStream {
+    receiver: UnboundedReceiver<BrowserEvent>,
+    // ストリームと一緒に監視スレッドを止める
+    _watcher: BrowserWatcher,
+}
+
+/// Sample the active browser every `interval` and stream the changes
+pub fn browser_events(interval: Duration) -> BrowserEventStream {
+    browser_events_with_options(WatchOptions::new().interval(interval))
+}
+
+/// [`browser_events`] with full control over the underlying watcher
+pub fn browser_events_with_options(options: WatchOptions) -> BrowserEventStream {
+    let (sender, receiver) = unbounded_channel();
+    let mut translator = Translator {
+        sender,
+        focused: None,
+    };
+    let watcher =
+        BrowserWatcher::spawn_with_options(options, move |event| translator.translate(event));
+    BrowserEventStream {
+        receiver,
+        _watcher: watcher,
+    }
+}
+
+impl Stream for BrowserEventStream {
+    type Item = BrowserEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<BrowserEvent>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Turns watcher samples into focus / URL / title / close events
+struct Translator {
+    sender: UnboundedSender<BrowserEvent>,
+    /// Browser that had the focus at the last sample
+    focused: Option<BrowserInfo>,
+}
+
+impl Translator {
+    fn translate(&mut self, event: WatchEvent) {
+        let timestamp = event.timestamp;
+        let send = |kind| {
+            // 受信側が破棄されても監視スレッドは drop まで動くだけ
+            let _ = self.sender.send(BrowserEvent { timestamp, kind });
+        };
+
+        match event.kind {
+            WatchEventKind::Changed(info) => {
+                let kind = match self.focused.take() {
+                    Some(last)
+                        if last.process_id == info.process_id
+                            && last.browser_type == info.browser_type =>
+                    {
+                        if last.url != info.url {
+                            BrowserEventKind::UrlChanged {
+                                info: info.clone(),
+                                previous_url: event.previous_url,
+                            }
+                        } else if last.title != info.title {
+                            BrowserEventKind::TitleChanged(info.clone())
+                        } else {
+                            // 位置やサイズの変化だけ
+                            self.focused = Some(info);
+                            return;
+                        }
+                    }
+                    // 別のブラウザへ直接フォーカスが移った
+                    Some(last) => {
+                        if let Some(closed) = closed_event(&last) {
+                            send(closed);
+                        }
+                        BrowserEventKind::FocusGained(info.clone())
+                    }
+                    None => BrowserEventKind::FocusGained(info.clone()),
+                };
+                send(kind);
+                self.focused = Some(info);
+            }
+            // ロック中・スリープ中・一時停止中の時間を直前のページに付けない
+            WatchEventKind::FocusLost
+            | WatchEventKind::SessionLocked
+            | WatchEventKind::SystemSleep
+            | WatchEventKind::Paused { .. } => {
+                if let Some(last) = self.focused.take() {
+                    send(closed_event(&last).unwrap_or(BrowserEventKind::FocusLost));
+                }
+            }
+            // 再開後は次の Changed が FocusGained になる
+            WatchEventKind::Error(_)
+            | WatchEventKind::Resumed
+            | WatchEventKind::SessionUnlocked
+            | WatchEventKind::SystemWake => {}
+        }
+    }
+}
+
+/// [`BrowserEventKind::BrowserClosed`] if the process behind `last` is gone
+fn closed_event(last: &BrowserInfo) -> Option<BrowserEventKind> {
+    (!is_process_running(last.process_id)).then(|| BrowserEventKind::BrowserClosed {
+        browser_type: last.browser_type.clone(),
+        process_id: last.process_id,
+    })
+}
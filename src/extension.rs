@@ -0,0 +1,181 @@
+// ================================================================================================
+// Extension pages and web-store listings - 拡張機能ページの検出
+// ================================================================================================
+
+use crate::BrowserInfo;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Where an extension is being looked at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ExtensionPage {
+    /// A page served by the extension itself (options, popup opened as a tab, ...)
+    Ui,
+    /// The extension's listing in a browser web store
+    StoreListing,
+}
+
+/// Extension the active page belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ExtensionContext {
+    /// Extension id: 32 letters `a`-`p` for Chromium, the listing slug on
+    /// addons.mozilla.org, the per-install UUID for `moz-extension://` pages
+    pub id: String,
+    /// Display name from the installed extension's manifest
+    ///
+    /// Only resolved for Chromium-based browsers, and only when the extension
+    /// is installed in the default profile and the manifest is readable
+    /// (requires the `serde` feature).
+    pub name: Option<String>,
+    pub page: ExtensionPage,
+}
+
+/// Web stores whose listing URLs end with the extension id
+const STORE_LISTINGS: [&str; 3] = [
+    "https://chromewebstore.google.com/detail/",
+    "https://chrome.google.com/webstore/detail/",
+    "https://microsoftedge.microsoft.com/addons/detail/",
+];
+
+/// Listing prefix of addons.mozilla.org, after the locale segment
+const FIREFOX_LISTING: &str = "/firefox/addon/";
+
+impl ExtensionContext {
+    /// Detect an extension page or web-store listing without resolving the name
+    ///
+    /// ```rust
+    /// use browser_info::extension::{ExtensionContext, ExtensionPage};
+    ///
+    /// let context = ExtensionContext::from_url(
+    ///     "chrome-extension://cjpalhdlnbpafiamejdnhcphjbkeiagm/dashboard.html",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(context.id, "cjpalhdlnbpafiamejdnhcphjbkeiagm");
+    /// assert_eq!(context.page, ExtensionPage::Ui);
+    ///
+    /// let listing = ExtensionContext::from_url(
+    ///     "https://chromewebstore.google.com/detail/ublock-origin/cjpalhdlnbpafiamejdnhcphjbkeiagm",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(listing.page, ExtensionPage::StoreListing);
+    ///
+    /// assert_eq!(ExtensionContext::from_url("https://example.com/"), None);
+    /// ```
+    pub fn from_url(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let lower = url.to_lowercase();
+
+        let (id, page) = if let Some(rest) =
+            ["chrome-extension://", "extension://", "moz-extension://"]
+                .iter()
+                .find_map(|scheme| lower.strip_prefix(scheme))
+        {
+            (first_segment(rest), ExtensionPage::Ui)
+        } else if let Some(rest) = STORE_LISTINGS
+            .iter()
+            .find_map(|prefix| lower.strip_prefix(prefix))
+        {
+            // `<slug>/<id>` (the slug is optional on the old Chrome store)
+            let path = &rest[..rest.find(['?', '#']).unwrap_or(rest.len())];
+            let id = path.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+            if !is_chromium_id(id) {
+                return None;
+            }
+            (id, ExtensionPage::StoreListing)
+        } else if let Some(rest) = lower.strip_prefix("https://addons.mozilla.org/") {
+            let (_, slug) = rest.split_once(FIREFOX_LISTING)?;
+            (first_segment(slug), ExtensionPage::StoreListing)
+        } else {
+            return None;
+        };
+
+        if id.is_empty() {
+            return None;
+        }
+        Some(Self {
+            id: id.to_string(),
+            name: None,
+            page,
+        })
+    }
+
+    /// Like [`from_url`](Self::from_url), then look up the display name in the
+    /// installed extension's manifest
+    pub fn resolve(url: &str) -> Option<Self> {
+        let context = Self::from_url(url)?;
+        #[cfg(feature = "serde")]
+        if is_chromium_id(&context.id) {
+            let name = manifest::installed_name(&context.id);
+            return Some(Self { name, ..context });
+        }
+        Some(context)
+    }
+}
+
+fn first_segment(rest: &str) -> &str {
+    &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())]
+}
+
+/// Chromium extension ids are 32 characters from `a` to `p`
+fn is_chromium_id(id: &str) -> bool {
+    id.len() == 32 && id.bytes().all(|b| (b'a'..=b'p').contains(&b))
+}
+
+#[cfg(feature = "serde")]
+mod manifest {
+    use serde_json::Value;
+    use std::path::Path;
+
+    /// Name of extension `id` from the first default profile that has it installed
+    pub(super) fn installed_name(id: &str) -> Option<String> {
+        crate::profiles::chromium_profiles()
+            .into_iter()
+            .find_map(|(_, profile)| {
+                // Extensions/<id>/<version>/manifest.json; any installed version will do
+                let versions = std::fs::read_dir(profile.join("Extensions").join(id)).ok()?;
+                versions
+                    .flatten()
+                    .find_map(|version| name_from_manifest(&version.path()))
+            })
+    }
+
+    fn name_from_manifest(dir: &Path) -> Option<String> {
+        let manifest: Value = read_json(&dir.join("manifest.json"))?;
+        let name = manifest.get("name")?.as_str()?;
+
+        // "__MSG_appName__" は _locales/<default_locale>/messages.json を参照する
+        let Some(key) = name
+            .strip_prefix("__MSG_")
+            .and_then(|rest| rest.strip_suffix("__"))
+        else {
+            return Some(name.to_string());
+        };
+        let locale = manifest.get("default_locale")?.as_str()?;
+        let messages = read_json(&dir.join("_locales").join(locale).join("messages.json"))?;
+        // Message keys are case-insensitive
+        messages
+            .as_object()?
+            .iter()
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(key))
+            .and_then(|(_, message)| message.get("message")?.as_str())
+            .map(str::to_string)
+    }
+
+    fn read_json(path: &Path) -> Option<Value> {
+        let text = std::fs::read_to_string(path).ok()?;
+        // Chromium が書き出す manifest には BOM が付くことがある
+        serde_json::from_str(text.trim_start_matches('\u{feff}')).ok()
+    }
+}
+
+impl BrowserInfo {
+    /// Extension whose page or store listing is shown, with its name when the
+    /// manifest can be read
+    pub fn extension_context(&self) -> Option<ExtensionContext> {
+        ExtensionContext::resolve(&self.url)
+    }
+}
@@ -0,0 +1,180 @@
+// ================================================================================================
+// Continuous focus tracking - 連続フォーカス時間の計測
+// ================================================================================================
+
+use crate::BrowserInfo;
+use crate::url_extraction::domain_of;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Part of the browser window that has keyboard focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum BrowserFocusTarget {
+    /// The user is typing in the address / search bar
+    AddressBar,
+    /// The web page itself
+    PageContent,
+    /// Developer tools (docked or undocked)
+    DevTools,
+    /// The find-in-page bar
+    FindBar,
+}
+
+impl BrowserFocusTarget {
+    /// Classify a focused accessibility element (UIA on Windows, AX on macOS)
+    ///
+    /// `role` is the UIA control type (`ControlType.Edit`) or AX role
+    /// (`AXTextField`); `name` is the element's name or description and `id`
+    /// its automation id / AX identifier, if any.
+    ///
+    /// ```rust
+    /// use browser_info::focus::BrowserFocusTarget;
+    ///
+    /// assert_eq!(
+    ///     BrowserFocusTarget::from_accessibility("ControlType.Edit", "Address and search bar", ""),
+    ///     Some(BrowserFocusTarget::AddressBar)
+    /// );
+    /// assert_eq!(
+    ///     BrowserFocusTarget::from_accessibility("AXWebArea", "GitHub", ""),
+    ///     Some(BrowserFocusTarget::PageContent)
+    /// );
+    /// ```
+    pub fn from_accessibility(role: &str, name: &str, id: &str) -> Option<Self> {
+        let role = role.to_lowercase();
+        let name = name.to_lowercase();
+        let id = id.to_lowercase();
+
+        if name.starts_with("devtools") || name.contains("developer tools") {
+            return Some(Self::DevTools);
+        }
+        if role.ends_with("document") || role == "axwebarea" {
+            return Some(Self::PageContent);
+        }
+
+        let is_text_input = ["edit", "combobox", "textfield", "searchfield"]
+            .iter()
+            .any(|input| role.ends_with(input));
+        if !is_text_input {
+            return None;
+        }
+        if name.contains("find") || id.contains("find") {
+            return Some(Self::FindBar);
+        }
+        let address_markers = [
+            "address",
+            "urlbar",
+            "location",
+            "search or enter",
+            "smart search",
+        ];
+        address_markers
+            .iter()
+            .any(|marker| name.contains(marker) || id.contains(marker))
+            .then_some(Self::AddressBar)
+    }
+}
+
+/// Where keyboard focus is inside the active browser window
+///
+/// Queries UI Automation (Windows) or the Accessibility API (macOS; requires
+/// the accessibility permission). Always `None` on other platforms.
+pub fn focus_target() -> Option<BrowserFocusTarget> {
+    #[cfg(target_os = "windows")]
+    let element = crate::platform::windows::focused_element();
+    #[cfg(target_os = "macos")]
+    let element = crate::platform::macos::focused_element();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let element: Option<(String, String, String)> = None;
+
+    let (role, name, id) = element?;
+    BrowserFocusTarget::from_accessibility(&role, &name, &id)
+}
+
+/// What counts as "the same page" for focus continuity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FocusScope {
+    /// Any URL change restarts the clock
+    Url,
+    /// Navigating within the same domain keeps the clock running
+    #[default]
+    Domain,
+}
+
+/// Incrementally maintained "currently focused for" state
+///
+/// Feed it every polling sample; it answers how long the current page (or
+/// domain) has been focused without interruption.
+///
+/// ```rust,no_run
+/// use browser_info::focus::FocusTracker;
+///
+/// let mut tracker = FocusTracker::default();
+/// if let Ok(info) = browser_info::get_active_browser_info() {
+///     tracker.observe(Some(info));
+/// } else {
+///     tracker.observe(None);
+/// }
+///
+/// if let Some((info, duration)) = tracker.current_focus() {
+///     println!("You've been on {} for {} minutes", info.url, duration.as_secs() / 60);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FocusTracker {
+    scope: FocusScope,
+    current: Option<(BrowserInfo, Instant)>,
+}
+
+impl FocusTracker {
+    pub fn new(scope: FocusScope) -> Self {
+        Self {
+            scope,
+            current: None,
+        }
+    }
+
+    /// Record a sample; `None` means no browser is focused
+    pub fn observe(&mut self, info: Option<BrowserInfo>) {
+        self.observe_at(info, Instant::now());
+    }
+
+    /// [`observe`](Self::observe) with an explicit sample time
+    pub fn observe_at(&mut self, info: Option<BrowserInfo>, now: Instant) {
+        let Some(info) = info else {
+            self.current = None;
+            return;
+        };
+
+        let since = match &self.current {
+            Some((previous, since)) if self.is_same_focus(previous, &info) => *since,
+            _ => now,
+        };
+        self.current = Some((info, since));
+    }
+
+    /// The focused page and how long it has been focused continuously
+    pub fn current_focus(&self) -> Option<(BrowserInfo, Duration)> {
+        self.current
+            .as_ref()
+            .map(|(info, since)| (info.clone(), since.elapsed()))
+    }
+
+    /// When the current focus stretch started
+    pub fn focused_since(&self) -> Option<Instant> {
+        self.current.as_ref().map(|(_, since)| *since)
+    }
+
+    fn is_same_focus(&self, previous: &BrowserInfo, next: &BrowserInfo) -> bool {
+        if previous.process_id != next.process_id {
+            return false;
+        }
+        match self.scope {
+            FocusScope::Url => previous.url == next.url,
+            FocusScope::Domain => domain_of(&previous.url) == domain_of(&next.url),
+        }
+    }
+}
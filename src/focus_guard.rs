@@ -0,0 +1,172 @@
+//! Focus sessions (Pomodoro timers, "deep work" blockers).
+//!
+//! A [`FocusGuard`] watches the active browser for as long as it lives and
+//! reports a [`FocusGuardEvent::Distraction`] whenever the focused page is on
+//! a domain outside the allowlist. With the `control` feature it can also act
+//! on its own: load the last allowed page again, or a fixed "stay focused"
+//! page.
+//!
+//! ```rust,no_run
+//! use browser_info::focus_guard::{FocusGuard, FocusGuardEvent};
+//!
+//! let session = FocusGuard::start(["github.com", "docs.rs"], |event| match event {
+//!     FocusGuardEvent::Distraction(url) => println!("🙅 {url}"),
+//!     FocusGuardEvent::BackOnTrack(url) => println!("✅ {url}"),
+//! });
+//!
+//! // 25 minutes later
+//! drop(session);
+//! ```
+//!
+//! Domains cover their subdomains (`"github.com"` allows `gist.github.com`).
+//! Pages without a domain (`about:blank`, `chrome://newtab`, file URLs) and
+//! samples where no URL could be extracted are never distractions.
+
+use crate::url_extraction::{domain_matches, domain_of};
+use crate::watcher::{BrowserWatcher, WatchEventKind, WatchOptions};
+use std::time::{Duration, Instant};
+
+/// What the guard noticed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FocusGuardEvent {
+    /// The focused page is outside the allowlist
+    Distraction(String),
+    /// An allowed page has the focus again after a distraction
+    BackOnTrack(String),
+}
+
+/// What the guard does by itself on a distraction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DistractionAction {
+    /// Only report the event
+    #[default]
+    Notify,
+    /// Load the last allowed URL in the active tab (`control` feature)
+    #[cfg(feature = "control")]
+    ReturnToAllowed,
+    /// Load this URL in the active tab (`control` feature)
+    #[cfg(feature = "control")]
+    Redirect(String),
+}
+
+/// Settings of a focus session
+#[derive(Debug, Clone)]
+pub struct FocusOptions {
+    allowed_domains: Vec<String>,
+    action: DistractionAction,
+    watch: WatchOptions,
+}
+
+impl FocusOptions {
+    pub fn new(allowed_domains: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            allowed_domains: allowed_domains
+                .into_iter()
+                .map(|domain| domain.into().trim_start_matches("www.").to_lowercase())
+                .collect(),
+            action: DistractionAction::default(),
+            watch: WatchOptions::new(),
+        }
+    }
+
+    /// What to do on a distraction ([`DistractionAction::Notify`] by default)
+    pub fn on_distraction(mut self, action: DistractionAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Watcher settings (interval, extraction config, ...)
+    pub fn watch(mut self, options: WatchOptions) -> Self {
+        self.watch = options;
+        self
+    }
+
+    /// Whether `url` may be focused during the session
+    pub fn is_allowed(&self, url: &str) -> bool {
+        let Some(domain) = domain_of(url) else {
+            return true;
+        };
+        self.allowed_domains
+            .iter()
+            .any(|allowed| domain_matches(&domain, allowed))
+    }
+}
+
+/// A running focus session; it ends when the guard is dropped
+#[derive(Debug)]
+pub struct FocusGuard {
+    started: Instant,
+    _watcher: BrowserWatcher,
+}
+
+impl FocusGuard {
+    /// Start a session allowing only `allowed_domains`
+    pub fn start<F>(
+        allowed_domains: impl IntoIterator<Item = impl Into<String>>,
+        on_event: F,
+    ) -> Self
+    where
+        F: FnMut(FocusGuardEvent) + Send + 'static,
+    {
+        Self::start_with_options(FocusOptions::new(allowed_domains), on_event)
+    }
+
+    pub fn start_with_options<F>(options: FocusOptions, mut on_event: F) -> Self
+    where
+        F: FnMut(FocusGuardEvent) + Send + 'static,
+    {
+        let watch = options.watch.clone();
+        let mut last_allowed: Option<String> = None;
+        let mut distracted_by: Option<String> = None;
+
+        let watcher = BrowserWatcher::spawn_with_options(watch, move |event| {
+            let WatchEventKind::Changed(info) = event.kind else {
+                return;
+            };
+            if info.url.is_empty() {
+                return;
+            }
+
+            if options.is_allowed(&info.url) {
+                if distracted_by.take().is_some() {
+                    on_event(FocusGuardEvent::BackOnTrack(info.url.clone()));
+                }
+                last_allowed = Some(info.url);
+            } else if distracted_by.as_deref() != Some(info.url.as_str()) {
+                // 同じページのタイトル変化では繰り返さない
+                log::info!("Distraction: {}", info.url);
+                distracted_by = Some(info.url.clone());
+                act(&options.action, last_allowed.as_deref());
+                on_event(FocusGuardEvent::Distraction(info.url));
+            }
+        });
+
+        Self {
+            started: Instant::now(),
+            _watcher: watcher,
+        }
+    }
+
+    /// Time since the session started
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+#[cfg(feature = "control")]
+fn act(action: &DistractionAction, last_allowed: Option<&str>) {
+    let target = match action {
+        DistractionAction::Notify => return,
+        DistractionAction::ReturnToAllowed => match last_allowed {
+            Some(url) => url,
+            None => return,
+        },
+        DistractionAction::Redirect(url) => url.as_str(),
+    };
+    if let Err(e) = crate::control::navigate_active_tab_blocking(target) {
+        log::warn!("Could not leave the distracting page: {e}");
+    }
+}
+
+#[cfg(not(feature = "control"))]
+fn act(_action: &DistractionAction, _last_allowed: Option<&str>) {}
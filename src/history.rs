@@ -0,0 +1,423 @@
+//! Reading the browsers' own history databases.
+//!
+//! New installations of a tracking tool start without any data. The readers in
+//! this module extract past visits from the history files Chromium-based
+//! browsers and Firefox keep on disk, and [`import_history`] backfills a
+//! [`VisitStore`] with them.
+//!
+//! Browsers keep their history database locked while running, so every reader
+//! works on a temporary copy.
+//!
+//! This module is only available with the `history` feature enabled.
+//!
+//! ```rust,no_run
+//! use browser_info::history::import_history;
+//! use browser_info::storage::JsonlStore;
+//! use std::time::{Duration, SystemTime};
+//!
+//! let mut store = JsonlStore::open("visits.jsonl");
+//! let now = SystemTime::now();
+//! let report = import_history(&mut store, now - Duration::from_secs(30 * 86_400)..now)?;
+//! println!("📥 Imported {} visits ({} already tracked)", report.imported, report.duplicates);
+//! # Ok::<(), browser_info::BrowserInfoError>(())
+//! ```
+
+use crate::storage::{Visit, VisitStore};
+use crate::{BrowserInfoError, BrowserType};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One visit as recorded by the browser
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryVisit {
+    pub url: String,
+    pub title: String,
+    pub browser_type: BrowserType,
+    /// When the page was opened
+    pub time: SystemTime,
+    /// How long the page stayed open, if the browser records it
+    ///
+    /// Chromium measures the lifetime of the tab, not focus time.
+    pub duration: Option<Duration>,
+}
+
+impl HistoryVisit {
+    fn into_visit(self) -> Visit {
+        Visit::new(
+            self.url,
+            self.title,
+            self.browser_type,
+            self.time,
+            self.duration.unwrap_or_default(),
+        )
+    }
+}
+
+/// Source of past visits
+pub trait HistoryReader {
+    /// Browser the history belongs to
+    fn browser_type(&self) -> BrowserType;
+
+    /// Visits that started within `range`, oldest first
+    fn read(&self, range: Range<SystemTime>) -> Result<Vec<HistoryVisit>, BrowserInfoError>;
+}
+
+/// `History` database of Chrome, Edge, Brave, Vivaldi, Opera, ...
+#[derive(Debug, Clone)]
+pub struct ChromiumHistory {
+    browser_type: BrowserType,
+    path: PathBuf,
+}
+
+impl ChromiumHistory {
+    pub fn new(browser_type: BrowserType, path: impl Into<PathBuf>) -> Self {
+        Self {
+            browser_type,
+            path: path.into(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl HistoryReader for ChromiumHistory {
+    fn browser_type(&self) -> BrowserType {
+        self.browser_type.clone()
+    }
+
+    fn read(&self, range: Range<SystemTime>) -> Result<Vec<HistoryVisit>, BrowserInfoError> {
+        // Chromium stores microseconds since 1601-01-01 (Windows FILETIME epoch)
+        const EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+        let to_chromium = |time: SystemTime| unix_micros(time) + EPOCH_OFFSET_MICROS;
+
+        let snapshot = Snapshot::copy(&self.path)?;
+        let connection = snapshot.open()?;
+        let mut statement = connection
+            .prepare(
+                "SELECT urls.url, urls.title, visits.visit_time, visits.visit_duration
+                 FROM visits JOIN urls ON urls.id = visits.url
+                 WHERE visits.visit_time >= ?1 AND visits.visit_time < ?2
+                 ORDER BY visits.visit_time",
+            )
+            .map_err(history_error)?;
+
+        let rows = statement
+            .query_map([to_chromium(range.start), to_chromium(range.end)], |row| {
+                Ok(HistoryVisit {
+                    url: row.get(0)?,
+                    title: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    browser_type: self.browser_type.clone(),
+                    time: from_unix_micros(row.get::<_, i64>(2)? - EPOCH_OFFSET_MICROS),
+                    duration: row
+                        .get::<_, Option<i64>>(3)?
+                        .filter(|micros| *micros > 0)
+                        .map(|micros| Duration::from_micros(micros as u64)),
+                })
+            })
+            .map_err(history_error)?;
+        rows.collect::<Result<_, _>>().map_err(history_error)
+    }
+}
+
+/// `places.sqlite` of a Firefox profile
+#[derive(Debug, Clone)]
+pub struct FirefoxHistory {
+    path: PathBuf,
+}
+
+impl FirefoxHistory {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl HistoryReader for FirefoxHistory {
+    fn browser_type(&self) -> BrowserType {
+        BrowserType::Firefox
+    }
+
+    fn read(&self, range: Range<SystemTime>) -> Result<Vec<HistoryVisit>, BrowserInfoError> {
+        let snapshot = Snapshot::copy(&self.path)?;
+        let connection = snapshot.open()?;
+        let mut statement = connection
+            .prepare(
+                "SELECT moz_places.url, moz_places.title, moz_historyvisits.visit_date
+                 FROM moz_historyvisits JOIN moz_places ON moz_places.id = moz_historyvisits.place_id
+                 WHERE moz_historyvisits.visit_date >= ?1 AND moz_historyvisits.visit_date < ?2
+                 ORDER BY moz_historyvisits.visit_date",
+            )
+            .map_err(history_error)?;
+
+        // Firefox stores microseconds since the Unix epoch and no durations
+        let rows = statement
+            .query_map([unix_micros(range.start), unix_micros(range.end)], |row| {
+                Ok(HistoryVisit {
+                    url: row.get(0)?,
+                    title: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    browser_type: BrowserType::Firefox,
+                    time: from_unix_micros(row.get(2)?),
+                    duration: None,
+                })
+            })
+            .map_err(history_error)?;
+        rows.collect::<Result<_, _>>().map_err(history_error)
+    }
+}
+
+/// Readers for every default browser profile found on this machine
+pub fn installed_readers() -> Vec<Box<dyn HistoryReader>> {
+    let mut readers: Vec<Box<dyn HistoryReader>> = Vec::new();
+
+    for (browser_type, profile) in crate::profiles::chromium_profiles() {
+        let path = profile.join("History");
+        if path.is_file() {
+            readers.push(Box::new(ChromiumHistory::new(browser_type, path)));
+        }
+    }
+
+    for profiles in firefox_profile_roots() {
+        let Ok(entries) = std::fs::read_dir(profiles) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path().join("places.sqlite");
+            if path.is_file() {
+                readers.push(Box::new(FirefoxHistory::new(path)));
+            }
+        }
+    }
+
+    readers
+}
+
+/// Outcome of [`import_history`]
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Visits appended to the store
+    pub imported: usize,
+    /// Visits skipped because the store already had them
+    pub duplicates: usize,
+    /// Readers that could not be read, e.g. a profile deleted meanwhile; the
+    /// other readers' visits were imported regardless
+    pub failed: Vec<(BrowserType, BrowserInfoError)>,
+}
+
+/// Backfill `store` with the history of all installed browsers
+///
+/// Equivalent to [`import_history_from`] with [`installed_readers`].
+pub fn import_history(
+    store: &mut dyn VisitStore,
+    range: Range<SystemTime>,
+) -> Result<ImportReport, BrowserInfoError> {
+    import_history_from(store, &installed_readers(), range)
+}
+
+/// Backfill `store` with visits from `readers` that started within `range`
+///
+/// A history entry counts as already tracked when the store has a visit of
+/// the same URL starting within a few seconds of it (or overlapping it), so
+/// running the import twice, or after live tracking, adds nothing twice.
+///
+/// A reader that fails is recorded in [`ImportReport::failed`] and the import
+/// goes on with the next one. Only an error of the store itself ends the
+/// import early, in which case the visits appended before it stay in the
+/// store; running the import again completes it without duplicates.
+pub fn import_history_from(
+    store: &mut dyn VisitStore,
+    readers: &[Box<dyn HistoryReader>],
+    range: Range<SystemTime>,
+) -> Result<ImportReport, BrowserInfoError> {
+    // 同じURLの訪問とだけ比べる
+    let mut known: HashMap<String, Vec<Visit>> = HashMap::new();
+    for visit in store.visits(range.clone())? {
+        known.entry(visit.url.clone()).or_default().push(visit);
+    }
+    let mut report = ImportReport::default();
+
+    for reader in readers {
+        let entries = match reader.read(range.clone()) {
+            Ok(entries) => entries,
+            Err(e) => {
+                report.failed.push((reader.browser_type(), e));
+                continue;
+            }
+        };
+        for entry in entries {
+            let visit = entry.into_visit();
+            let same_url = known.entry(visit.url.clone()).or_default();
+            if same_url
+                .iter()
+                .any(|existing| is_same_visit(existing, &visit))
+            {
+                report.duplicates += 1;
+                continue;
+            }
+            store.append(&visit)?;
+            same_url.push(visit);
+            report.imported += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+fn is_same_visit(existing: &Visit, imported: &Visit) -> bool {
+    const TOLERANCE: Duration = Duration::from_secs(5);
+
+    if existing.url != imported.url {
+        return false;
+    }
+    let (earlier, later) = if existing.start <= imported.start {
+        (existing, imported)
+    } else {
+        (imported, existing)
+    };
+    later.start < earlier.end() + TOLERANCE
+}
+
+/// Temporary copy of a (possibly locked) database, removed on drop
+///
+/// Recent changes may still sit in the `-wal` (Firefox) or `-journal`
+/// (Chromium) file next to the database, so those are copied along; SQLite
+/// applies them when the copy is opened.
+struct Snapshot {
+    path: PathBuf,
+}
+
+/// Files SQLite keeps next to a database; `-shm` is created when a copied WAL is opened
+const SIDECARS: [&str; 3] = ["-wal", "-journal", "-shm"];
+
+impl Snapshot {
+    fn copy(source: &Path) -> Result<Self, BrowserInfoError> {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        let snapshot = Self {
+            path: std::env::temp_dir().join(format!(
+                "browser-info-history-{}-{}.sqlite",
+                std::process::id(),
+                NEXT_ID.fetch_add(1, Ordering::Relaxed)
+            )),
+        };
+        let copy = |from: &Path, to: &Path| {
+            std::fs::copy(from, to).map_err(|e| {
+                BrowserInfoError::StorageError(format!("Cannot copy {}: {e}", from.display()))
+            })
+        };
+        copy(source, &snapshot.path)?;
+        for suffix in ["-wal", "-journal"] {
+            let sidecar = with_suffix(source, suffix);
+            if sidecar.is_file() {
+                copy(&sidecar, &with_suffix(&snapshot.path, suffix))?;
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Opened writable: replaying the WAL or rolling back a journal writes to
+    /// the copy (never to the browser's file)
+    fn open(&self) -> Result<Connection, BrowserInfoError> {
+        Connection::open_with_flags(&self.path, OpenFlags::SQLITE_OPEN_READ_WRITE)
+            .map_err(history_error)
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+        for suffix in SIDECARS {
+            let _ = std::fs::remove_file(with_suffix(&self.path, suffix));
+        }
+    }
+}
+
+/// `History` → `History-wal`
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn firefox_profile_roots() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let root = std::env::var_os("APPDATA")
+        .map(PathBuf::from)
+        .map(|appdata| appdata.join(r"Mozilla\Firefox\Profiles"));
+    #[cfg(target_os = "macos")]
+    let root = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .map(|home| home.join("Library/Application Support/Firefox/Profiles"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let root = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .map(|home| home.join(".mozilla/firefox"));
+
+    root.into_iter().collect()
+}
+
+fn unix_micros(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_micros() as i64,
+        Err(e) => -(e.duration().as_micros() as i64),
+    }
+}
+
+fn from_unix_micros(micros: i64) -> SystemTime {
+    if micros >= 0 {
+        UNIX_EPOCH + Duration::from_micros(micros as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_micros(micros.unsigned_abs())
+    }
+}
+
+fn history_error(e: rusqlite::Error) -> BrowserInfoError {
+    BrowserInfoError::StorageError(format!("History database: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_includes_changes_still_in_the_wal() {
+        let dir = std::env::temp_dir().join(format!("browser-info-wal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("places.sqlite");
+
+        // ブラウザが開いたままの状態: 変更はまだ WAL にしかない
+        let browser = Connection::open(&source).unwrap();
+        browser
+            .execute_batch(
+                "PRAGMA journal_mode = WAL; PRAGMA wal_autocheckpoint = 0;
+                 CREATE TABLE visits (url TEXT);
+                 INSERT INTO visits VALUES ('https://example.com/');",
+            )
+            .unwrap();
+        assert!(with_suffix(&source, "-wal").is_file());
+
+        let snapshot = Snapshot::copy(&source).unwrap();
+        let copied = snapshot.path.clone();
+        let url: String = snapshot
+            .open()
+            .unwrap()
+            .query_row("SELECT url FROM visits", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(url, "https://example.com/");
+
+        drop(snapshot);
+        for suffix in ["", "-wal", "-journal", "-shm"] {
+            assert!(!with_suffix(&copied, suffix).exists());
+        }
+        drop(browser);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,77 @@
+// ================================================================================================
+// Browser-internal pages - ブラウザ内部ページの分類
+// ================================================================================================
+
+use crate::BrowserInfo;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Browser-internal page (`chrome://settings`, `about:addons`, ...) by purpose
+///
+/// The same page has a different URL in every browser; grouping by this enum
+/// keeps analytics from treating `chrome://settings/privacy` and
+/// `edge://settings/privacy` as unrelated sites.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum InternalPage {
+    Settings,
+    Extensions,
+    History,
+    NewTab,
+    Downloads,
+    Bookmarks,
+    /// Experimental flags (`chrome://flags`, `about:config`)
+    Flags,
+    /// Any other internal page, by its lowercase name (e.g. `"gpu"`, `"about"`)
+    Other(String),
+}
+
+/// Schemes of browser-internal pages
+const INTERNAL_SCHEMES: [&str; 6] = [
+    "chrome://",
+    "edge://",
+    "brave://",
+    "opera://",
+    "vivaldi://",
+    "about:",
+];
+
+impl InternalPage {
+    /// Classify `url`; `None` for web pages
+    ///
+    /// ```rust
+    /// use browser_info::InternalPage;
+    ///
+    /// assert_eq!(InternalPage::from_url("edge://settings/privacy"), Some(InternalPage::Settings));
+    /// assert_eq!(InternalPage::from_url("about:addons"), Some(InternalPage::Extensions));
+    /// assert_eq!(InternalPage::from_url("chrome://gpu"), Some(InternalPage::Other("gpu".to_string())));
+    /// assert_eq!(InternalPage::from_url("https://example.com/settings"), None);
+    /// ```
+    pub fn from_url(url: &str) -> Option<Self> {
+        let url = url.trim().to_lowercase();
+        let rest = INTERNAL_SCHEMES
+            .iter()
+            .find_map(|scheme| url.strip_prefix(scheme))?;
+        let name = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+
+        Some(match name {
+            "settings" | "preferences" => InternalPage::Settings,
+            "extensions" | "addons" => InternalPage::Extensions,
+            "history" => InternalPage::History,
+            "newtab" | "new-tab-page" | "home" | "startpage" => InternalPage::NewTab,
+            "downloads" => InternalPage::Downloads,
+            "bookmarks" | "favorites" => InternalPage::Bookmarks,
+            "flags" | "config" | "experiments" => InternalPage::Flags,
+            "" => return None,
+            other => InternalPage::Other(other.to_string()),
+        })
+    }
+}
+
+impl BrowserInfo {
+    /// Which internal page is shown, `None` for regular web pages
+    pub fn internal_page(&self) -> Option<InternalPage> {
+        InternalPage::from_url(&self.url)
+    }
+}
@@ -24,22 +24,116 @@
 // Import Section
 //================================================================================================
 
-use active_win_pos_rs::get_active_window;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
+pub mod alerts;
+pub mod appearance;
+pub mod assistive;
+pub mod audio;
+pub mod audit;
+mod blocking;
 pub mod browser_detection;
+#[cfg(feature = "storage")]
+pub mod budget;
+pub mod cancel;
+pub mod capabilities;
+pub mod config;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+#[cfg(feature = "control")]
+pub mod control;
+pub mod dialog;
+mod display;
 pub mod error;
+#[cfg(feature = "stream")]
+pub mod events;
+pub mod extension;
+pub mod focus;
+pub mod focus_guard;
+#[cfg(feature = "history")]
+pub mod history;
+pub mod internal_page;
+pub mod lock_screen;
+#[cfg(feature = "raw-handles")]
+pub mod native_handle;
+pub mod overview;
+#[cfg(feature = "perf")]
+pub mod perf;
+pub mod performance;
+pub mod policy;
+mod process_runner;
+#[cfg(feature = "serde")]
+mod profiles;
+pub mod resources;
+pub mod responsiveness;
+#[cfg(feature = "devtools")]
+mod runtime;
+pub mod schedule;
+mod script_error;
+pub mod security;
+#[cfg(feature = "serde")]
+pub mod serde_time;
+pub mod session;
+pub mod shortcut;
+pub mod site_heuristics;
+#[cfg(feature = "storage")]
+pub mod storage;
+pub mod strategy;
+pub mod tabs;
+#[cfg(any(target_os = "windows", target_os = "macos", test))]
+mod temp_script;
+pub mod thumbnail;
 pub mod url_extraction;
+pub mod verify;
+pub mod watcher;
+pub mod window_kind;
+pub mod window_list;
+pub mod window_provider;
 
 pub mod platform;
 
+pub use appearance::ColorScheme;
+pub use audio::BrowserAudioState;
+pub use cancel::CancellationToken;
+pub use capabilities::{
+    BackendStatus, BrowserCapabilities, CapabilityReport, EventCapability, UrlCapability,
+    probe_capabilities,
+};
+pub use config::{ExtractionConfig, Preset, UrlPrivacy};
+pub use dialog::DialogKind;
 pub use error::BrowserInfoError;
+pub use extension::ExtensionContext;
+pub use focus::BrowserFocusTarget;
+pub use internal_page::InternalPage;
+#[cfg(feature = "raw-handles")]
+pub use native_handle::NativeWindowHandle;
+pub use overview::{BrowserOverview, get_browser_overview};
+pub use performance::PagePerformance;
+pub use process_runner::{ProcessLimits, process_limits, set_process_limits};
+pub use security::{CertificateInfo, SecurityState};
+pub use session::BrowserInfoSession;
+pub use strategy::StrategyOrder;
+pub use tabs::{
+    DuplicateTabs, TabInfo, TabPage, TabQuery, find_duplicate_tabs, get_all_tabs, get_tabs,
+};
+pub use window_list::{BrowserWindowInfo, get_all_browser_windows};
+
+pub use url_extraction::{
+    AttemptOutcome, ExtractionAttempt, NativeExtractor, UrlConfidence, UrlExtractor, UrlSource,
+};
 
 #[cfg(any(
     all(feature = "devtools", target_os = "windows"),
     all(doc, feature = "devtools")
 ))]
-pub use platform::chrome_devtools::ChromeDevToolsExtractor;
+pub use platform::chrome_devtools::{
+    ChromeDevToolsExtractor, DevToolsBrowser, DevToolsConnectionStats, DevToolsEndpoint,
+    DevToolsProxy, DevToolsScan, PortResult, devtools_connection_stats, devtools_endpoint,
+    reset_devtools_connections, set_devtools_endpoint,
+};
 
 //================================================================================================
 // Data Types & Module Variables
@@ -53,26 +147,168 @@ pub enum ExtractionMethod {
     DevTools,
     /// PowerShell (高速・互換性重視)
     PowerShell,
+    /// Native and DevTools at the same time; the first accurate result wins
+    /// and the other is cancelled (same as `PowerShell` without DevTools).
+    /// DevTools only counts when its page belongs to the focused browser window.
+    Race,
+    /// No keystrokes and no clipboard; see [`ExtractionConfig::passive`]
+    Passive,
 }
 
-/// [derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Information about the active browser window
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BrowserInfo {
     /// Current URL displayed in the browser
     pub url: String,
     pub title: String,
     pub browser_name: String,
     pub browser_type: BrowserType,
+    /// Browser version; natively extracted pages report it only with
+    /// [`ExtractionConfig::detect_metadata`]
     pub version: Option<String>,
+    /// Open tabs of the browser; natively extracted pages report it only with
+    /// [`ExtractionConfig::detect_metadata`]
     pub tabs_count: Option<u32>,
     pub is_incognito: bool,
     /// Process ID
     pub process_id: u64,
     /// Window position and size
     pub window_position: WindowPosition,
+    /// Minimized / maximized / fullscreen; only reported through DevTools
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub window_state: Option<WindowState>,
+    /// Method that produced `url`; `None` when no URL was extracted.
+    /// [`UrlSource::TitleHeuristic`] URLs are guesses and deserve less trust
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub url_source: Option<UrlSource>,
+    /// How far `url` can be trusted; `None` when no URL was extracted
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub confidence: Option<UrlConfidence>,
+    /// Extraction methods tried, in order; the successful one comes last
+    pub attempts: Vec<ExtractionAttempt>,
+    /// Keyboard focus inside the window before extraction, when
+    /// [`ExtractionConfig::detect_focus_target`] is enabled
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub focus_target: Option<BrowserFocusTarget>,
+    /// Page language (BCP 47, e.g. `en-US`), when
+    /// [`ExtractionConfig::detect_page_language`] is enabled
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub page_language: Option<String>,
+    /// Connection security of the page; `None` when the URL was only guessed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub security_state: Option<SecurityState>,
+    /// Audio output of the browser, when [`ExtractionConfig::detect_audio`] is enabled
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub audio: Option<BrowserAudioState>,
+    /// Kind of the focused window; only [`BrowserWindowKind::Normal`] windows have a URL
+    /// of their own (a picture-in-picture window reports its source tab's, when known)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub window_kind: BrowserWindowKind,
+    /// Native dialog open over the browser; extraction is skipped while one is shown
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub blocking_dialog: Option<DialogKind>,
+    /// Load timings of the page, when [`ExtractionConfig::detect_performance`] is
+    /// enabled and the page came through DevTools
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub performance: Option<PagePerformance>,
+    /// The title names a different site than the URL (see
+    /// [`site_heuristics::title_matches_url`]); the URL may belong to another
+    /// window, e.g. after a clipboard race, and should be treated as low confidence
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub title_mismatch: bool,
+    /// Server certificate of the page, when the page came through DevTools
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub certificate: Option<CertificateInfo>,
+    /// The browser did not answer the responsiveness probe; nothing was
+    /// extracted and `url` is empty
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub not_responding: bool,
+    /// Native id of the browser window (`raw-handles` feature); `None` when
+    /// the page came through DevTools alone or the platform has no window ids
+    #[cfg(feature = "raw-handles")]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub native_handle: Option<NativeWindowHandle>,
+}
+
+#[cfg(feature = "serde")]
+impl BrowserInfo {
+    /// Version of the [`to_json`](Self::to_json) layout, written as `format_version`
+    ///
+    /// Bumped only when a field is renamed, retyped or removed; added fields
+    /// keep the version, and older readers ignore them.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Serialize for persisting, tagged with [`FORMAT_VERSION`](Self::FORMAT_VERSION)
+    pub fn to_json(&self) -> Result<String, BrowserInfoError> {
+        let mut value =
+            serde_json::to_value(self).map_err(|e| BrowserInfoError::ParseError(e.to_string()))?;
+        if let serde_json::Value::Object(fields) = &mut value {
+            fields.insert("format_version".to_string(), Self::FORMAT_VERSION.into());
+        }
+        Ok(value.to_string())
+    }
+
+    /// Read JSON written by [`to_json`](Self::to_json) (or a plain serde dump)
+    ///
+    /// Fields added after the JSON was written take their defaults; JSON from
+    /// a newer format version is rejected instead of being misread.
+    ///
+    /// ```rust
+    /// use browser_info::BrowserInfo;
+    ///
+    /// let info = BrowserInfo::from_json(
+    ///     r#"{"format_version": 1, "url": "https://docs.rs/", "title": "Docs.rs",
+    ///         "browser_name": "firefox", "browser_type": "Firefox", "version": null,
+    ///         "tabs_count": null, "is_incognito": false, "process_id": 4242,
+    ///         "window_position": {"x": 0.0, "y": 0.0, "width": 1280.0, "height": 800.0},
+    ///         "attempts": []}"#,
+    /// )?;
+    /// assert_eq!(info.url, "https://docs.rs/");
+    /// assert_eq!(BrowserInfo::from_json(&info.to_json()?)?, info);
+    /// # Ok::<(), browser_info::BrowserInfoError>(())
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, BrowserInfoError> {
+        let parse_error = |e: serde_json::Error| BrowserInfoError::ParseError(e.to_string());
+        let mut value: serde_json::Value = serde_json::from_str(json).map_err(parse_error)?;
+        let version = value
+            .as_object_mut()
+            .and_then(|fields| fields.remove("format_version"))
+            .and_then(|version| version.as_u64())
+            .unwrap_or(1);
+        if version > u64::from(Self::FORMAT_VERSION) {
+            return Err(BrowserInfoError::ParseError(format!(
+                "BrowserInfo format version {version} is newer than supported ({})",
+                Self::FORMAT_VERSION
+            )));
+        }
+        serde_json::from_value(value).map_err(parse_error)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl BrowserInfo {
+    /// JSON Schema describing the serialized form of [`BrowserInfo`]
+    ///
+    /// The schema is stable within a minor version: fields are only added
+    /// (never renamed, retyped or removed) in patch releases. The current
+    /// schema is committed as `schema/browser_info.schema.json`.
+    ///
+    /// ```rust
+    /// let schema = browser_info::BrowserInfo::json_schema();
+    /// let json = serde_json::to_value(&schema).unwrap();
+    /// assert_eq!(json["title"], "BrowserInfo");
+    /// ```
+    pub fn json_schema() -> schemars::Schema {
+        schemars::schema_for!(BrowserInfo)
+    }
 }
 
 /// Browser type classification
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum BrowserType {
     Chrome,
     Firefox,
@@ -81,11 +317,40 @@ pub enum BrowserType {
     Brave,
     Opera,
     Vivaldi,
+    /// Tor Browser (Firefox-based, URL extraction refused by default)
+    Tor,
+    /// WebView2 (msedgewebview2.exe) hosted inside another application
+    Embedded,
     Unknown(String),
 }
 
+/// Kind of browser window that has the focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum BrowserWindowKind {
+    /// Regular window with tabs and an address bar
+    #[default]
+    Normal,
+    /// Floating picture-in-picture video window
+    PictureInPicture,
+    /// Detached developer tools window
+    DevTools,
+    /// Extension popup or extension-owned window
+    ExtensionPopup,
+}
+
+impl BrowserWindowKind {
+    /// Whether the window has an address bar to read the URL from
+    pub fn has_address_bar(&self) -> bool {
+        matches!(self, BrowserWindowKind::Normal)
+    }
+}
+
 /// Window position and dimensions
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct WindowPosition {
     pub x: f64,
     pub y: f64,
@@ -93,6 +358,70 @@ pub struct WindowPosition {
     pub height: f64,
 }
 
+impl WindowPosition {
+    pub const fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// Show state of a browser window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum WindowState {
+    #[default]
+    Normal,
+    Minimized,
+    Maximized,
+    Fullscreen,
+}
+
+/// The focused window as reported by the window provider
+///
+/// Mirrors `active_win_pos_rs::ActiveWindow`, so the public API does not tie
+/// callers to the active-win-pos-rs version this crate was built with.
+/// Convert with `From` when calling in with a window obtained elsewhere.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WindowHandleInfo {
+    pub title: String,
+    pub app_name: String,
+    pub process_path: PathBuf,
+    pub process_id: u64,
+    /// Platform window id as text: `HWND(<handle>)` on Windows, the window
+    /// number on macOS, the X11 resource id on Linux
+    pub window_id: String,
+    pub position: WindowPosition,
+}
+
+impl From<active_win_pos_rs::ActiveWindow> for WindowHandleInfo {
+    fn from(window: active_win_pos_rs::ActiveWindow) -> Self {
+        Self {
+            title: window.title,
+            app_name: window.app_name,
+            process_path: window.process_path,
+            process_id: window.process_id,
+            window_id: window.window_id,
+            position: WindowPosition::new(
+                window.position.x,
+                window.position.y,
+                window.position.width,
+                window.position.height,
+            ),
+        }
+    }
+}
+
+impl From<&active_win_pos_rs::ActiveWindow> for WindowHandleInfo {
+    fn from(window: &active_win_pos_rs::ActiveWindow) -> Self {
+        window.clone().into()
+    }
+}
+
 //================================================================================================
 // procedure
 //================================================================================================
@@ -116,31 +445,160 @@ pub struct WindowPosition {
 /// }
 /// ```
 pub fn get_active_browser_info() -> Result<BrowserInfo, BrowserInfoError> {
-    // Step 0: Check if the active window is browser
-    if !is_browser_active() {
-        return Err(BrowserInfoError::NotABrowser);
+    get_active_browser_info_with_config(&ExtractionConfig::default())
+}
+
+/// [`get_active_browser_info`] with explicit [`ExtractionConfig`]
+pub fn get_active_browser_info_with_config(
+    config: &ExtractionConfig,
+) -> Result<BrowserInfo, BrowserInfoError> {
+    let config = &config.effective();
+
+    within_timeout(config, || {
+        // Step 1-2: Get the active window once and verify it's a browser window
+        let (window, browser_type) = active_browser_window(config)?;
+
+        extract_from_window(window, browser_type, config)
+    })
+}
+
+/// Steps 2.5 to 5 of [`get_active_browser_info_with_config`], for a window
+/// that is already classified (also used by [`BrowserInfoSession`])
+pub(crate) fn extract_from_window(
+    window: WindowHandleInfo,
+    browser_type: BrowserType,
+    config: &ExtractionConfig,
+) -> Result<BrowserInfo, BrowserInfoError> {
+    // Step 2.5: Every query below would block on a hung browser
+    if !responsiveness::is_responding(&window) {
+        return not_responding_info(window, browser_type, config);
     }
 
-    // Step 1: Definitely browser. Get active window using active-win-pos-rs
-    let window = get_active_window().map_err(|_| BrowserInfoError::WindowNotFound)?;
+    // Step 3: Where the user is typing (must be read before Ctrl+L moves the focus)
+    let focus_target = config
+        .detect_focus_target
+        .then(focus::focus_target)
+        .flatten();
 
-    // Step 2: Verify it's a browser window
-    let browser_type = browser_detection::classify_browser(&window)?;
+    // Step 4: Extract URL using platform-specific methods
+    //         (PiP, DevTools and extension popups have no address bar; sending keys could break them)
+    let window_kind = browser_detection::classify_window_kind(&window);
+    let blocking_dialog = dialog::blocking_dialog(&window);
+    let mut attempts = Vec::new();
+    let url = if let Some(dialog) = blocking_dialog {
+        attempts.push(skipped_dialog(dialog));
+        String::new()
+    } else if window_kind.has_address_bar() {
+        url_extraction::extract_raw_url_traced(&window, &browser_type, config, &mut attempts)?
+    } else {
+        attempts.push(skipped_window_kind(window_kind));
+        String::new()
+    };
 
-    // Step 3: Extract URL using platform-specific methods
-    let url = url_extraction::extract_url(&window, &browser_type)?;
+    // Step 5: Get additional browser metadata (evaluated lazily, only here)
+    build_browser_info(
+        window,
+        browser_type,
+        url,
+        attempts,
+        focus_target,
+        window_kind,
+        config,
+    )
+    .map(|info| BrowserInfo {
+        blocking_dialog,
+        performance: None,
+        ..info
+    })
+}
 
-    // Step 4: Get additional browser metadata
+/// `BrowserInfo` for a hung browser: window data only, nothing extracted
+fn not_responding_info(
+    window: WindowHandleInfo,
+    browser_type: BrowserType,
+    config: &ExtractionConfig,
+) -> Result<BrowserInfo, BrowserInfoError> {
+    log::info!("Browser is not responding; skipping extraction");
+    build_browser_info(
+        window,
+        browser_type,
+        String::new(),
+        vec![url_extraction::skipped_not_responding()],
+        None,
+        BrowserWindowKind::Normal,
+        config,
+    )
+    .map(|info| BrowserInfo {
+        not_responding: true,
+        ..info
+    })
+}
+
+fn skipped_dialog(dialog: DialogKind) -> ExtractionAttempt {
+    ExtractionAttempt {
+        source: UrlSource::Keyboard,
+        outcome: AttemptOutcome::Skipped(format!("{dialog:?} dialog is open")),
+    }
+}
+
+fn skipped_window_kind(window_kind: BrowserWindowKind) -> ExtractionAttempt {
+    ExtractionAttempt {
+        source: UrlSource::Keyboard,
+        outcome: AttemptOutcome::Skipped(format!("{window_kind:?} window has no address bar")),
+    }
+}
+
+/// Assemble a `BrowserInfo` from an already classified window
+fn build_browser_info(
+    window: WindowHandleInfo,
+    browser_type: BrowserType,
+    url: String,
+    attempts: Vec<ExtractionAttempt>,
+    focus_target: Option<BrowserFocusTarget>,
+    window_kind: BrowserWindowKind,
+    config: &ExtractionConfig,
+) -> Result<BrowserInfo, BrowserInfoError> {
     let metadata = browser_detection::get_browser_metadata(&window, &browser_type)?;
+    let page_language = config
+        .detect_page_language
+        .then(|| site_heuristics::language_from_title(&window.title))
+        .flatten()
+        .map(str::to_string);
+    let url_source = url_extraction::successful_source(&attempts);
+    let confidence = UrlConfidence::of(url_source, &url, config.url_privacy);
+    // A URL guessed from the title says nothing about the real connection
+    let url_is_accurate = url_source.is_none_or(|source| source.is_accurate());
+    // Neither does one whose scheme the address bar hid
+    let security_state = (url_is_accurate && url_extraction::has_scheme(&url))
+        .then(|| SecurityState::from_url(&url));
+    // 別ウィンドウのURLをクリップボード経由で拾っていないか
+    let title_mismatch =
+        url_is_accurate && site_heuristics::title_matches_url(&window.title, &url) == Some(false);
+    if title_mismatch {
+        log::warn!("URL does not match the window title; low confidence");
+    }
+    let audio = config
+        .detect_audio
+        .then(|| audio::audio_state(&window))
+        .flatten();
+    #[cfg(feature = "raw-handles")]
+    let native_handle = window.native_handle();
 
     Ok(BrowserInfo {
-        url,
+        url: config.url_privacy.apply(&url),
+        version: config
+            .detect_metadata
+            .then(|| metadata.version().map(str::to_string))
+            .flatten(),
+        tabs_count: config
+            .detect_metadata
+            .then(|| metadata.tabs_count())
+            .flatten(),
+        // タイトルを見るだけなので常に求める
+        is_incognito: metadata.is_incognito(),
         title: window.title,
         browser_name: window.app_name,
         browser_type,
-        version: metadata.version,
-        tabs_count: metadata.tabs_count,
-        is_incognito: metadata.is_incognito,
         process_id: window.process_id,
         window_position: WindowPosition {
             x: window.position.x,
@@ -148,29 +606,123 @@ pub fn get_active_browser_info() -> Result<BrowserInfo, BrowserInfoError> {
             width: window.position.width,
             height: window.position.height,
         },
+        window_state: None,
+        url_source,
+        confidence,
+        attempts,
+        focus_target,
+        page_language,
+        security_state,
+        audio,
+        window_kind,
+        blocking_dialog: None,
+        performance: None,
+        title_mismatch,
+        certificate: None,
+        not_responding: false,
+        #[cfg(feature = "raw-handles")]
+        native_handle,
     })
 }
 
+/// [`get_active_browser_info`] that fails with [`BrowserInfoError::Timeout`] after `timeout`
+///
+/// Every PowerShell / osascript / xprop child started for this call is killed
+/// when the deadline passes, and no new one is started after it, so a stuck
+/// script cannot hold the caller up. In-process steps between children (the
+/// window lookup, classification) are not interrupted and may overrun the
+/// deadline by their own, short, duration.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// match browser_info::get_active_browser_info_with_timeout(Duration::from_millis(800)) {
+///     Ok(info) => println!("{info}"),
+///     Err(browser_info::BrowserInfoError::Timeout) => println!("⏱️ too slow, skipping this tick"),
+///     Err(e) => println!("{e}"),
+/// }
+/// ```
+pub fn get_active_browser_info_with_timeout(
+    timeout: Duration,
+) -> Result<BrowserInfo, BrowserInfoError> {
+    get_active_browser_info_with_config(&ExtractionConfig::default().timeout(timeout))
+}
+
+/// Run `f` under [`ExtractionConfig::timeout`], if one is set
+pub(crate) fn within_timeout<T>(
+    config: &ExtractionConfig,
+    f: impl FnOnce() -> Result<T, BrowserInfoError>,
+) -> Result<T, BrowserInfoError> {
+    let Some(timeout) = config.timeout else {
+        return f();
+    };
+    let deadline = Instant::now() + timeout;
+    let result = process_runner::with_deadline(deadline, f);
+    // 子プロセスが打ち切られた後はタイトル推測の結果しか残らないので捨てる
+    if Instant::now() >= deadline {
+        return Err(BrowserInfoError::Timeout);
+    }
+    result
+}
+
 /// Get only the URL from the active browser (lightweight version)
 pub fn get_active_browser_url() -> Result<String, BrowserInfoError> {
-    // Step 0: 高速事前チェック
-    if !is_browser_active() {
-        return Err(BrowserInfoError::NotABrowser);
-    }
+    get_active_browser_url_with_config(&ExtractionConfig::default())
+}
+
+/// [`get_active_browser_url`] with explicit [`ExtractionConfig`]
+pub fn get_active_browser_url_with_config(
+    config: &ExtractionConfig,
+) -> Result<String, BrowserInfoError> {
+    let config = &config.effective();
 
-    let window = get_active_window().map_err(|_| BrowserInfoError::WindowNotFound)?;
+    within_timeout(config, || {
+        let (window, browser_type) = active_browser_window(config)?;
+        url_extraction::extract_url_traced(&window, &browser_type, config, &mut Vec::new())
+    })
+}
 
-    let browser_type = browser_detection::classify_browser(&window)?;
-    url_extraction::extract_url(&window, &browser_type)
+/// Cargo features this build of the crate was compiled with
+///
+/// Useful for diagnostics in applications shipping the minimal build
+/// (`default-features = false`).
+pub fn build_features() -> Vec<&'static str> {
+    [
+        ("devtools", cfg!(feature = "devtools")),
+        ("serde", cfg!(feature = "serde")),
+        ("schemars", cfg!(feature = "schemars")),
+        ("conformance", cfg!(feature = "conformance")),
+        ("control", cfg!(feature = "control")),
+        ("perf", cfg!(feature = "perf")),
+        ("storage", cfg!(feature = "storage")),
+        ("history", cfg!(feature = "history")),
+        ("webhook", cfg!(feature = "webhook")),
+        ("raw-handles", cfg!(feature = "raw-handles")),
+        ("stream", cfg!(feature = "stream")),
+        ("sync-only", cfg!(feature = "sync-only")),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(name, _)| name)
+    .collect()
 }
 
 /// Check if the currently active window is a browser
 pub fn is_browser_active() -> bool {
-    if let Ok(window) = get_active_window() {
-        browser_detection::classify_browser(&window).is_ok()
-    } else {
-        false
-    }
+    active_browser_window(&ExtractionConfig::default()).is_ok()
+}
+
+/// The focused window and its browser type, fetched once per request
+///
+/// A window that cannot be read counts as "not a browser", as it always has
+/// for the synchronous API.
+fn active_browser_window(
+    config: &ExtractionConfig,
+) -> Result<(WindowHandleInfo, BrowserType), BrowserInfoError> {
+    let window =
+        window_provider::active_window_for(config).map_err(|_| BrowserInfoError::NotABrowser)?;
+    let browser_type = browser_detection::classify_browser_with_config(&window, config)?;
+    Ok((window, browser_type))
 }
 
 /// 高速・互換性重視（PowerShell方式）
@@ -184,7 +736,13 @@ pub fn get_browser_info_safe() -> Result<BrowserInfo, BrowserInfoError> {
     all(doc, feature = "devtools")
 ))]
 pub async fn get_browser_info_detailed() -> Result<BrowserInfo, BrowserInfoError> {
-    ChromeDevToolsExtractor::extract_browser_info().await
+    let config = ExtractionConfig::default().effective();
+    let mut info = runtime::on_tokio(ChromeDevToolsExtractor::extract_browser_info()).await?;
+    // マシンポリシー（無効化されたブラウザ・URLプライバシー）はここでも効かせる
+    config.check_browser(&info.browser_type)?;
+    info.confidence = UrlConfidence::of(info.url_source, &info.url, config.url_privacy);
+    info.url = config.url_privacy.apply(&info.url);
+    Ok(info)
 }
 
 /// 後方互換性のためのエイリアス
@@ -196,29 +754,315 @@ pub async fn get_browser_info_fast() -> Result<BrowserInfo, BrowserInfoError> {
     get_browser_info_detailed().await
 }
 
-/// デフォルト（自動判定・推奨）- PowerShell優先
+/// デフォルト（自動判定・推奨）
+///
+/// Methods are probed from cheap-and-accurate to heuristic:
+/// 1. native address-bar reads (PowerShell / AppleScript)
+/// 2. Chrome DevTools, when a debugging port is reachable
+/// 3. title-based guessing, only if everything above failed
+///
+/// The order actually taken is recorded in [`BrowserInfo::attempts`].
 pub async fn get_browser_info() -> Result<BrowserInfo, BrowserInfoError> {
-    // 1. PowerShell方式を最優先（高速・確実）
-    match get_browser_info_safe() {
-        Ok(info) => {
-            println!("✅ Using PowerShell method (fastest)");
+    get_browser_info_with_config(&ExtractionConfig::default()).await
+}
+
+/// [`get_browser_info`] with explicit [`ExtractionConfig`]
+pub async fn get_browser_info_with_config(
+    config: &ExtractionConfig,
+) -> Result<BrowserInfo, BrowserInfoError> {
+    let config = &config.effective();
+    let (window, browser_type) = active_browser_window(config)?;
+    extract_from_window_async(window, browser_type, config).await
+}
+
+/// [`get_browser_info_with_config`] for a window that is already classified
+pub(crate) async fn extract_from_window_async(
+    window: WindowHandleInfo,
+    browser_type: BrowserType,
+    config: &ExtractionConfig,
+) -> Result<BrowserInfo, BrowserInfoError> {
+    config.check_browser(&browser_type)?;
+    let deadline = config.timeout.map(|timeout| Instant::now() + timeout);
+    let timed_out = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+    if !responsiveness::is_responding(&window) {
+        return not_responding_info(window, browser_type, config);
+    }
+    let focus_target = config
+        .detect_focus_target
+        .then(focus::focus_target)
+        .flatten();
+    let window_kind = browser_detection::classify_window_kind(&window);
+    let mut attempts = Vec::new();
+
+    // 0. 認証ダイアログ等にキー入力を送らない
+    if let Some(dialog) = dialog::blocking_dialog(&window) {
+        attempts.push(skipped_dialog(dialog));
+        return build_browser_info(
+            window,
+            browser_type,
+            String::new(),
+            attempts,
+            focus_target,
+            window_kind,
+            config,
+        )
+        .map(|info| BrowserInfo {
+            blocking_dialog: Some(dialog),
+            performance: None,
+            title_mismatch: false,
+            certificate: None,
+            ..info
+        });
+    }
+
+    //    PiPウィンドウはアドレスバーを持たない。CDPが使えれば元のタブを特定する
+    if !window_kind.has_address_bar() {
+        attempts.push(skipped_window_kind(window_kind));
+        #[allow(unused_mut)]
+        let mut info = build_browser_info(
+            window,
+            browser_type,
+            String::new(),
+            attempts,
+            focus_target,
+            window_kind,
+            config,
+        )?;
+
+        #[cfg(all(feature = "devtools", target_os = "windows"))]
+        if window_kind == BrowserWindowKind::PictureInPicture
+            && let Ok(Some((url, title))) =
+                runtime::on_tokio(ChromeDevToolsExtractor::picture_in_picture_tab_on_port(
+                    ChromeDevToolsExtractor::DEFAULT_PORT,
+                ))
+                .await
+        {
+            info.security_state = Some(SecurityState::from_url(&url));
+            info.url = config.url_privacy.apply(&url);
+            info.title = title;
+            info.url_source = Some(UrlSource::DevTools);
+            // 元タブの URL であってこのウィンドウ自身のものではない
+            info.confidence = Some(UrlConfidence::Derived);
+            info.attempts.push(ExtractionAttempt {
+                source: UrlSource::DevTools,
+                outcome: AttemptOutcome::Success,
+            });
+        }
+        return Ok(info);
+    }
+
+    // 1. ネイティブ方式を最優先（高速・確実）
+    //    ただし統計上 DevTools の方が速く確実なら先に試す (strategy module)
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    let devtools_first = match config.strategy_order {
+        StrategyOrder::Learned => strategy::prefers_devtools(&browser_type),
+        StrategyOrder::NativeFirst => false,
+        StrategyOrder::DevToolsFirst => true,
+    };
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    if devtools_first {
+        log::debug!("Trying DevTools first ({:?})", config.strategy_order);
+        if let Some(info) =
+            try_devtools(&window, &browser_type, &mut attempts, focus_target, config).await
+        {
             return Ok(info);
         }
-        Err(e) => {
-            println!("⚠️ PowerShell failed: {e}, trying DevTools...");
+    }
+
+    let native = match deadline {
+        Some(deadline) => process_runner::with_deadline(deadline, || {
+            url_extraction::extract_raw_url_native(&window, &browser_type, config, &mut attempts)
+        }),
+        None => {
+            url_extraction::extract_raw_url_native(&window, &browser_type, config, &mut attempts)
         }
+    };
+    if timed_out() {
+        return Err(BrowserInfoError::Timeout);
+    }
+    if let Ok(url) = native {
+        log::debug!("Using native method (fastest)");
+        return build_browser_info(
+            window,
+            browser_type,
+            url,
+            attempts,
+            focus_target,
+            window_kind,
+            config,
+        );
     }
 
-    // 2. PowerShell失敗時のみDevTools
+    // 2. 推測より先にDevToolsを試す
     #[cfg(all(feature = "devtools", target_os = "windows"))]
-    if ChromeDevToolsExtractor::is_available().await {
-        println!("🔄 Fallback to Chrome DevTools Protocol");
-        return ChromeDevToolsExtractor::extract_browser_info().await;
+    if !devtools_first
+        && let Some(info) =
+            try_devtools(&window, &browser_type, &mut attempts, focus_target, config).await
+    {
+        return Ok(info);
+    }
+
+    #[cfg(not(all(feature = "devtools", target_os = "windows")))]
+    attempts.push(ExtractionAttempt {
+        source: UrlSource::DevTools,
+        outcome: AttemptOutcome::Skipped("DevTools not available on this platform".to_string()),
+    });
+
+    // 3. タイトル推測は最終手段 (WebView2はページタイトルを持たない)
+    if timed_out() {
+        return Err(BrowserInfoError::Timeout);
+    }
+    if browser_type == BrowserType::Embedded {
+        return Err(BrowserInfoError::UrlExtractionFailed(
+            "Embedded WebView2 without a reachable DevTools port".to_string(),
+        ));
+    }
+    log::info!("Accurate methods failed");
+    let url = url_extraction::title_fallback(&window, &browser_type, config, &mut attempts)?;
+    build_browser_info(
+        window,
+        browser_type,
+        url,
+        attempts,
+        focus_target,
+        window_kind,
+        config,
+    )
+}
+
+/// Whether a DevTools result describes the focused window's browser
+///
+/// The DevTools port can belong to a different browser than the one in front
+/// (Chrome with `--remote-debugging-port` behind a focused Firefox), so the
+/// browser process has to be the window's. `Err` says why it is not.
+#[cfg(all(feature = "devtools", target_os = "windows"))]
+fn devtools_matches_window(
+    info: &BrowserInfo,
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+) -> Result<(), String> {
+    // The port was read from this window's own WebView2 process
+    if *browser_type == BrowserType::Embedded {
+        return Ok(());
+    }
+    // Brave and Vivaldi report themselves as Chrome
+    let same_browser = info.browser_type == *browser_type
+        || (info.browser_type == BrowserType::Chrome
+            && matches!(browser_type, BrowserType::Brave | BrowserType::Vivaldi));
+    if !same_browser {
+        return Err(format!(
+            "DevTools port belongs to {:?}, not the focused {browser_type:?}",
+            info.browser_type
+        ));
+    }
+    match info.process_id {
+        0 => Err("DevTools did not report its browser process".to_string()),
+        id if id != window.process_id => Err(format!(
+            "DevTools browser process {id} is not the focused window's ({})",
+            window.process_id
+        )),
+        _ => Ok(()),
     }
+}
+
+/// DevTools step of [`get_browser_info_with_config`]
+#[cfg(all(feature = "devtools", target_os = "windows"))]
+async fn try_devtools(
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+    attempts: &mut Vec<ExtractionAttempt>,
+    focus_target: Option<BrowserFocusTarget>,
+    config: &ExtractionConfig,
+) -> Option<BrowserInfo> {
+    // WebView2 hosts expose CDP only on the port their host app chose
+    let devtools_port = if *browser_type == BrowserType::Embedded {
+        browser_detection::webview2_debugging_port(window.process_id)
+    } else {
+        Some(ChromeDevToolsExtractor::DEFAULT_PORT)
+    };
 
-    Err(BrowserInfoError::Other(
-        "All extraction methods failed".to_string(),
+    let Some(port) = devtools_port else {
+        attempts.push(ExtractionAttempt {
+            source: UrlSource::DevTools,
+            outcome: AttemptOutcome::Skipped("DevTools port not reachable".to_string()),
+        });
+        return None;
+    };
+    if !runtime::on_tokio(ChromeDevToolsExtractor::is_available_on_port(port)).await {
+        attempts.push(ExtractionAttempt {
+            source: UrlSource::DevTools,
+            outcome: AttemptOutcome::Skipped("DevTools port not reachable".to_string()),
+        });
+        return None;
+    }
+
+    log::debug!("Using Chrome DevTools Protocol");
+    let started = std::time::Instant::now();
+    let result = runtime::on_tokio(ChromeDevToolsExtractor::extract_browser_info_from_port(
+        port,
     ))
+    .await;
+    // Port 9222 belongs to whichever browser was started with it
+    let result = result.and_then(|info| {
+        devtools_matches_window(&info, window, browser_type)
+            .map(|()| info)
+            .map_err(BrowserInfoError::UrlExtractionFailed)
+    });
+    let outcome = match &result {
+        Ok(_) => AttemptOutcome::Success,
+        Err(e) => AttemptOutcome::Failed(e.to_string()),
+    };
+    attempts.push(ExtractionAttempt {
+        source: UrlSource::DevTools,
+        outcome,
+    });
+    strategy::record(
+        browser_type,
+        &attempts[attempts.len() - 1..],
+        started.elapsed(),
+    );
+
+    let mut info = result.ok()?;
+    if *browser_type == BrowserType::Embedded {
+        info.browser_type = BrowserType::Embedded;
+        info.browser_name = window.app_name.clone();
+        info.process_id = window.process_id;
+    }
+    info.confidence = UrlConfidence::of(Some(UrlSource::DevTools), &info.url, config.url_privacy);
+    info.url = config.url_privacy.apply(&info.url);
+    info.url_source = Some(UrlSource::DevTools);
+    info.attempts = std::mem::take(attempts);
+    info.focus_target = focus_target;
+    #[cfg(feature = "raw-handles")]
+    {
+        info.native_handle = window.native_handle();
+    }
+    info.audio = config
+        .detect_audio
+        .then(|| audio::audio_state(window))
+        .flatten();
+    match runtime::on_tokio(ChromeDevToolsExtractor::security_on_port(port)).await {
+        Ok((state, certificate)) => {
+            info.security_state = Some(state);
+            info.certificate = certificate;
+        }
+        Err(_) => info.security_state = Some(SecurityState::from_url(&info.url)),
+    }
+    if config.detect_page_language {
+        info.page_language =
+            runtime::on_tokio(ChromeDevToolsExtractor::page_language_on_port(port))
+                .await
+                .ok()
+                .flatten()
+                .or_else(|| site_heuristics::language_from_title(&info.title).map(str::to_string));
+    }
+    if config.detect_performance {
+        info.performance =
+            runtime::on_tokio(ChromeDevToolsExtractor::page_performance_on_port(port))
+                .await
+                .ok();
+    }
+    Some(info)
 }
 
 /// 明示的な方法指定
@@ -240,5 +1084,77 @@ pub async fn get_browser_info_with_method(
             "DevTools feature not available on this platform".to_string(),
         )),
         ExtractionMethod::PowerShell => get_browser_info_safe(),
+        #[cfg(all(feature = "devtools", target_os = "windows"))]
+        ExtractionMethod::Race => race_native_and_devtools().await,
+        #[cfg(not(all(feature = "devtools", target_os = "windows")))]
+        ExtractionMethod::Race => get_browser_info_safe(),
+        ExtractionMethod::Passive => {
+            get_browser_info_with_config(&ExtractionConfig::default().passive(true)).await
+        }
+    }
+}
+
+/// [`ExtractionMethod::Race`]
+///
+/// A title guess from the native chain only wins once DevTools has failed.
+/// DevTools only wins for the focused window's own browser process; a page
+/// from another browser on the port counts as a DevTools failure.
+#[cfg(all(feature = "devtools", target_os = "windows"))]
+async fn race_native_and_devtools() -> Result<BrowserInfo, BrowserInfoError> {
+    let focused = active_browser_window(&ExtractionConfig::default().effective()).ok();
+    // WebView2 の CDP は 9222 ではなくホストが選んだポートにある
+    let matches_focused = |info: &BrowserInfo| {
+        focused.as_ref().is_some_and(|(window, browser_type)| {
+            *browser_type != BrowserType::Embedded
+                && devtools_matches_window(info, window, browser_type).is_ok()
+        })
+    };
+    let token = cancel::CancellationToken::new();
+    let native_token = token.clone();
+    // spawn_blocking は tokio の中でしか使えないので自前のスレッドで動かす
+    let (sender, native) = tokio::sync::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(process_runner::with_cancellation(
+            &native_token,
+            get_active_browser_info,
+        ));
+    });
+    let devtools = get_browser_info_detailed();
+    tokio::pin!(native, devtools);
+
+    let is_accurate =
+        |info: &BrowserInfo| info.url_source.is_none_or(|source| source.is_accurate());
+    let mut native_result = None;
+    let mut devtools_failed = false;
+    loop {
+        tokio::select! {
+            joined = &mut native, if native_result.is_none() => {
+                let result = joined.unwrap_or_else(|e| Err(BrowserInfoError::Other(e.to_string())));
+                match result {
+                    Ok(info) if is_accurate(&info) || devtools_failed => return Ok(info),
+                    result => native_result = Some(result),
+                }
+            }
+            result = &mut devtools, if !devtools_failed => {
+                match result {
+                    Ok(info) if matches_focused(&info) => {
+                        // 負けたネイティブ側の子プロセスを止める
+                        token.cancel();
+                        log::debug!("DevTools won the race");
+                        return Ok(info);
+                    }
+                    Ok(info) => log::debug!(
+                        "DevTools page belongs to {:?} (pid {}), not the focused window",
+                        info.browser_type,
+                        info.process_id
+                    ),
+                    Err(_) => {}
+                }
+                devtools_failed = true;
+            }
+        }
+        if devtools_failed && let Some(result) = native_result.take() {
+            return result;
+        }
     }
 }
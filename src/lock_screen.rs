@@ -0,0 +1,41 @@
+// ================================================================================================
+// Lock screen detection - 画面ロックの検出
+// ================================================================================================
+
+/// Whether the user's session is locked (lock screen or password-protected
+/// screensaver); `None` when the platform cannot tell
+///
+/// The state is queried on every call rather than subscribed to, so it works
+/// without a message window (Windows) or a running main run loop (macOS):
+/// - Windows: the input desktop is not the user's `Default` desktop
+/// - macOS: `CGSSessionScreenIsLocked` in the current session dictionary
+/// - Linux: logind's `LockedHint` for `$XDG_SESSION_ID` (set by most lockers)
+pub fn is_session_locked() -> Option<bool> {
+    #[cfg(target_os = "windows")]
+    return Some(crate::platform::windows::session_locked());
+
+    #[cfg(target_os = "macos")]
+    return crate::platform::macos::session_locked();
+
+    #[cfg(target_os = "linux")]
+    return logind_locked_hint();
+
+    #[allow(unreachable_code)]
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn logind_locked_hint() -> Option<bool> {
+    use crate::process_runner::ProcessRunner;
+
+    let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "auto".to_string());
+    let output = ProcessRunner::new("loginctl")
+        .args(["show-session", &session, "--property=LockedHint", "--value"])
+        .run()
+        .ok()?;
+    match output.stdout.trim() {
+        "yes" if output.success() => Some(true),
+        "no" if output.success() => Some(false),
+        _ => None,
+    }
+}
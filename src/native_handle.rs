@@ -0,0 +1,63 @@
+// ================================================================================================
+// Platform-native window identifiers (`raw-handles` feature) - ネイティブのウィンドウ識別子
+// ================================================================================================
+
+use crate::WindowHandleInfo;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Native identifier of a browser window, for the caller's own platform code
+/// (screenshots, embedding, focus management)
+///
+/// The values are plain numbers, not owned handles: the window may close at
+/// any time and the number may then be reused by the system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum NativeWindowHandle {
+    /// Windows `HWND`, as an integer (`hwnd as isize`)
+    Win32 { hwnd: isize },
+    /// macOS `CGWindowID` (the window number of Quartz Window Services)
+    CoreGraphics { window_id: u32 },
+    /// X11 `Window` resource id (also for XWayland windows)
+    X11 { window: u64 },
+}
+
+impl WindowHandleInfo {
+    /// [`window_id`](Self::window_id) as a [`NativeWindowHandle`]
+    ///
+    /// `None` when the platform listed the window without an id (macOS
+    /// System Events) and on native Wayland, which has no global window ids.
+    pub fn native_handle(&self) -> Option<NativeWindowHandle> {
+        let id = self.window_id.trim();
+        #[cfg(target_os = "windows")]
+        {
+            // active-win-pos-rs の書式: "HWND(123456)"
+            id.strip_prefix("HWND(")
+                .and_then(|id| id.strip_suffix(')'))
+                .and_then(|id| id.parse().ok())
+                .map(|hwnd| NativeWindowHandle::Win32 { hwnd })
+        }
+        #[cfg(target_os = "macos")]
+        {
+            id.parse()
+                .ok()
+                .map(|window_id| NativeWindowHandle::CoreGraphics { window_id })
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let window = match id.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                None => id.parse().ok(),
+            };
+            window
+                .filter(|window| *window != 0)
+                .map(|window| NativeWindowHandle::X11 { window })
+        }
+        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+        {
+            let _ = id;
+            None
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// ================================================================================================
+// Window and tab totals per browser - ブラウザ全体の概要
+// ================================================================================================
+
+use crate::{BrowserInfoError, BrowserType};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Open windows and tabs of one browser, e.g. for a "Chrome: 4 windows, 57 tabs" widget
+///
+/// ```rust
+/// use browser_info::BrowserType;
+/// use browser_info::overview::BrowserOverview;
+///
+/// let overview = BrowserOverview {
+///     browser_type: BrowserType::Chrome,
+///     windows: 4,
+///     tabs: 57,
+///     incognito_windows: Some(1),
+/// };
+/// assert_eq!(overview.to_string(), "Chrome: 4 windows, 57 tabs (1 incognito)");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BrowserOverview {
+    pub browser_type: BrowserType,
+    /// Browser windows with tabs (detached DevTools and popups are not counted)
+    pub windows: u32,
+    /// Page tabs across all windows
+    pub tabs: u32,
+    /// Private/incognito windows among `windows`; `None` when the browser does
+    /// not tell them apart (Safari)
+    pub incognito_windows: Option<u32>,
+}
+
+impl BrowserOverview {
+    /// Whether a private/incognito window is open, when known
+    pub fn has_incognito(&self) -> Option<bool> {
+        self.incognito_windows.map(|count| count > 0)
+    }
+}
+
+impl fmt::Display for BrowserOverview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |count: u32| if count == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{:?}: {} window{}, {} tab{}",
+            self.browser_type,
+            self.windows,
+            plural(self.windows),
+            self.tabs,
+            plural(self.tabs)
+        )?;
+        match self.incognito_windows {
+            Some(count) if count > 0 => write!(f, " ({count} incognito)"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Summarize the windows and tabs of `browser`, whether or not it has the focus
+///
+/// On Windows the counts come from the DevTools endpoint (needs the `devtools`
+/// feature and a Chromium-based browser started with `--remote-debugging-port`;
+/// whichever browser listens on the default port is assumed to be `browser`).
+/// On macOS they come from AppleScript, which covers Safari and the
+/// Chromium-based browsers; a browser that is not running reports zeros.
+pub async fn get_browser_overview(
+    browser: &BrowserType,
+) -> Result<BrowserOverview, BrowserInfoError> {
+    let overview = |(windows, tabs, incognito_windows)| BrowserOverview {
+        browser_type: browser.clone(),
+        windows,
+        tabs,
+        incognito_windows,
+    };
+
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    {
+        use crate::platform::chrome_devtools::ChromeDevToolsExtractor;
+
+        if !matches!(
+            browser,
+            BrowserType::Chrome
+                | BrowserType::Edge
+                | BrowserType::Brave
+                | BrowserType::Opera
+                | BrowserType::Vivaldi
+        ) {
+            return Err(BrowserInfoError::PlatformError(format!(
+                "{browser:?} has no DevTools window list"
+            )));
+        }
+        return crate::runtime::on_tokio(ChromeDevToolsExtractor::window_overview_on_port(
+            ChromeDevToolsExtractor::DEFAULT_PORT,
+        ))
+        .await
+        .map(overview);
+    }
+
+    #[cfg(target_os = "macos")]
+    return crate::platform::macos::window_overview(browser).map(overview);
+
+    #[allow(unreachable_code)]
+    {
+        let _ = overview;
+        Err(BrowserInfoError::PlatformError(
+            "Browser overview needs the devtools feature on Windows, or macOS".to_string(),
+        ))
+    }
+}
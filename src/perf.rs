@@ -0,0 +1,149 @@
+//! Allocation-conscious types for high-frequency polling.
+//!
+//! Sampling at 10 Hz with [`get_active_browser_info`](crate::get_active_browser_info)
+//! allocates fresh strings for every field of every sample. This module offers
+//! three cheaper shapes:
+//!
+//! - [`BrowserInfoRef`]: a borrowed view of a [`BrowserInfo`]
+//! - [`CompactBrowserInfo`]: an owned copy using [`SmolStr`] (short strings are
+//!   stored inline, long ones are shared and cheap to clone)
+//! - [`BrowserSampler`]: polls the active browser while reusing its buffers
+//!
+//! This module is only available with the `perf` feature enabled.
+
+use crate::{
+    BrowserInfo, BrowserInfoError, BrowserType, ExtractionAttempt, ExtractionConfig,
+    WindowPosition, browser_detection, url_extraction,
+};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use smol_str::SmolStr;
+
+/// Borrowed view of browser information
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BrowserInfoRef<'a> {
+    pub url: &'a str,
+    pub title: &'a str,
+    pub browser_name: &'a str,
+    pub browser_type: &'a BrowserType,
+    pub is_incognito: bool,
+    pub process_id: u64,
+    pub window_position: &'a WindowPosition,
+}
+
+impl BrowserInfo {
+    /// Borrow the commonly used fields without cloning
+    pub fn view(&self) -> BrowserInfoRef<'_> {
+        BrowserInfoRef {
+            url: &self.url,
+            title: &self.title,
+            browser_name: &self.browser_name,
+            browser_type: &self.browser_type,
+            is_incognito: self.is_incognito,
+            process_id: self.process_id,
+            window_position: &self.window_position,
+        }
+    }
+}
+
+/// Owned browser information backed by small strings
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CompactBrowserInfo {
+    pub url: SmolStr,
+    pub title: SmolStr,
+    pub browser_name: SmolStr,
+    pub browser_type: BrowserType,
+    pub is_incognito: bool,
+    pub process_id: u64,
+    pub window_position: WindowPosition,
+}
+
+impl From<BrowserInfoRef<'_>> for CompactBrowserInfo {
+    fn from(info: BrowserInfoRef<'_>) -> Self {
+        Self {
+            url: SmolStr::new(info.url),
+            title: SmolStr::new(info.title),
+            browser_name: SmolStr::new(info.browser_name),
+            browser_type: info.browser_type.clone(),
+            is_incognito: info.is_incognito,
+            process_id: info.process_id,
+            window_position: info.window_position.clone(),
+        }
+    }
+}
+
+impl From<&BrowserInfo> for CompactBrowserInfo {
+    fn from(info: &BrowserInfo) -> Self {
+        info.view().into()
+    }
+}
+
+/// Repeated sampling of the active browser with reused buffers
+///
+/// ```rust,no_run
+/// use browser_info::perf::BrowserSampler;
+///
+/// let mut sampler = BrowserSampler::default();
+/// for _ in 0..10 {
+///     if let Ok(sample) = sampler.sample() {
+///         println!("{} {}", sample.browser_name, sample.url);
+///     }
+///     std::thread::sleep(std::time::Duration::from_millis(100));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct BrowserSampler {
+    config: ExtractionConfig,
+    url: String,
+    title: String,
+    browser_name: String,
+    browser_type: Option<BrowserType>,
+    is_incognito: bool,
+    process_id: u64,
+    window_position: WindowPosition,
+    attempts: Vec<ExtractionAttempt>,
+}
+
+impl BrowserSampler {
+    pub fn new(config: ExtractionConfig) -> Self {
+        Self {
+            config: config.effective(),
+            ..Self::default()
+        }
+    }
+
+    /// Take a sample; the returned view is valid until the next call
+    pub fn sample(&mut self) -> Result<BrowserInfoRef<'_>, BrowserInfoError> {
+        let window = crate::window_provider::active_window_for(&self.config)?;
+        let browser_type = browser_detection::classify_browser_with_config(&window, &self.config)?;
+
+        self.attempts.clear();
+        let url = url_extraction::extract_url_traced(
+            &window,
+            &browser_type,
+            &self.config,
+            &mut self.attempts,
+        )?;
+
+        self.url = url;
+        // clone_from keeps the existing capacity instead of reallocating
+        self.title.clone_from(&window.title);
+        self.browser_name.clone_from(&window.app_name);
+        self.is_incognito = browser_detection::detect_incognito_mode(&window, &browser_type);
+        self.process_id = window.process_id;
+        self.window_position = window.position;
+        let browser_type = self.browser_type.insert(browser_type);
+
+        Ok(BrowserInfoRef {
+            url: &self.url,
+            title: &self.title,
+            browser_name: &self.browser_name,
+            browser_type,
+            is_incognito: self.is_incognito,
+            process_id: self.process_id,
+            window_position: &self.window_position,
+        })
+    }
+}
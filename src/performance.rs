@@ -0,0 +1,118 @@
+// ================================================================================================
+// Page load timings - ページ読み込み性能
+// ================================================================================================
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+
+/// Load timings of the active page, as measured by the page itself
+///
+/// Milestones are relative to [`navigation_start`](Self::navigation_start) and
+/// `None` when the page has not reached them yet (or the browser does not
+/// report them). Only available through DevTools.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PagePerformance {
+    /// When the navigation to the current document started
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_time::rfc3339"))]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub navigation_start: SystemTime,
+    /// `DOMContentLoaded` handlers finished
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_time::millis_option", default)
+    )]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<u64>"))]
+    pub dom_content_loaded: Option<Duration>,
+    /// `load` handlers finished
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_time::millis_option", default)
+    )]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<u64>"))]
+    pub load: Option<Duration>,
+    /// Largest Contentful Paint; Chromium only, and only for pages that painted
+    /// before losing focus
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_time::millis_option", default)
+    )]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<u64>"))]
+    pub largest_contentful_paint: Option<Duration>,
+}
+
+impl PagePerformance {
+    /// Parse the object produced by [`PERFORMANCE_SCRIPT`]
+    #[cfg(any(
+        all(feature = "devtools", target_os = "windows"),
+        all(doc, feature = "devtools")
+    ))]
+    pub(crate) fn from_script_value(value: &serde_json::Value) -> Option<Self> {
+        let origin = value["timeOrigin"].as_f64()?;
+        // Navigation Timing reports 0 for milestones that have not happened yet
+        let milestone = |key: &str| {
+            value[key]
+                .as_f64()
+                .filter(|millis| *millis > 0.0)
+                .map(|millis| Duration::from_secs_f64(millis / 1000.0))
+        };
+
+        Some(Self {
+            navigation_start: SystemTime::UNIX_EPOCH + Duration::from_secs_f64(origin / 1000.0),
+            dom_content_loaded: milestone("domContentLoaded"),
+            load: milestone("load"),
+            largest_contentful_paint: milestone("largestContentfulPaint"),
+        })
+    }
+}
+
+/// Collects the timings in the page; resolves to a plain object
+///
+/// LCP entries are only delivered to a buffered `PerformanceObserver`, so the
+/// promise waits briefly for the observer before giving up on LCP.
+#[cfg(any(
+    all(feature = "devtools", target_os = "windows"),
+    all(doc, feature = "devtools")
+))]
+pub(crate) const PERFORMANCE_SCRIPT: &str = r#"new Promise(resolve => {
+    const nav = performance.getEntriesByType('navigation')[0];
+    const result = {
+        timeOrigin: performance.timeOrigin,
+        domContentLoaded: nav ? nav.domContentLoadedEventEnd : 0,
+        load: nav ? nav.loadEventEnd : 0,
+        largestContentfulPaint: 0,
+    };
+    if (!(PerformanceObserver.supportedEntryTypes || []).includes('largest-contentful-paint')) {
+        resolve(result);
+        return;
+    }
+    new PerformanceObserver((list, observer) => {
+        const entries = list.getEntries();
+        result.largestContentfulPaint = entries[entries.length - 1].startTime;
+        observer.disconnect();
+        resolve(result);
+    }).observe({ type: 'largest-contentful-paint', buffered: true });
+    setTimeout(() => resolve(result), 500);
+})"#;
+
+/// Load timings of the active page through DevTools
+///
+/// `None` when no DevTools endpoint is reachable. Windows only for now, like
+/// the rest of the DevTools path.
+#[cfg(feature = "devtools")]
+pub async fn page_performance() -> Option<PagePerformance> {
+    #[cfg(target_os = "windows")]
+    {
+        use crate::platform::chrome_devtools::ChromeDevToolsExtractor;
+
+        crate::runtime::on_tokio(ChromeDevToolsExtractor::page_performance_on_port(
+            ChromeDevToolsExtractor::DEFAULT_PORT,
+        ))
+        .await
+        .ok()
+    }
+    #[cfg(not(target_os = "windows"))]
+    None
+}
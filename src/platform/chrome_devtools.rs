@@ -1,75 +1,1071 @@
 //! Chrome DevTools Protocol integration for detailed browser information extraction.
 //!
 //! This module is only available on Windows with the `devtools` feature enabled.
+//! Its methods must be awaited inside a tokio runtime; the crate's top-level
+//! async functions also work under other executors.
 
-use crate::{BrowserInfo, BrowserInfoError, BrowserType};
+use crate::appearance::ColorScheme;
+use crate::performance::{PERFORMANCE_SCRIPT, PagePerformance};
+use crate::security::{CertificateInfo, SecurityState};
+use crate::tabs::TabInfo;
+use crate::url_extraction::{UrlConfidence, UrlSource};
+use crate::{
+    BrowserInfo, BrowserInfoError, BrowserType, BrowserWindowKind, WindowPosition, WindowState,
+};
+use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError, RwLock};
 use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 
 #[derive(Debug, Deserialize)]
 struct ChromeTab {
-    #[allow(dead_code)]
     id: String,
     title: String,
     url: String,
     #[serde(rename = "type")]
     tab_type: String,
+    #[serde(rename = "webSocketDebuggerUrl", default)]
+    ws_url: Option<String>,
+}
+
+/// How DevTools HTTP requests reach the browser
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum DevToolsProxy {
+    /// Loopback hosts are always contacted directly; other hosts use
+    /// `HTTP_PROXY` / `HTTPS_PROXY` / `ALL_PROXY` unless `NO_PROXY` matches
+    #[default]
+    Auto,
+    /// Never use a proxy
+    Direct,
+    /// Send requests for non-loopback hosts through this proxy (`NO_PROXY` still applies)
+    Url(String),
+}
+
+/// Host whose DevTools port is queried, and how to get there
+///
+/// The default is `localhost` without a proxy. Point it at another machine to
+/// read a browser started with `--remote-debugging-address`:
+///
+/// ```rust,no_run
+/// # #[cfg(target_os = "windows")] {
+/// use browser_info::{DevToolsEndpoint, DevToolsProxy, set_devtools_endpoint};
+///
+/// set_devtools_endpoint(
+///     DevToolsEndpoint::new("10.0.0.5")
+///         .proxy(DevToolsProxy::Url("http://proxy.corp:8080".to_string())),
+/// );
+/// # }
+/// ```
+///
+/// Only the HTTP endpoints (`/json`, `/json/version`, ...) go through the
+/// proxy; the WebSocket connection for CDP commands is always direct.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DevToolsEndpoint {
+    host: String,
+    proxy: DevToolsProxy,
+    origin: Option<String>,
+}
+
+impl DevToolsEndpoint {
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            proxy: DevToolsProxy::Auto,
+            origin: None,
+        }
+    }
+
+    pub fn proxy(mut self, proxy: DevToolsProxy) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// `Origin` header for DevTools WebSocket connections (none by default)
+    ///
+    /// Chrome 111+ accepts connections without an `Origin`. Set this only when
+    /// something in between adds one; it must then be listed in the browser's
+    /// `--remote-allow-origins`.
+    pub fn origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// `localhost`, `127.0.0.0/8` or `::1`
+    pub fn is_loopback(&self) -> bool {
+        let host = self.host.trim_start_matches('[').trim_end_matches(']');
+        host.eq_ignore_ascii_case("localhost")
+            || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+    }
+
+    fn http_client(&self, timeout: Duration) -> Result<reqwest::Client, BrowserInfoError> {
+        // ポーリングのたびに TCP を張り直さないよう、接続をプールに残す
+        let builder = reqwest::Client::builder()
+            .timeout(timeout)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .tcp_keepalive(Duration::from_secs(30));
+        // 社内プロキシが localhost:9222 を横取りしないよう、ループバックは常に直接つなぐ
+        let builder = match &self.proxy {
+            _ if self.is_loopback() => builder.no_proxy(),
+            DevToolsProxy::Auto => builder,
+            DevToolsProxy::Direct => builder.no_proxy(),
+            DevToolsProxy::Url(url) => {
+                let proxy = reqwest::Proxy::all(url)
+                    .map_err(|e| BrowserInfoError::NetworkError(format!("Invalid proxy: {e}")))?
+                    .no_proxy(reqwest::NoProxy::from_env());
+                builder.proxy(proxy)
+            }
+        };
+        builder
+            .build()
+            .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))
+    }
+
+    fn url(&self, port: u16, path: &str) -> String {
+        format!("http://{}:{port}{path}", self.host)
+    }
+}
+
+impl Default for DevToolsEndpoint {
+    fn default() -> Self {
+        Self::new("localhost")
+    }
+}
+
+static ENDPOINT: RwLock<Option<DevToolsEndpoint>> = RwLock::new(None);
+
+/// Use `endpoint` for every DevTools request of this process
+pub fn set_devtools_endpoint(endpoint: DevToolsEndpoint) {
+    *ENDPOINT.write().unwrap_or_else(PoisonError::into_inner) = Some(endpoint);
+}
+
+/// The endpoint currently in use
+pub fn devtools_endpoint() -> DevToolsEndpoint {
+    ENDPOINT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default()
+}
+
+/// HTTP clients reused by DevTools requests, per endpoint, with the epoch they were built in
+///
+/// Shared by every thread. A pooled connection opened on a runtime that has
+/// since shut down (the watcher's navigation hook runs short-lived ones) is
+/// seen as closed and replaced by the pool.
+static CLIENTS: OnceLock<Mutex<HashMap<DevToolsEndpoint, (u64, reqwest::Client)>>> =
+    OnceLock::new();
+
+/// Bumped by [`reset_devtools_connections`]; clients built before are discarded
+static CLIENT_EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// Drop the pooled DevTools connections
+///
+/// The next request connects afresh instead of waiting for a
+/// socket that died while the machine slept or the network changed. The
+/// [`watcher`](crate::watcher) calls this itself when it notices a wake-up.
+pub fn reset_devtools_connections() {
+    CLIENT_EPOCH.fetch_add(1, Ordering::Relaxed);
+}
+
+static CLIENTS_BUILT: AtomicU64 = AtomicU64::new(0);
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+/// How often DevTools HTTP requests could reuse the shared client
+///
+/// Every request after the first on a client goes through its keep-alive
+/// pool, so a steady `clients_built` while `requests` grows means polling is
+/// not re-handshaking.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DevToolsConnectionStats {
+    /// Clients created (once per endpoint, plus once after each reset)
+    pub clients_built: u64,
+    /// HTTP requests sent to DevTools endpoints
+    pub requests: u64,
+}
+
+impl DevToolsConnectionStats {
+    /// Requests served by an already existing client
+    pub fn reused(&self) -> u64 {
+        self.requests.saturating_sub(self.clients_built)
+    }
+}
+
+/// Counters since process start
+pub fn devtools_connection_stats() -> DevToolsConnectionStats {
+    DevToolsConnectionStats {
+        clients_built: CLIENTS_BUILT.load(Ordering::Relaxed),
+        requests: REQUESTS.load(Ordering::Relaxed),
+    }
+}
+
+/// Cached client for `endpoint`, built on first use or after a reset
+fn shared_client(endpoint: &DevToolsEndpoint) -> Result<reqwest::Client, BrowserInfoError> {
+    REQUESTS.fetch_add(1, Ordering::Relaxed);
+    let epoch = CLIENT_EPOCH.load(Ordering::Relaxed);
+    let mut clients = CLIENTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some((built_in, client)) = clients.get(endpoint)
+        && *built_in == epoch
+    {
+        return Ok(client.clone());
+    }
+    let client =
+        endpoint.http_client(Duration::from_secs(ChromeDevToolsExtractor::TIMEOUT_SECS))?;
+    CLIENTS_BUILT.fetch_add(1, Ordering::Relaxed);
+    clients.insert(endpoint.clone(), (epoch, client.clone()));
+    Ok(client)
+}
+
+/// Several DevTools ports queried at once (one per browser started with its
+/// own `--remote-debugging-port`)
+///
+/// Ports are queried concurrently, at most `max_concurrent` at a time, and
+/// the whole scan gives up after `timeout`; ports still pending then report
+/// [`Timeout`](BrowserInfoError::Timeout).
+///
+/// ```rust,no_run
+/// # #[cfg(target_os = "windows")]
+/// # async fn run() {
+/// use browser_info::{ChromeDevToolsExtractor, DevToolsScan};
+///
+/// let scan = DevToolsScan::new().ports([9222, 9223, 9333]);
+/// for found in ChromeDevToolsExtractor::discover_browsers(&scan).await {
+///     match found.result {
+///         Ok(browser) => println!("{}: {:?} {:?}", found.port, browser.browser_type, browser.version),
+///         Err(e) => println!("{}: {e}", found.port),
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevToolsScan {
+    ports: Vec<u16>,
+    max_concurrent: usize,
+    timeout: Duration,
+}
+
+impl Default for DevToolsScan {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DevToolsScan {
+    /// Ports 9222–9229, four at a time, 3 s in total
+    pub fn new() -> Self {
+        Self {
+            ports: (ChromeDevToolsExtractor::DEFAULT_PORT
+                ..=ChromeDevToolsExtractor::DEFAULT_PORT + 7)
+                .collect(),
+            max_concurrent: 4,
+            timeout: Duration::from_secs(ChromeDevToolsExtractor::TIMEOUT_SECS),
+        }
+    }
+
+    pub fn ports(mut self, ports: impl IntoIterator<Item = u16>) -> Self {
+        self.ports = ports.into_iter().collect();
+        self
+    }
+
+    /// Requests in flight at the same time (at least one)
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    /// Time limit for the whole scan, not per port
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run `query` for every port; results come back in port order
+    async fn run<T, F, Fut>(&self, query: F) -> Vec<PortResult<T>>
+    where
+        F: Fn(u16) -> Fut,
+        Fut: std::future::Future<Output = Result<T, BrowserInfoError>>,
+    {
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        let mut results: Vec<PortResult<T>> = futures_util::stream::iter(self.ports.clone())
+            .map(|port| {
+                let request = query(port);
+                async move {
+                    // 締め切りは全体で共通。待たされたポートほど持ち時間が減る
+                    let result = tokio::time::timeout_at(deadline, request)
+                        .await
+                        .unwrap_or(Err(BrowserInfoError::Timeout));
+                    PortResult { port, result }
+                }
+            })
+            .buffer_unordered(self.max_concurrent)
+            .collect()
+            .await;
+        results.sort_by_key(|found| found.port);
+        results
+    }
+}
+
+/// Outcome of a [`DevToolsScan`] for one port
+#[derive(Debug)]
+pub struct PortResult<T> {
+    pub port: u16,
+    pub result: Result<T, BrowserInfoError>,
+}
+
+/// Browser answering on a DevTools port
+#[derive(Debug, Clone, PartialEq)]
+pub struct DevToolsBrowser {
+    pub browser_type: BrowserType,
+    pub browser_name: String,
+    pub version: Option<String>,
 }
 
 pub struct ChromeDevToolsExtractor;
 
 impl ChromeDevToolsExtractor {
-    const DEFAULT_PORT: u16 = 9222;
+    pub const DEFAULT_PORT: u16 = 9222;
     const TIMEOUT_SECS: u64 = 3;
 
     pub async fn is_available() -> bool {
         Self::test_connection(Self::DEFAULT_PORT).await
     }
 
-    async fn test_connection(port: u16) -> bool {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(Self::TIMEOUT_SECS))
-            .build()
-            .unwrap();
+    /// Check a specific debugging port (e.g. a WebView2 host's)
+    pub async fn is_available_on_port(port: u16) -> bool {
+        Self::test_connection(port).await
+    }
 
-        let url = format!("http://localhost:{port}/json/version");
-        client.get(&url).send().await.is_ok()
+    async fn test_connection(port: u16) -> bool {
+        let endpoint = devtools_endpoint();
+        let Ok(client) = shared_client(&endpoint) else {
+            return false;
+        };
+        client
+            .get(endpoint.url(port, "/json/version"))
+            .send()
+            .await
+            .is_ok()
     }
 
     pub async fn extract_browser_info() -> Result<BrowserInfo, BrowserInfoError> {
-        let tabs = Self::get_tabs(Self::DEFAULT_PORT).await?;
+        Self::extract_browser_info_from_port(Self::DEFAULT_PORT).await
+    }
+
+    /// Extract from the DevTools endpoint listening on `port`
+    ///
+    /// Version and browser come from `/json/version`, the tab count from
+    /// `/json`. Incognito state, the browser's process id and the tab's window
+    /// bounds need the browser-level WebSocket; they stay `false` / `0` /
+    /// zero-sized when it cannot be reached.
+    pub async fn extract_browser_info_from_port(
+        port: u16,
+    ) -> Result<BrowserInfo, BrowserInfoError> {
+        let tabs = Self::get_tabs(port).await?;
+        let tabs_count = tabs.iter().filter(|tab| is_page(tab)).count() as u32;
 
         // 最初に見つかったページタブを返す
         let active_tab = tabs
             .into_iter()
-            .find(|tab| tab.tab_type == "page")
+            .find(is_page)
             .ok_or(BrowserInfoError::Other("No active tabs found".to_string()))?;
 
+        let version_info = Self::version_info(port).await.ok();
+        let (browser_type, browser_name, version) = version_info
+            .as_ref()
+            .and_then(|info| info["Browser"].as_str())
+            .map(parse_browser_field)
+            .unwrap_or((BrowserType::Chrome, "Chrome", None));
+        let details = match version_info
+            .as_ref()
+            .and_then(|info| info["webSocketDebuggerUrl"].as_str())
+        {
+            Some(ws_url) => tokio::time::timeout(
+                Duration::from_secs(Self::TIMEOUT_SECS),
+                Self::target_details(ws_url, &active_tab.id),
+            )
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .unwrap_or_default(),
+            None => TargetDetails::default(),
+        };
+
         Ok(BrowserInfo {
             url: active_tab.url,
             title: active_tab.title,
-            browser_name: "Chrome".to_string(),
-            browser_type: BrowserType::Chrome,
-            version: None,       // DevTools APIからは簡単には取得できない
-            tabs_count: None,    // 今回は簡略化
-            is_incognito: false, // 今回は簡略化
-            process_id: 0,       // DevTools APIからは取得できない
-            window_position: Default::default(), // Default trait使用
+            browser_name: browser_name.to_string(),
+            browser_type,
+            version,
+            tabs_count: Some(tabs_count),
+            is_incognito: details.is_incognito,
+            process_id: details.process_id,
+            window_position: details.window_position,
+            window_state: details.window_state,
+            url_source: Some(UrlSource::DevTools),
+            confidence: (!active_tab.url.is_empty()).then_some(UrlConfidence::Exact),
+            attempts: Vec::new(),
+            focus_target: None,
+            page_language: None,
+            security_state: Some(SecurityState::from_url(&active_tab.url)),
+            audio: None,
+            window_kind: BrowserWindowKind::Normal,
+            blocking_dialog: None,
+            performance: None,
+            title_mismatch: false,
+            certificate: None,
+            not_responding: false,
+            #[cfg(feature = "raw-handles")]
+            native_handle: None,
+        })
+    }
+
+    /// Send a CDP command to the first page tab and return its `result` object
+    pub async fn send_command_on_port(
+        port: u16,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, BrowserInfoError> {
+        let ws_url = Self::page_ws_url(port).await?;
+
+        tokio::time::timeout(
+            Duration::from_secs(Self::TIMEOUT_SECS),
+            Self::send_command(&ws_url, method, params),
+        )
+        .await
+        .map_err(|_| BrowserInfoError::Timeout)?
+    }
+
+    /// Evaluate a JavaScript expression in the first page tab
+    ///
+    /// The value is returned by value, so the expression should produce
+    /// something JSON-serializable.
+    pub async fn evaluate_on_port(port: u16, expression: &str) -> Result<Value, BrowserInfoError> {
+        let result = Self::send_command_on_port(
+            port,
+            "Runtime.evaluate",
+            json!({ "expression": expression, "returnByValue": true }),
+        )
+        .await?;
+
+        if let Some(exception) = result.get("exceptionDetails") {
+            return Err(BrowserInfoError::Other(format!(
+                "Evaluation failed: {}",
+                exception["text"].as_str().unwrap_or("unknown exception")
+            )));
+        }
+        Ok(result["result"]["value"].clone())
+    }
+
+    /// Color scheme the page is rendered with (honours `Emulation` overrides)
+    pub async fn color_scheme_on_port(port: u16) -> Result<ColorScheme, BrowserInfoError> {
+        let dark =
+            Self::evaluate_on_port(port, "matchMedia('(prefers-color-scheme: dark)').matches")
+                .await?
+                .as_bool()
+                .ok_or_else(|| BrowserInfoError::ParseError("expected a boolean".to_string()))?;
+        Ok(if dark {
+            ColorScheme::Dark
+        } else {
+            ColorScheme::Light
+        })
+    }
+
+    /// Report same-document navigations (History API, fragment changes) of the first page tab
+    ///
+    /// Single-page apps change the URL through `history.pushState` without a
+    /// reload and often without a title change; `on_navigate` receives the new
+    /// URL of every `Page.navigatedWithinDocument` event. Returns when
+    /// `keep_going` yields `false` (checked at least once per second) or the
+    /// connection closes.
+    pub async fn watch_navigations_on_port(
+        port: u16,
+        mut keep_going: impl FnMut() -> bool,
+        mut on_navigate: impl FnMut(String),
+    ) -> Result<(), BrowserInfoError> {
+        let ws_url = Self::page_ws_url(port).await?;
+        let mut socket = connect_socket(&ws_url).await?;
+
+        let request = json!({ "id": 1, "method": "Page.enable", "params": {} });
+        socket
+            .send(Message::Text(request.to_string()))
+            .await
+            .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))?;
+
+        while keep_going() {
+            let message = match tokio::time::timeout(Duration::from_secs(1), socket.next()).await {
+                Err(_) => continue,
+                Ok(None) => return Ok(()),
+                Ok(Some(message)) => {
+                    message.map_err(|e| BrowserInfoError::NetworkError(e.to_string()))?
+                }
+            };
+            let Message::Text(text) = message else {
+                continue;
+            };
+            let Ok(event) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            if event["method"] == "Page.navigatedWithinDocument"
+                && let Some(url) = event["params"]["url"].as_str()
+            {
+                on_navigate(url.to_string());
+            }
+        }
+
+        let _ = socket.close(None).await;
+        Ok(())
+    }
+
+    /// `lang` attribute of the page's root element; `None` when not declared
+    pub async fn page_language_on_port(port: u16) -> Result<Option<String>, BrowserInfoError> {
+        let lang = Self::evaluate_on_port(port, "document.documentElement.lang").await?;
+        Ok(lang
+            .as_str()
+            .map(str::trim)
+            .filter(|lang| !lang.is_empty())
+            .map(str::to_string))
+    }
+
+    /// Load timings of the first page tab (Navigation Timing and LCP)
+    pub async fn page_performance_on_port(port: u16) -> Result<PagePerformance, BrowserInfoError> {
+        let result = Self::send_command_on_port(
+            port,
+            "Runtime.evaluate",
+            json!({
+                "expression": PERFORMANCE_SCRIPT,
+                "awaitPromise": true,
+                "returnByValue": true,
+            }),
+        )
+        .await?;
+
+        PagePerformance::from_script_value(&result["result"]["value"]).ok_or_else(|| {
+            BrowserInfoError::ParseError("unexpected performance snapshot".to_string())
         })
     }
 
+    /// Security state of the first page tab as shown by the browser's lock icon
+    ///
+    /// Unlike [`SecurityState::from_url`] this detects certificate errors.
+    pub async fn security_state_on_port(port: u16) -> Result<SecurityState, BrowserInfoError> {
+        Self::security_on_port(port).await.map(|(state, _)| state)
+    }
+
+    /// Server certificate of the first page tab; `None` for non-TLS pages
+    pub async fn certificate_on_port(
+        port: u16,
+    ) -> Result<Option<CertificateInfo>, BrowserInfoError> {
+        Self::security_on_port(port)
+            .await
+            .map(|(_, certificate)| certificate)
+    }
+
+    /// Security state and certificate from a single `Security.visibleSecurityStateChanged`
+    pub async fn security_on_port(
+        port: u16,
+    ) -> Result<(SecurityState, Option<CertificateInfo>), BrowserInfoError> {
+        let ws_url = Self::page_ws_url(port).await?;
+        let params = tokio::time::timeout(
+            Duration::from_secs(Self::TIMEOUT_SECS),
+            Self::wait_for_event(
+                &ws_url,
+                "Security.enable",
+                "Security.visibleSecurityStateChanged",
+            ),
+        )
+        .await
+        .map_err(|_| BrowserInfoError::Timeout)??;
+
+        let visible = &params["visibleSecurityState"];
+        let state = visible["securityState"].as_str().unwrap_or_default();
+        let state = SecurityState::from_cdp(state).ok_or_else(|| {
+            BrowserInfoError::ParseError(format!("unknown security state: {state}"))
+        })?;
+        // certificateSecurityState is absent for plain HTTP and internal pages
+        let certificate = CertificateInfo::from_cdp(&visible["certificateSecurityState"]);
+        Ok((state, certificate))
+    }
+
+    /// Send `enable_method` and return the params of the first `event` that follows
+    async fn wait_for_event(
+        ws_url: &str,
+        enable_method: &str,
+        event: &str,
+    ) -> Result<Value, BrowserInfoError> {
+        let mut socket = connect_socket(ws_url).await?;
+
+        let request = json!({ "id": 1, "method": enable_method, "params": {} });
+        socket
+            .send(Message::Text(request.to_string()))
+            .await
+            .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))?;
+
+        while let Some(message) = socket.next().await {
+            let message = message.map_err(|e| BrowserInfoError::NetworkError(e.to_string()))?;
+            let Message::Text(text) = message else {
+                continue;
+            };
+            let mut message: Value = serde_json::from_str(&text)
+                .map_err(|e| BrowserInfoError::ParseError(e.to_string()))?;
+
+            if let Some(error) = message.get("error") {
+                return Err(BrowserInfoError::Other(format!(
+                    "{enable_method} failed: {}",
+                    error["message"].as_str().unwrap_or("unknown error")
+                )));
+            }
+            if message["method"] == event {
+                let _ = socket.close(None).await;
+                return Ok(message["params"].take());
+            }
+        }
+
+        Err(BrowserInfoError::NetworkError(format!(
+            "DevTools connection closed before {event}"
+        )))
+    }
+
+    /// Open `url` in a new tab (`PUT /json/new`)
+    pub async fn open_tab_on_port(port: u16, url: &str) -> Result<(), BrowserInfoError> {
+        let endpoint = devtools_endpoint();
+        let client = shared_client(&endpoint)?;
+
+        // Chrome 111+ rejects GET for this endpoint
+        let response = client
+            .put(endpoint.url(port, &format!("/json/new?{url}")))
+            .send()
+            .await
+            .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))
+            .and_then(reject_forbidden)?;
+        if !response.status().is_success() {
+            return Err(BrowserInfoError::Other(format!(
+                "Opening a tab failed: HTTP {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Activate a tab by target id and bring its window to the front
+    pub async fn activate_tab_on_port(port: u16, tab_id: &str) -> Result<(), BrowserInfoError> {
+        let endpoint = devtools_endpoint();
+        let client = shared_client(&endpoint)?;
+
+        let response = client
+            .get(endpoint.url(port, &format!("/json/activate/{tab_id}")))
+            .send()
+            .await
+            .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))
+            .and_then(reject_forbidden)?;
+        if !response.status().is_success() {
+            return Err(BrowserInfoError::NoActiveTabs);
+        }
+        Ok(())
+    }
+
+    /// Close a tab by target id
+    pub async fn close_tab_on_port(port: u16, tab_id: &str) -> Result<(), BrowserInfoError> {
+        let endpoint = devtools_endpoint();
+        let client = shared_client(&endpoint)?;
+
+        let response = client
+            .get(endpoint.url(port, &format!("/json/close/{tab_id}")))
+            .send()
+            .await
+            .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))
+            .and_then(reject_forbidden)?;
+        if !response.status().is_success() {
+            return Err(BrowserInfoError::NoActiveTabs);
+        }
+        Ok(())
+    }
+
+    /// [`evaluate_on_port`](Self::evaluate_on_port) in the tab with target id `tab_id`
+    pub async fn evaluate_in_tab_on_port(
+        port: u16,
+        tab_id: &str,
+        expression: &str,
+    ) -> Result<Value, BrowserInfoError> {
+        let ws_url = Self::get_tabs(port)
+            .await?
+            .into_iter()
+            .find(|tab| tab.id == tab_id)
+            .and_then(|tab| tab.ws_url)
+            .ok_or(BrowserInfoError::NoActiveTabs)?;
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(Self::TIMEOUT_SECS),
+            Self::send_command(
+                &ws_url,
+                "Runtime.evaluate",
+                json!({ "expression": expression, "returnByValue": true }),
+            ),
+        )
+        .await
+        .map_err(|_| BrowserInfoError::Timeout)??;
+
+        if let Some(exception) = result.get("exceptionDetails") {
+            return Err(BrowserInfoError::Other(format!(
+                "Evaluation failed: {}",
+                exception["text"].as_str().unwrap_or("unknown exception")
+            )));
+        }
+        Ok(result["result"]["value"].clone())
+    }
+
+    /// URL and title of the tab playing in a picture-in-picture window
+    ///
+    /// Checks every page tab for a PiP video (or Document PiP window). Videos in
+    /// cross-origin iframes are not visible to the top document and are missed.
+    pub async fn picture_in_picture_tab_on_port(
+        port: u16,
+    ) -> Result<Option<(String, String)>, BrowserInfoError> {
+        let expression = json!({
+            "expression": "!!(document.pictureInPictureElement || window.documentPictureInPicture?.window)",
+            "returnByValue": true,
+        });
+
+        for tab in Self::get_tabs(port).await? {
+            let Some(ws_url) = tab.ws_url.as_deref().filter(|_| tab.tab_type == "page") else {
+                continue;
+            };
+            let result = tokio::time::timeout(
+                Duration::from_secs(Self::TIMEOUT_SECS),
+                Self::send_command(ws_url, "Runtime.evaluate", expression.clone()),
+            )
+            .await;
+
+            if let Ok(Ok(result)) = result
+                && result["result"]["value"] == true
+            {
+                return Ok(Some((tab.url, tab.title)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Windows, page tabs and incognito windows of the browser behind `port`
+    ///
+    /// Page targets are grouped into windows with `Browser.getWindowForTarget`.
+    /// A window counts as incognito when its pages live outside the default
+    /// browser context; the incognito count is `None` for browsers that do not
+    /// report the default context.
+    pub async fn window_overview_on_port(
+        port: u16,
+    ) -> Result<(u32, u32, Option<u32>), BrowserInfoError> {
+        let ws_url = Self::browser_ws_url(port).await?;
+        tokio::time::timeout(
+            Duration::from_secs(Self::TIMEOUT_SECS),
+            Self::window_overview(&ws_url),
+        )
+        .await
+        .map_err(|_| BrowserInfoError::Timeout)?
+    }
+
+    async fn window_overview(ws_url: &str) -> Result<(u32, u32, Option<u32>), BrowserInfoError> {
+        let mut session = CdpSession::connect(ws_url).await?;
+        let contexts = session.call("Target.getBrowserContexts", json!({})).await?;
+        let default_context = contexts["defaultBrowserContextId"]
+            .as_str()
+            .map(str::to_string);
+        let targets = session.call("Target.getTargets", json!({})).await?;
+
+        // windowId → 非デフォルトのコンテキストのページを含むか
+        let mut windows: HashMap<i64, bool> = HashMap::new();
+        let mut tabs = 0;
+        let pages = targets["targetInfos"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|target| target["type"] == "page")
+            // 切り離された DevTools もページとして列挙される
+            .filter(|target| {
+                !target["url"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .starts_with("devtools://")
+            });
+        for target in pages {
+            tabs += 1;
+            let window = session
+                .call(
+                    "Browser.getWindowForTarget",
+                    json!({ "targetId": target["targetId"] }),
+                )
+                .await?;
+            let Some(window_id) = window["windowId"].as_i64() else {
+                continue;
+            };
+            let incognito = default_context
+                .as_deref()
+                .is_some_and(|default| target["browserContextId"].as_str() != Some(default));
+            *windows.entry(window_id).or_default() |= incognito;
+        }
+        session.close().await;
+
+        let incognito_windows = default_context
+            .map(|_| windows.values().filter(|incognito| **incognito).count() as u32);
+        Ok((windows.len() as u32, tabs, incognito_windows))
+    }
+
+    /// Every page tab of the browser behind `port`, grouped by window
+    ///
+    /// `/json` lists targets by activation, most recent first, so the first
+    /// page of each window is its selected tab. Without the browser-level
+    /// WebSocket all tabs report an empty `window_id` and only the most
+    /// recently activated one is marked active.
+    pub async fn all_tabs_on_port(port: u16) -> Result<Vec<TabInfo>, BrowserInfoError> {
+        let pages: Vec<ChromeTab> = Self::get_tabs(port)
+            .await?
+            .into_iter()
+            .filter(is_page)
+            .collect();
+        let version_info = Self::version_info(port).await.ok();
+        let (browser_type, _, _) = version_info
+            .as_ref()
+            .and_then(|info| info["Browser"].as_str())
+            .map(parse_browser_field)
+            .unwrap_or((BrowserType::Chrome, "Chrome", None));
+
+        let mut window_ids = HashMap::new();
+        if let Some(ws_url) = version_info
+            .as_ref()
+            .and_then(|info| info["webSocketDebuggerUrl"].as_str())
+        {
+            let ids: Vec<&str> = pages.iter().map(|tab| tab.id.as_str()).collect();
+            window_ids = tokio::time::timeout(
+                Duration::from_secs(Self::TIMEOUT_SECS),
+                Self::window_ids(ws_url, &ids),
+            )
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .unwrap_or_default();
+        }
+
+        let mut seen_windows = std::collections::HashSet::new();
+        Ok(pages
+            .into_iter()
+            .map(|tab| {
+                let window_id = window_ids.remove(&tab.id).unwrap_or_default();
+                TabInfo {
+                    active: seen_windows.insert(window_id.clone()),
+                    url: tab.url,
+                    title: tab.title,
+                    window_id,
+                    index: None,
+                    browser_type: browser_type.clone(),
+                    tab_id: Some(tab.id),
+                }
+            })
+            .collect())
+    }
+
+    /// Which browser answers on each port of `scan`
+    pub async fn discover_browsers(scan: &DevToolsScan) -> Vec<PortResult<DevToolsBrowser>> {
+        scan.run(|port| async move {
+            let version_info = Self::version_info(port).await?;
+            let (browser_type, browser_name, version) =
+                parse_browser_field(version_info["Browser"].as_str().unwrap_or_default());
+            Ok(DevToolsBrowser {
+                browser_type,
+                browser_name: browser_name.to_string(),
+                version,
+            })
+        })
+        .await
+    }
+
+    /// [`all_tabs_on_port`](Self::all_tabs_on_port) for every port of `scan`
+    ///
+    /// A port that fails (nothing listening, 403, timeout) only affects its
+    /// own entry.
+    pub async fn all_tabs_on_ports(scan: &DevToolsScan) -> Vec<PortResult<Vec<TabInfo>>> {
+        scan.run(Self::all_tabs_on_port).await
+    }
+
+    /// Target id → window id (as text) for `target_ids`
+    async fn window_ids(
+        ws_url: &str,
+        target_ids: &[&str],
+    ) -> Result<HashMap<String, String>, BrowserInfoError> {
+        let mut session = CdpSession::connect(ws_url).await?;
+        let mut windows = HashMap::new();
+        for target_id in target_ids {
+            let window = session
+                .call(
+                    "Browser.getWindowForTarget",
+                    json!({ "targetId": target_id }),
+                )
+                .await?;
+            if let Some(window_id) = window["windowId"].as_i64() {
+                windows.insert(target_id.to_string(), window_id.to_string());
+            }
+        }
+        session.close().await;
+        Ok(windows)
+    }
+
+    /// Incognito state and window of `target_id`, and the browser process id
+    async fn target_details(
+        ws_url: &str,
+        target_id: &str,
+    ) -> Result<TargetDetails, BrowserInfoError> {
+        let mut session = CdpSession::connect(ws_url).await?;
+        let contexts = session.call("Target.getBrowserContexts", json!({})).await?;
+        let target = session
+            .call("Target.getTargetInfo", json!({ "targetId": target_id }))
+            .await?;
+        // { windowId, bounds: { left, top, width, height, windowState } }
+        let window = session
+            .call(
+                "Browser.getWindowForTarget",
+                json!({ "targetId": target_id }),
+            )
+            .await
+            .unwrap_or_default();
+        // Chrome 以外では SystemInfo ドメインがないことがある
+        let processes = session
+            .call("SystemInfo.getProcessInfo", json!({}))
+            .await
+            .unwrap_or_default();
+        session.close().await;
+
+        let is_incognito = contexts["defaultBrowserContextId"]
+            .as_str()
+            .is_some_and(|default| {
+                target["targetInfo"]["browserContextId"].as_str() != Some(default)
+            });
+        let process_id = processes["processInfo"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|process| process["type"] == "browser")
+            .and_then(|process| process["id"].as_u64())
+            .unwrap_or(0);
+        let bounds = &window["bounds"];
+        let coordinate = |name: &str| bounds[name].as_f64().unwrap_or_default();
+        Ok(TargetDetails {
+            is_incognito,
+            process_id,
+            window_position: WindowPosition::new(
+                coordinate("left"),
+                coordinate("top"),
+                coordinate("width"),
+                coordinate("height"),
+            ),
+            window_state: bounds["windowState"].as_str().map(parse_window_state),
+        })
+    }
+
+    /// `/json/version`: browser name and version, protocol version, browser-level endpoint
+    /// Browser listening on `port`, from the `Browser` field of `/json/version`
+    pub(crate) async fn browser_type_on_port(port: u16) -> Result<BrowserType, BrowserInfoError> {
+        let version_info = Self::version_info(port).await?;
+        let browser = version_info["Browser"]
+            .as_str()
+            .ok_or_else(|| BrowserInfoError::ParseError("no Browser field".to_string()))?;
+        Ok(parse_browser_field(browser).0)
+    }
+
+    async fn version_info(port: u16) -> Result<Value, BrowserInfoError> {
+        let endpoint = devtools_endpoint();
+        let client = shared_client(&endpoint)?;
+
+        client
+            .get(endpoint.url(port, "/json/version"))
+            .send()
+            .await
+            .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))
+            .and_then(reject_forbidden)?
+            .json()
+            .await
+            .map_err(|e| BrowserInfoError::ParseError(e.to_string()))
+    }
+
+    /// Browser-level endpoint (`/json/version`), needed for `Target` and `Browser` commands
+    async fn browser_ws_url(port: u16) -> Result<String, BrowserInfoError> {
+        Self::version_info(port).await?["webSocketDebuggerUrl"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or(BrowserInfoError::ChromeDevToolsNotAvailable)
+    }
+
+    async fn page_ws_url(port: u16) -> Result<String, BrowserInfoError> {
+        Self::get_tabs(port)
+            .await?
+            .into_iter()
+            .find(|tab| tab.tab_type == "page")
+            .and_then(|tab| tab.ws_url)
+            .ok_or(BrowserInfoError::NoActiveTabs)
+    }
+
+    async fn send_command(
+        ws_url: &str,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, BrowserInfoError> {
+        const COMMAND_ID: u64 = 1;
+
+        let mut socket = connect_socket(ws_url).await?;
+
+        let request = json!({ "id": COMMAND_ID, "method": method, "params": params });
+        socket
+            .send(Message::Text(request.to_string()))
+            .await
+            .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))?;
+
+        while let Some(message) = socket.next().await {
+            let message = message.map_err(|e| BrowserInfoError::NetworkError(e.to_string()))?;
+            let Message::Text(text) = message else {
+                continue;
+            };
+            let response: Value = serde_json::from_str(&text)
+                .map_err(|e| BrowserInfoError::ParseError(e.to_string()))?;
+
+            // Events arrive on the same socket; only the reply carries our id
+            if response["id"] != COMMAND_ID {
+                continue;
+            }
+            let _ = socket.close(None).await;
+
+            if let Some(error) = response.get("error") {
+                return Err(BrowserInfoError::Other(format!(
+                    "{method} failed: {}",
+                    error["message"].as_str().unwrap_or("unknown error")
+                )));
+            }
+            return Ok(response["result"].clone());
+        }
+
+        Err(BrowserInfoError::NetworkError(
+            "DevTools connection closed before replying".to_string(),
+        ))
+    }
+
     async fn get_tabs(port: u16) -> Result<Vec<ChromeTab>, BrowserInfoError> {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(Self::TIMEOUT_SECS))
-            .build()
-            .map_err(|e| BrowserInfoError::Other(format!("Network error: {e}")))?;
+        let endpoint = devtools_endpoint();
+        let client = shared_client(&endpoint)?;
 
-        let url = format!("http://localhost:{port}/json");
         let response = client
-            .get(&url)
+            .get(endpoint.url(port, "/json"))
             .send()
             .await
-            .map_err(|e| BrowserInfoError::Other(format!("Network error: {e}")))?;
+            .map_err(|e| BrowserInfoError::Other(format!("Network error: {e}")))
+            .and_then(reject_forbidden)?;
 
         let tabs: Vec<ChromeTab> = response
             .json()
@@ -79,3 +1075,137 @@ impl ChromeDevToolsExtractor {
         Ok(tabs)
     }
 }
+
+/// How to get past a 403 from the DevTools server
+const FORBIDDEN_FIX: &str = "Chrome 111+ only accepts DevTools clients from allowed origins; \
+     start the browser with --remote-allow-origins=* (or the origin set with \
+     DevToolsEndpoint::origin)";
+
+/// Turn an HTTP 403 into [`BrowserInfoError::DevToolsForbidden`]
+fn reject_forbidden(response: reqwest::Response) -> Result<reqwest::Response, BrowserInfoError> {
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        return Err(BrowserInfoError::DevToolsForbidden(
+            FORBIDDEN_FIX.to_string(),
+        ));
+    }
+    Ok(response)
+}
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Open a CDP WebSocket, with the configured `Origin` if any
+async fn connect_socket(ws_url: &str) -> Result<Socket, BrowserInfoError> {
+    use tokio_tungstenite::tungstenite::Error as WsError;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::http::{HeaderValue, StatusCode};
+
+    let mut request = ws_url
+        .into_client_request()
+        .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))?;
+    if let Some(origin) = devtools_endpoint().origin {
+        let origin = HeaderValue::from_str(&origin)
+            .map_err(|e| BrowserInfoError::NetworkError(format!("Invalid origin: {e}")))?;
+        request.headers_mut().insert("Origin", origin);
+    }
+
+    match tokio_tungstenite::connect_async(request).await {
+        Ok((socket, _)) => Ok(socket),
+        // "Rejected an incoming WebSocket connection from the ... origin"
+        Err(WsError::Http(response)) if response.status() == StatusCode::FORBIDDEN => Err(
+            BrowserInfoError::DevToolsForbidden(FORBIDDEN_FIX.to_string()),
+        ),
+        Err(e) => Err(BrowserInfoError::NetworkError(e.to_string())),
+    }
+}
+
+/// What the browser-level endpoint knows about one target
+#[derive(Debug, Default)]
+struct TargetDetails {
+    is_incognito: bool,
+    process_id: u64,
+    /// Screen coordinates of the target's window, in device-independent pixels
+    window_position: WindowPosition,
+    window_state: Option<WindowState>,
+}
+
+/// CDP `Browser.WindowState`
+fn parse_window_state(state: &str) -> WindowState {
+    match state {
+        "minimized" => WindowState::Minimized,
+        "maximized" => WindowState::Maximized,
+        "fullscreen" => WindowState::Fullscreen,
+        _ => WindowState::Normal,
+    }
+}
+
+/// Page target that is not a detached DevTools window
+fn is_page(tab: &ChromeTab) -> bool {
+    tab.tab_type == "page" && !tab.url.starts_with("devtools://")
+}
+
+/// `"Chrome/120.0.6099.71"`, `"Edg/120.0.2210.91"`, `"HeadlessChrome/..."`
+///
+/// Brave and Vivaldi report themselves as Chrome here.
+fn parse_browser_field(browser: &str) -> (BrowserType, &'static str, Option<String>) {
+    let (name, version) = browser.split_once('/').unwrap_or((browser, ""));
+    let (browser_type, browser_name) = match name {
+        "Edg" | "Edge" => (BrowserType::Edge, "Edge"),
+        "OPR" | "Opera" => (BrowserType::Opera, "Opera"),
+        _ => (BrowserType::Chrome, "Chrome"),
+    };
+    let version = (!version.is_empty()).then(|| version.to_string());
+    (browser_type, browser_name, version)
+}
+
+/// One DevTools connection for several commands in a row
+struct CdpSession {
+    socket: Socket,
+    next_id: u64,
+}
+
+impl CdpSession {
+    async fn connect(ws_url: &str) -> Result<Self, BrowserInfoError> {
+        let socket = connect_socket(ws_url).await?;
+        Ok(Self { socket, next_id: 1 })
+    }
+
+    /// Send `method` and wait for its reply, skipping events
+    async fn call(&mut self, method: &str, params: Value) -> Result<Value, BrowserInfoError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let request = json!({ "id": id, "method": method, "params": params });
+        self.socket
+            .send(Message::Text(request.to_string()))
+            .await
+            .map_err(|e| BrowserInfoError::NetworkError(e.to_string()))?;
+
+        while let Some(message) = self.socket.next().await {
+            let message = message.map_err(|e| BrowserInfoError::NetworkError(e.to_string()))?;
+            let Message::Text(text) = message else {
+                continue;
+            };
+            let mut response: Value = serde_json::from_str(&text)
+                .map_err(|e| BrowserInfoError::ParseError(e.to_string()))?;
+            if response["id"] != id {
+                continue;
+            }
+
+            if let Some(error) = response.get("error") {
+                return Err(BrowserInfoError::Other(format!(
+                    "{method} failed: {}",
+                    error["message"].as_str().unwrap_or("unknown error")
+                )));
+            }
+            return Ok(response["result"].take());
+        }
+
+        Err(BrowserInfoError::NetworkError(format!(
+            "DevTools connection closed before replying to {method}"
+        )))
+    }
+
+    async fn close(mut self) {
+        let _ = self.socket.close(None).await;
+    }
+}
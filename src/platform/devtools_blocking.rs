@@ -0,0 +1,230 @@
+//! Blocking DevTools client for builds without an async stack (`sync-only` feature).
+//!
+//! A few dozen lines of HTTP/1.1 over `std::net` are enough for the DevTools
+//! HTTP endpoints (`/json`, `/json/version`), so CLI tools and plugins can
+//! read the active tab without tokio or reqwest:
+//!
+//! ```toml
+//! browser-info = { version = "0.2", default-features = false, features = ["sync-only"] }
+//! ```
+//!
+//! On Windows the synchronous functions
+//! ([`get_active_browser_info`](crate::get_active_browser_info) and friends)
+//! then ask DevTools for the URL when the native methods fail, before any
+//! title guessing. Only `localhost` is contacted, without a proxy, and
+//! nothing that needs the CDP WebSocket (incognito state, window bounds,
+//! certificates) is available; use the `devtools` feature for those.
+//!
+//! ```rust,no_run
+//! use browser_info::platform::devtools_blocking::{DEFAULT_PORT, pages_on_port};
+//!
+//! for page in pages_on_port(DEFAULT_PORT)? {
+//!     println!("{} {}", page.title, page.url);
+//! }
+//! # Ok::<(), browser_info::BrowserInfoError>(())
+//! ```
+
+use crate::BrowserInfoError;
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Port Chromium-based browsers are usually started with (`--remote-debugging-port=9222`)
+pub const DEFAULT_PORT: u16 = 9222;
+
+/// Connect, write and per-read limit
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// DevTools answers are a few KiB per tab; anything far beyond is not DevTools
+const MAX_RESPONSE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// A page target of the DevTools endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DevToolsPage {
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct Target {
+    #[serde(flatten)]
+    page: DevToolsPage,
+    #[serde(rename = "type", default)]
+    kind: String,
+}
+
+/// Whether a DevTools endpoint answers on `port`
+pub fn is_available_on_port(port: u16) -> bool {
+    get(port, "/json/version").is_ok()
+}
+
+/// Open pages on `port`, most recently activated first
+///
+/// Service workers, extension background pages and DevTools windows are
+/// left out.
+pub fn pages_on_port(port: u16) -> Result<Vec<DevToolsPage>, BrowserInfoError> {
+    let body = get(port, "/json")?;
+    let targets: Vec<Target> =
+        serde_json::from_str(&body).map_err(|e| BrowserInfoError::ParseError(e.to_string()))?;
+    Ok(targets
+        .into_iter()
+        .filter(|target| target.kind == "page" && !target.page.url.starts_with("devtools://"))
+        .map(|target| target.page)
+        .collect())
+}
+
+/// The `Browser` field of `/json/version`, e.g. `"Chrome/120.0.6099.71"`
+pub fn browser_version_on_port(port: u16) -> Result<String, BrowserInfoError> {
+    let body = get(port, "/json/version")?;
+    let version: serde_json::Value =
+        serde_json::from_str(&body).map_err(|e| BrowserInfoError::ParseError(e.to_string()))?;
+    version["Browser"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| BrowserInfoError::ParseError("no Browser field".to_string()))
+}
+
+/// DevTools step of the synchronous extraction chain
+///
+/// `None` for browsers without DevTools or when no page was found; the
+/// attempt is recorded either way.
+#[cfg(target_os = "windows")]
+pub(crate) fn active_page_url(
+    window: &crate::WindowHandleInfo,
+    browser_type: &crate::BrowserType,
+    attempts: &mut Vec<crate::ExtractionAttempt>,
+) -> Option<String> {
+    use crate::BrowserType;
+    use crate::url_extraction::{AttemptOutcome, ExtractionAttempt, UrlSource};
+
+    let port = match browser_type {
+        BrowserType::Chrome
+        | BrowserType::Edge
+        | BrowserType::Brave
+        | BrowserType::Opera
+        | BrowserType::Vivaldi => Some(DEFAULT_PORT),
+        // WebView2 hosts expose CDP only on the port their host app chose
+        BrowserType::Embedded => {
+            crate::browser_detection::webview2_debugging_port(window.process_id)
+        }
+        _ => return None,
+    };
+
+    let started = std::time::Instant::now();
+    let result = port
+        .ok_or(BrowserInfoError::ChromeDevToolsNotAvailable)
+        .and_then(pages_on_port)
+        .and_then(|pages| {
+            pages
+                .into_iter()
+                .next()
+                .ok_or(BrowserInfoError::NoActiveTabs)
+        });
+    let outcome = match &result {
+        Ok(_) => AttemptOutcome::Success,
+        Err(BrowserInfoError::ChromeDevToolsNotAvailable) => {
+            AttemptOutcome::Skipped("DevTools port not reachable".to_string())
+        }
+        Err(e) => AttemptOutcome::Failed(e.to_string()),
+    };
+    attempts.push(ExtractionAttempt {
+        source: UrlSource::DevTools,
+        outcome,
+    });
+    crate::strategy::record(
+        browser_type,
+        &attempts[attempts.len() - 1..],
+        started.elapsed(),
+    );
+    result.ok().map(|page| page.url)
+}
+
+/// `GET http://localhost:<port><path>`, returning the body
+fn get(port: u16, path: &str) -> Result<String, BrowserInfoError> {
+    let network = |e: std::io::Error| BrowserInfoError::NetworkError(e.to_string());
+
+    // localhost は 127.0.0.1 と ::1 のどちらにも解決されうる
+    let mut stream = ("localhost", port)
+        .to_socket_addrs()
+        .map_err(network)?
+        .find_map(|address| TcpStream::connect_timeout(&address, TIMEOUT).ok())
+        .ok_or(BrowserInfoError::ChromeDevToolsNotAvailable)?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(network)?;
+    stream.set_write_timeout(Some(TIMEOUT)).map_err(network)?;
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: localhost:{port}\r\nAccept: application/json\r\nConnection: close\r\n\r\n"
+    )
+    .map_err(network)?;
+
+    let mut response = Vec::new();
+    stream
+        .take(MAX_RESPONSE_BYTES)
+        .read_to_end(&mut response)
+        .map_err(network)?;
+    parse_response(&response)
+}
+
+/// Status check and body of a complete HTTP/1.1 response
+fn parse_response(response: &[u8]) -> Result<String, BrowserInfoError> {
+    let malformed = || BrowserInfoError::ParseError("malformed HTTP response".to_string());
+
+    let split = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(malformed)?;
+    let head = String::from_utf8_lossy(&response[..split]).to_ascii_lowercase();
+    let body = &response[split + 4..];
+
+    let status: u16 = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(malformed)?;
+    match status {
+        200 => {}
+        403 => {
+            return Err(BrowserInfoError::DevToolsForbidden(
+                "start the browser with --remote-allow-origins=*".to_string(),
+            ));
+        }
+        status => {
+            return Err(BrowserInfoError::NetworkError(format!(
+                "DevTools answered HTTP {status}"
+            )));
+        }
+    }
+
+    let chunked = head.lines().any(|line| {
+        line.strip_prefix("transfer-encoding:")
+            .is_some_and(|value| value.contains("chunked"))
+    });
+    let body = if chunked {
+        dechunk(body).ok_or_else(malformed)?
+    } else {
+        body.to_vec()
+    };
+    String::from_utf8(body).map_err(|e| BrowserInfoError::ParseError(e.to_string()))
+}
+
+/// Body of a `Transfer-Encoding: chunked` response
+fn dechunk(mut chunks: &[u8]) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = chunks.windows(2).position(|window| window == b"\r\n")?;
+        let size = std::str::from_utf8(&chunks[..line_end]).ok()?;
+        // 拡張 (";name=value") は無視する
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        chunks = &chunks[line_end + 2..];
+        if size == 0 {
+            return Some(body);
+        }
+        body.extend_from_slice(chunks.get(..size)?);
+        chunks = chunks.get(size + 2..)?;
+    }
+}
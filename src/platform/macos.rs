@@ -2,38 +2,79 @@
 // src/platform/macos.rs
 // ================================================================================================
 
-use crate::{BrowserInfoError, BrowserType};
-use active_win_pos_rs::ActiveWindow;
-use std::process::Command;
-
+use crate::WindowHandleInfo;
+use crate::audit::{self, AuditAction};
+use crate::process_runner::ProcessRunner;
+use crate::script_error;
+use crate::shortcut::KeyChord;
+use crate::tabs::TabInfo;
+use crate::temp_script::TempScript;
+use crate::url_extraction::AttemptOutcome;
+use crate::url_extraction::{ExtractionAttempt, UrlSource};
+use crate::{BrowserInfoError, BrowserType, ExtractionConfig};
+use cocoa::base::{id, nil};
+use cocoa::foundation::NSString;
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Sel};
+use objc::{class, msg_send, sel, sel_impl};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, Once, PoisonError};
+
+/// macOS環境でのURL抽出（タイトル推測は呼び出し側で行う）
 pub fn extract_url(
-    window: &ActiveWindow,
+    window: &WindowHandleInfo,
     browser_type: &BrowserType,
+    config: &ExtractionConfig,
+    attempts: &mut Vec<ExtractionAttempt>,
 ) -> Result<String, BrowserInfoError> {
     // 1. AppleScript
-    if let Ok(url) = try_applescript_extraction(browser_type) {
-        return Ok(url);
+    let result = try_applescript_extraction(browser_type, config, window.process_id);
+    ExtractionAttempt::record(attempts, UrlSource::AppleScript, &result);
+    if result.is_ok() {
+        return result;
     }
 
     // 2.キーボードシミュレーション（win版と同じアプローチ）
-    if let Ok(url) = try_keyboard_extraction() {
-        return Ok(url);
+    if !config.allow_keyboard_simulation {
+        attempts.push(ExtractionAttempt {
+            source: UrlSource::Keyboard,
+            outcome: AttemptOutcome::Skipped("keyboard simulation disabled".to_string()),
+        });
+        return result;
     }
-
-    // 3. タイトル推測 (最終手段)
-    extract_url_from_title(&window.title)
+    let result = try_keyboard_extraction();
+    ExtractionAttempt::record(attempts, UrlSource::Keyboard, &result);
+    result
 }
 
-fn try_applescript_extraction(browser_type: &BrowserType) -> Result<String, BrowserInfoError> {
-    println!("🔧 Attempting AppleScript extraction for {browser_type:?}");
+fn try_applescript_extraction(
+    browser_type: &BrowserType,
+    config: &ExtractionConfig,
+    target_pid: u64,
+) -> Result<String, BrowserInfoError> {
+    log::debug!("Attempting AppleScript extraction for {browser_type:?}");
 
     // まず外部スクリプトファイルを試行
-    if let Ok(url) = try_external_applescript_file() {
-        return Ok(url);
+    // （外部スクリプトはFirefoxに対してCmd+Lを送信するため、無効時はスキップ）
+    let external_uses_keyboard = *browser_type == BrowserType::Firefox;
+    if config.allow_keyboard_simulation || !external_uses_keyboard {
+        let actions: &[AuditAction] = if external_uses_keyboard {
+            &[
+                AuditAction::ScriptExecution,
+                AuditAction::KeyboardSimulation,
+                AuditAction::ClipboardAccess,
+            ]
+        } else {
+            &[AuditAction::ScriptExecution]
+        };
+        audit::record(config, actions, "applescript_file", target_pid);
+        if let Ok(url) = try_external_applescript_file(config) {
+            return Ok(url);
+        }
     }
 
     // フォールバック: インライン AppleScript
-    println!("⚠️ External script failed, trying inline AppleScript...");
+    log::info!("External script failed, trying inline AppleScript...");
 
     let script = match browser_type {
         BrowserType::Chrome => {
@@ -85,112 +126,63 @@ fn try_applescript_extraction(browser_type: &BrowserType) -> Result<String, Brow
         }
     };
 
+    audit::record(
+        config,
+        &[AuditAction::ScriptExecution],
+        "applescript_inline",
+        target_pid,
+    );
     execute_inline_applescript(script)
 }
 
-/// 外部AppleScriptファイルを実行
-fn try_external_applescript_file() -> Result<String, BrowserInfoError> {
-    let script_paths = [
-        // メインの場所
-        "src/platform/scripts/macos_get_url.scpt",
-        // 開発時の相対パス
-        "platform/scripts/macos_get_url.scpt",
-        "scripts/macos_get_url.scpt",
-        // 実行時の相対パス
-        "../src/platform/scripts/macos_get_url.scpt",
-        "../../src/platform/scripts/macos_get_url.scpt",
-    ];
-
-    for script_path in &script_paths {
-        if std::path::Path::new(script_path).exists() {
-            println!("📁 Found AppleScript file at: {script_path}");
-            return execute_external_applescript_file(script_path);
-        }
-    }
-
-    Err(BrowserInfoError::PlatformError(
-        "AppleScript file not found. Expected at: src/platform/scripts/macos_get_url.scpt"
-            .to_string(),
-    ))
+/// 同梱AppleScriptを一時ファイルとして実行
+///
+/// カレントディレクトリのスクリプトは実行しない（差し替えられる恐れがあるため）
+fn try_external_applescript_file(config: &ExtractionConfig) -> Result<String, BrowserInfoError> {
+    // Firefox 分岐の Cmd+L を設定の先頭ショートカットに置き換える
+    let focus_address_bar = config
+        .address_bar_shortcuts_for(&BrowserType::Firefox)
+        .first()
+        .map(KeyChord::applescript)
+        .unwrap_or_default();
+    let source = include_str!("scripts/macos_get_url.scpt")
+        .replace("key code 37 using command down", &focus_address_bar);
+    let script = TempScript::write(config, "applescript", &source)?;
+    log::debug!("AppleScript materialized at: {}", script.path().display());
+    execute_external_applescript_file(script.path())
 }
 
 /// 外部AppleScriptファイルを実行
-fn execute_external_applescript_file(script_path: &str) -> Result<String, BrowserInfoError> {
-    use std::time::{Duration, Instant};
-
-    let start_time = Instant::now();
-    let timeout = Duration::from_secs(5);
-
-    println!("🔧 Executing external AppleScript file: {script_path}");
+fn execute_external_applescript_file(
+    script_path: &std::path::Path,
+) -> Result<String, BrowserInfoError> {
+    log::debug!(
+        "Executing external AppleScript file: {}",
+        script_path.display()
+    );
 
-    let output = Command::new("osascript")
+    let stdout = ProcessRunner::new("osascript")
         .arg(script_path)
-        .output()
-        .map_err(|e| {
-            BrowserInfoError::PlatformError(format!("AppleScript file execution error: {e}"))
-        })?;
-
-    if start_time.elapsed() > timeout {
-        return Err(BrowserInfoError::Timeout);
-    }
-
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if !stderr.is_empty() {
-        println!("⚠️ AppleScript stderr: {stderr}");
-    }
-
-    if !output.status.success() {
-        return Err(BrowserInfoError::PlatformError(format!(
-            "AppleScript file failed with exit code: {}",
-            output.status
-        )));
-    }
-
-    let stdout = String::from_utf8(output.stdout).map_err(|e| {
-        BrowserInfoError::PlatformError(format!("AppleScript output parsing error: {e}"))
-    })?;
+        .run()?
+        .into_stdout("AppleScript file")?;
 
     parse_applescript_output(&stdout)
 }
 
-/// インライン AppleScript を実行
-fn execute_inline_applescript(script: &str) -> Result<String, BrowserInfoError> {
-    use std::time::{Duration, Instant};
-
-    let start_time = Instant::now();
-    let timeout = Duration::from_secs(5);
-
-    println!("🔧 Executing inline AppleScript...");
-
-    let output = Command::new("osascript")
+/// インライン AppleScript を実行し、標準出力を返す
+fn run_inline_applescript(script: &str) -> Result<String, BrowserInfoError> {
+    ProcessRunner::new("osascript")
         .arg("-e")
         .arg(script)
-        .output()
-        .map_err(|e| {
-            BrowserInfoError::PlatformError(format!("AppleScript execution error: {e}"))
-        })?;
-
-    if start_time.elapsed() > timeout {
-        return Err(BrowserInfoError::Timeout);
-    }
-
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if !stderr.is_empty() {
-        println!("⚠️ AppleScript stderr: {stderr}");
-    }
-
-    if !output.status.success() {
-        return Err(BrowserInfoError::PlatformError(format!(
-            "AppleScript failed with exit code: {}",
-            output.status
-        )));
-    }
+        .run()?
+        .into_stdout("AppleScript")
+}
 
-    let stdout = String::from_utf8(output.stdout).map_err(|e| {
-        BrowserInfoError::PlatformError(format!("AppleScript output parsing error: {e}"))
-    })?;
+/// インライン AppleScript でURLを取得
+fn execute_inline_applescript(script: &str) -> Result<String, BrowserInfoError> {
+    log::debug!("Executing inline AppleScript...");
 
-    let url = stdout.trim().to_string();
+    let url = run_inline_applescript(script)?.trim().to_string();
 
     if url.starts_with("http") || url.starts_with("file://") {
         Ok(url)
@@ -203,7 +195,7 @@ fn execute_inline_applescript(script: &str) -> Result<String, BrowserInfoError>
 
 /// AppleScript出力を解析
 fn parse_applescript_output(output: &str) -> Result<String, BrowserInfoError> {
-    println!("🔍 Parsing AppleScript output...");
+    log::debug!("Parsing AppleScript output...");
 
     let lines: Vec<&str> = output.lines().collect();
 
@@ -221,7 +213,7 @@ fn parse_applescript_output(output: &str) -> Result<String, BrowserInfoError> {
         ));
     }
 
-    println!("📤 AppleScript result line: {result_line}");
+    log::debug!("AppleScript result line: {result_line}");
 
     let parts: Vec<&str> = result_line.split('|').collect();
 
@@ -230,7 +222,7 @@ fn parse_applescript_output(output: &str) -> Result<String, BrowserInfoError> {
             "SUCCESS" => {
                 let url = parts[1].trim();
                 if url.starts_with("http") || url.starts_with("file://") {
-                    println!("✅ AppleScript extraction successful: {url}");
+                    log::debug!("AppleScript extraction successful: {url}");
                     Ok(url.to_string())
                 } else {
                     Err(BrowserInfoError::InvalidUrl(format!(
@@ -240,9 +232,12 @@ fn parse_applescript_output(output: &str) -> Result<String, BrowserInfoError> {
             }
             "ERROR" => {
                 let error_msg = parts[1].trim();
-                Err(BrowserInfoError::PlatformError(format!(
-                    "AppleScript error: {error_msg}"
-                )))
+                match script_error::from_script_error(error_msg, parts.get(3).copied()) {
+                    BrowserInfoError::PlatformError(message) => Err(
+                        BrowserInfoError::PlatformError(format!("AppleScript error: {message}")),
+                    ),
+                    error => Err(error),
+                }
             }
             _ => {
                 // 単純な URL の場合（互換性のため）
@@ -266,38 +261,439 @@ fn parse_applescript_output(output: &str) -> Result<String, BrowserInfoError> {
 fn try_keyboard_extraction() -> Result<String, BrowserInfoError> {
     // TODO: macOS版キーボードシミュレーション（実機テスト後に実装）
     // 現在はAppleScript優先のため、フォールバックとして実装予定
-    println!("⚠️ Keyboard simulation fallback - not yet implemented for macOS");
+    log::warn!("Keyboard simulation fallback - not yet implemented for macOS");
     Err(BrowserInfoError::PlatformError(
         "Keyboard extraction not implemented - AppleScript method preferred".to_string(),
     ))
 }
 
-/// タイトルからのURL推測（最終フォールバック）
-fn extract_url_from_title(title: &str) -> Result<String, BrowserInfoError> {
-    println!("🔍 macOS fallback: extracting URL from title: {title}");
-
-    let title_lower = title.to_lowercase();
-
-    // 一般的なサイトのURL推測（Windows版と同様）
-    if title_lower.contains("claude") {
-        Ok("https://claude.ai/chat".to_string())
-    } else if title_lower.contains("github") {
-        Ok("https://github.com".to_string())
-    } else if title_lower.contains("google") {
-        Ok("https://www.google.com".to_string())
-    } else if title_lower.contains("youtube") {
-        Ok("https://www.youtube.com".to_string())
-    } else if title_lower.contains("stackoverflow") {
-        Ok("https://stackoverflow.com".to_string())
-    } else if title_lower.contains("twitter") || title_lower.contains("x.com") {
-        Ok("https://x.com".to_string())
-    } else if title_lower.contains("reddit") {
-        Ok("https://www.reddit.com".to_string())
+/// Accessibility APIでフォーカス中の要素を取得 (role, description, identifier)
+pub fn focused_element() -> Option<(String, String, String)> {
+    let script = r#"tell application "System Events"
+        set e to value of attribute "AXFocusedUIElement" of (first application process whose frontmost is true)
+        set r to role of e
+        set d to ""
+        try
+            set d to description of e
+        end try
+        set i to ""
+        try
+            set i to value of attribute "AXIdentifier" of e
+        end try
+        return r & "|" & d & "|" & i
+    end tell"#;
+
+    let stdout = run_inline_applescript(script).ok()?;
+    let mut parts = stdout.trim().split('|');
+    Some((
+        parts.next()?.to_string(),
+        parts.next().unwrap_or_default().to_string(),
+        parts.next().unwrap_or_default().to_string(),
+    ))
+}
+
+/// ブラウザのウィンドウ数・タブ数・シークレットウィンドウ数 (AppleScript)
+///
+/// 起動していないブラウザは起動せずに 0 を返す。Safari はプライベートウィンドウを
+/// 区別できないため、シークレットウィンドウ数は `None`。
+pub fn window_overview(
+    browser_type: &BrowserType,
+) -> Result<(u32, u32, Option<u32>), BrowserInfoError> {
+    let app = match browser_type {
+        BrowserType::Chrome => "Google Chrome",
+        BrowserType::Edge => "Microsoft Edge",
+        BrowserType::Brave => "Brave Browser",
+        BrowserType::Vivaldi => "Vivaldi",
+        BrowserType::Opera => "Opera",
+        BrowserType::Safari => "Safari",
+        other => {
+            return Err(BrowserInfoError::PlatformError(format!(
+                "{other:?} has no AppleScript window list"
+            )));
+        }
+    };
+    // Chromium の window には mode ("normal" / "incognito") がある
+    let incognito = if *browser_type == BrowserType::Safari {
+        ""
     } else {
-        Err(BrowserInfoError::UrlExtractionFailed(format!(
-            "Cannot determine URL from macOS title: {title}"
-        )))
+        r#"if mode of win is "incognito" then set p to p + 1"#
+    };
+
+    let script = format!(
+        r#"if application "{app}" is not running then return "0|0|0"
+        tell application "{app}"
+            set w to 0
+            set t to 0
+            set p to 0
+            repeat with win in windows
+                try
+                    set t to t + (count of tabs of win)
+                    set w to w + 1
+                    {incognito}
+                end try
+            end repeat
+            return (w as text) & "|" & (t as text) & "|" & (p as text)
+        end tell"#
+    );
+    let output = run_inline_applescript(&script)?;
+
+    let mut counts = output.trim().split('|').map(|count| count.parse::<u32>());
+    let mut next = || {
+        counts
+            .next()
+            .and_then(Result::ok)
+            .ok_or_else(|| BrowserInfoError::ParseError(format!("unexpected output: {output}")))
+    };
+    let (windows, tabs, incognito_windows) = (next()?, next()?, next()?);
+    Ok((
+        windows,
+        tabs,
+        (*browser_type != BrowserType::Safari).then_some(incognito_windows),
+    ))
+}
+
+/// 画面ロック中か (CGSessionCopyCurrentDictionary の CGSSessionScreenIsLocked)
+///
+/// `None` outside a GUI login session (e.g. over SSH).
+pub fn session_locked() -> Option<bool> {
+    use core_foundation::base::{CFType, TCFType};
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::string::CFString;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    unsafe extern "C" {
+        fn CGSessionCopyCurrentDictionary() -> CFDictionaryRef;
+    }
+
+    let dictionary = unsafe { CGSessionCopyCurrentDictionary() };
+    if dictionary.is_null() {
+        return None;
     }
+    let dictionary: CFDictionary<CFString, CFType> =
+        unsafe { CFDictionary::wrap_under_create_rule(dictionary) };
+    // ロック中だけキーが存在する
+    let locked = dictionary
+        .find(CFString::from_static_string("CGSSessionScreenIsLocked"))
+        .and_then(|value| value.downcast::<CFBoolean>())
+        .is_some_and(bool::from);
+    Some(locked)
+}
+
+/// 全プロセスの (PID, 実行ファイル, 常駐メモリ) 一覧 (`ps`)
+pub fn process_memory() -> Result<Vec<(u64, std::path::PathBuf, u64)>, BrowserInfoError> {
+    // rss は KiB。comm は実行ファイルのフルパス（空白を含み得るので最後の列）
+    let output = ProcessRunner::new("ps")
+        .args(["-axo", "pid=,rss=,comm="])
+        .run()?
+        .into_stdout("ps")?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let (pid, rest) = line.trim_start().split_once(char::is_whitespace)?;
+            let (rss_kib, path) = rest.trim_start().split_once(char::is_whitespace)?;
+            let rss_kib: u64 = rss_kib.parse().ok()?;
+            Some((pid.parse().ok()?, path.trim_start().into(), rss_kib * 1024))
+        })
+        .collect())
+}
+
+/// 通常アプリの全ウィンドウ (System Events)
+///
+/// 補助アクセスの許可が必要。ウィンドウ番号は取れないため `window_id` は空。
+pub fn visible_windows() -> Result<Vec<WindowHandleInfo>, BrowserInfoError> {
+    // タイトルにタブが含まれても壊れないよう最後の列にする
+    let script = r#"set sep to character id 9
+        set out to ""
+        tell application "System Events"
+            repeat with p in (every application process whose background only is false)
+                try
+                    set pid to unix id of p
+                    set appName to name of p
+                    set appPath to POSIX path of (application file of p)
+                    repeat with w in windows of p
+                        try
+                            set {x, y} to position of w
+                            set {ww, hh} to size of w
+                            set t to ""
+                            try
+                                set t to (name of w) as text
+                            end try
+                            set out to out & pid & sep & appName & sep & appPath & sep & x & sep & y & sep & ww & sep & hh & sep & t & linefeed
+                        end try
+                    end repeat
+                end try
+            end repeat
+        end tell
+        return out"#;
+    let output = run_inline_applescript(script)?;
+
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(8, '\t');
+            let process_id = fields.next()?.trim().parse().ok()?;
+            let app_name = fields.next()?.to_string();
+            let process_path = fields.next()?.into();
+            let mut number = || fields.next()?.trim().parse::<f64>().ok();
+            let (x, y, width, height) = (number()?, number()?, number()?, number()?);
+            let title = fields.next()?.to_string();
+            (!title.is_empty()).then(|| WindowHandleInfo {
+                title,
+                app_name,
+                process_path,
+                process_id,
+                window_id: String::new(),
+                position: crate::WindowPosition::new(x, y, width, height),
+            })
+        })
+        .collect())
+}
+
+/// ブラウザの全ウィンドウの全タブ (AppleScript)
+///
+/// 起動していないブラウザは起動せずに空を返す。Safari のタブには id がない。
+pub fn all_tabs(browser_type: &BrowserType) -> Result<Vec<TabInfo>, BrowserInfoError> {
+    let app = match browser_type {
+        BrowserType::Chrome => "Google Chrome",
+        BrowserType::Edge => "Microsoft Edge",
+        BrowserType::Brave => "Brave Browser",
+        BrowserType::Vivaldi => "Vivaldi",
+        BrowserType::Opera => "Opera",
+        BrowserType::Safari => "Safari",
+        other => {
+            return Err(BrowserInfoError::PlatformError(format!(
+                "{other:?} has no AppleScript tab list"
+            )));
+        }
+    };
+    // 1行1タブ: windowId, 位置, tabId, active, URL, タイトル（タブを含み得るので最後）
+    let tab_line = if *browser_type == BrowserType::Safari {
+        r#"set out to out & (id of win) & sep & i & sep & "" & sep & (t is current tab of win) & sep & (URL of t) & sep & (name of t) & linefeed"#
+    } else {
+        r#"set out to out & (id of win) & sep & i & sep & (id of t) & sep & (i = active tab index of win) & sep & (URL of t) & sep & (title of t) & linefeed"#
+    };
+    let script = format!(
+        r#"if application "{app}" is not running then return ""
+        -- tell の中では `tab` がタブクラスを指すので区切りは変数にする
+        set sep to character id 9
+        set out to ""
+        tell application "{app}"
+            repeat with win in windows
+                try
+                    repeat with i from 1 to (count of tabs of win)
+                        set t to tab i of win
+                        try
+                            {tab_line}
+                        end try
+                    end repeat
+                end try
+            end repeat
+        end tell
+        return out"#
+    );
+    let output = run_inline_applescript(&script)?;
+
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(6, '\t');
+            let window_id = fields.next()?.trim().to_string();
+            // AppleScript は1始まり
+            let index = fields.next()?.trim().parse::<u32>().ok()?.checked_sub(1);
+            let tab_id = fields.next()?.trim();
+            let active = fields.next()?.trim() == "true";
+            let url = fields.next()?.to_string();
+            let title = fields.next().unwrap_or_default().to_string();
+            Some(TabInfo {
+                url,
+                title,
+                active,
+                window_id,
+                index,
+                browser_type: browser_type.clone(),
+                tab_id: (!tab_id.is_empty()).then(|| tab_id.to_string()),
+            })
+        })
+        .collect())
+}
+
+/// ウィンドウの内容を RGBA で取得（最小化・画面外なら `None`）
+///
+/// `window_id` は CGWindowID (active-win-pos-rs の window_id)。画面収録の許可がないと
+/// デスクトップの壁紙だけが返る。
+pub fn capture_window(window_id: &str) -> Result<Option<(u32, u32, Vec<u8>)>, BrowserInfoError> {
+    use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+    use core_graphics::window::{
+        create_image, kCGWindowImageBoundsIgnoreFraming, kCGWindowImageNominalResolution,
+        kCGWindowListOptionIncludingWindow,
+    };
+
+    let id: u32 = window_id
+        .trim()
+        .parse()
+        .map_err(|_| BrowserInfoError::WindowNotFound)?;
+    // CGRectNull: ウィンドウ自身の大きさで撮る
+    let null_rect = CGRect::new(
+        &CGPoint::new(f64::INFINITY, f64::INFINITY),
+        &CGSize::new(0.0, 0.0),
+    );
+    let Some(image) = create_image(
+        null_rect,
+        kCGWindowListOptionIncludingWindow,
+        id,
+        kCGWindowImageBoundsIgnoreFraming | kCGWindowImageNominalResolution,
+    ) else {
+        return Ok(None);
+    };
+    let (width, height) = (image.width(), image.height());
+    if width == 0 || height == 0 || image.bits_per_pixel() != 32 {
+        return Ok(None);
+    }
+
+    // 32bit BGRA (premultiplied)、行末にパディングあり
+    let stride = image.bytes_per_row();
+    let data = image.data();
+    let bytes = data.bytes();
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for row in bytes.chunks(stride).take(height) {
+        for pixel in row[..width * 4].chunks_exact(4) {
+            pixels.extend_from_slice(&[pixel[2], pixel[1], pixel[0], 255]);
+        }
+    }
+    Ok(Some((width as u32, height as u32, pixels)))
+}
+
+/// プロセスがアクセシビリティ問い合わせに `timeout` 以内に応答するか
+///
+/// 応答なしのアプリへの AppleEvent は既定で数秒待たされるため、期限を過ぎたら
+/// osascript ごと終了させる。エラーでも期限内に終われば応答ありとみなす。
+pub fn is_process_responding(process_id: u64, timeout: std::time::Duration) -> bool {
+    let script = format!(
+        r#"tell application "System Events" to get name of front window of (first process whose unix id is {process_id})"#
+    );
+    let result = ProcessRunner::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .timeout(timeout)
+        .run();
+    !matches!(result, Err(BrowserInfoError::Timeout))
+}
+
+/// 最前面ウィンドウの AXSubrole (AXStandardWindow, AXFloatingWindow, AXDialog ...)
+pub fn front_window_subrole() -> Option<String> {
+    let script = r#"tell application "System Events"
+        return subrole of front window of (first application process whose frontmost is true)
+    end tell"#;
+
+    let subrole = run_inline_applescript(script).ok()?.trim().to_string();
+    (!subrole.is_empty() && subrole != "missing value").then_some(subrole)
+}
+
+/// 最前面ウィンドウに表示中のシートの説明
+pub fn sheet_title() -> Option<String> {
+    let script = r#"tell application "System Events"
+        set w to front window of (first application process whose frontmost is true)
+        if exists sheet 1 of w then
+            set t to ""
+            try
+                set t to description of sheet 1 of w
+            end try
+            return "DIALOG|" & t
+        end if
+        return "NONE|"
+    end tell"#;
+
+    run_inline_applescript(script)
+        .ok()?
+        .trim()
+        .strip_prefix("DIALOG|")
+        .map(|title| title.to_string())
+}
+
+type ActivationCallback = Box<dyn Fn() + Send>;
+
+static ACTIVATION_CALLBACKS: Mutex<Vec<(usize, ActivationCallback)>> = Mutex::new(Vec::new());
+
+/// NSWorkspace のアプリ切替通知の購読（Drop で解除）
+///
+/// 通知はメインスレッドのランループで配送されるため、ランループを回していない
+/// コマンドラインアプリでは呼ばれない。
+#[derive(Debug)]
+pub struct ActivationObserver {
+    observer: usize,
+    slot: usize,
+}
+
+/// Call `on_activate` whenever another application becomes frontmost
+pub fn observe_app_activation(on_activate: impl Fn() + Send + 'static) -> ActivationObserver {
+    static NEXT_SLOT: AtomicUsize = AtomicUsize::new(0);
+
+    let slot = NEXT_SLOT.fetch_add(1, Ordering::Relaxed);
+    ACTIVATION_CALLBACKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push((slot, Box::new(on_activate)));
+
+    let observer = unsafe {
+        let observer: id = msg_send![activation_observer_class(), new];
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let center: id = msg_send![workspace, notificationCenter];
+        let name = NSString::alloc(nil).init_str("NSWorkspaceDidActivateApplicationNotification");
+        let _: () = msg_send![center, addObserver: observer
+                                         selector: sel!(applicationActivated:)
+                                             name: name
+                                           object: nil];
+        observer
+    };
+
+    ActivationObserver {
+        observer: observer as usize,
+        slot,
+    }
+}
+
+impl Drop for ActivationObserver {
+    fn drop(&mut self) {
+        unsafe {
+            let observer = self.observer as id;
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let center: id = msg_send![workspace, notificationCenter];
+            let _: () = msg_send![center, removeObserver: observer];
+            let _: () = msg_send![observer, release];
+        }
+        ACTIVATION_CALLBACKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|(slot, _)| *slot != self.slot);
+    }
+}
+
+extern "C" fn application_activated(_this: &Object, _cmd: Sel, _notification: id) {
+    let callbacks = ACTIVATION_CALLBACKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    for (_, callback) in callbacks.iter() {
+        callback();
+    }
+}
+
+fn activation_observer_class() -> &'static Class {
+    const NAME: &str = "BrowserInfoActivationObserver";
+    static REGISTER: Once = Once::new();
+
+    REGISTER.call_once(|| {
+        if let Some(mut decl) = ClassDecl::new(NAME, class!(NSObject)) {
+            unsafe {
+                decl.add_method(
+                    sel!(applicationActivated:),
+                    application_activated as extern "C" fn(&Object, Sel, id),
+                );
+            }
+            decl.register();
+        }
+    });
+    Class::get(NAME).expect("observer class is registered")
 }
 
 // Clipboard functionality removed due to security vulnerability RUSTSEC-2020-0097
@@ -335,3 +731,28 @@ fn simulate_key_press(_key: u32) -> Result<(), BrowserInfoError> {
         "Key simulation not implemented".to_string(),
     ))
 }
+
+/// アクセシビリティ権限 (AXIsProcessTrusted)。キー入力の送信に必要
+pub fn accessibility_trusted() -> bool {
+    #[link(name = "ApplicationServices", kind = "framework")]
+    unsafe extern "C" {
+        fn AXIsProcessTrusted() -> u8;
+    }
+
+    unsafe { AXIsProcessTrusted() != 0 }
+}
+
+/// osascript が System Events を操作できるか
+///
+/// 未許可なら初回だけ macOS の「オートメーション」許可ダイアログが出る。
+pub fn probe_osascript() -> Result<(), BrowserInfoError> {
+    ProcessRunner::new("osascript")
+        .args([
+            "-e",
+            "tell application \"System Events\" to count application processes",
+        ])
+        .timeout(std::time::Duration::from_secs(10))
+        .run()?
+        .into_stdout("osascript probe")
+        .map(|_| ())
+}
@@ -10,5 +10,8 @@ pub mod macos;
 ))]
 pub mod chrome_devtools;
 
+#[cfg(feature = "sync-only")]
+pub mod devtools_blocking;
+
 // 将来の拡張用
 // pub mod firefox_remote;
@@ -1,4 +1,7 @@
 # Windows Browser Info Retrieval Script
+# -Shortcuts: comma-separated address-bar shortcuts to try, e.g. "ctrl+l,f8,alt+d"
+param([string]$Shortcuts = "ctrl+l")
+
 [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
 $OutputEncoding = [System.Text.Encoding]::UTF8
 
@@ -33,6 +36,37 @@ Add-Type -TypeDefinition @"
 
 Add-Type -AssemblyName System.Windows.Forms
 
+# Press one chord such as "ctrl+l", "alt+d" or "f8"; $false if it cannot be parsed
+function Send-Chord {
+    param([string]$Chord)
+
+    $modifiers = @()
+    $key = $null
+    foreach ($part in $Chord.Trim().ToLower().Split('+')) {
+        switch -Regex ($part) {
+            '^ctrl$' { $modifiers += [byte]0x11 }
+            '^alt$' { $modifiers += [byte]0x12 }
+            '^shift$' { $modifiers += [byte]0x10 }
+            '^f([1-9]|1[0-2])$' { $key = [byte](0x6F + [int]$Matches[1]) }
+            '^[a-z0-9]$' { $key = [byte][char]($part.ToUpper()) }
+        }
+    }
+    if ($null -eq $key) {
+        return $false
+    }
+
+    foreach ($modifier in $modifiers) {
+        [Win32API]::keybd_event($modifier, 0, 0, 0)
+    }
+    [Win32API]::keybd_event($key, 0, 0, 0)
+    [Win32API]::keybd_event($key, 0, [Win32API]::KEYEVENTF_KEYUP, 0)
+    [array]::Reverse($modifiers)
+    foreach ($modifier in $modifiers) {
+        [Win32API]::keybd_event($modifier, 0, [Win32API]::KEYEVENTF_KEYUP, 0)
+    }
+    return $true
+}
+
 function Get-URLFromAddressBar {
     param($ProcessName)
     
@@ -47,34 +81,38 @@ function Get-URLFromAddressBar {
         }
         $url = $null
                 
-        # Method 1: Ctrl+L -> C (fast)
-        [Win32API]::keybd_event([Win32API]::VK_CONTROL, 0, 0, 0)
-        [Win32API]::keybd_event([Win32API]::VK_L, 0, 0, 0)
-        Start-Sleep -Milliseconds 30
-        [Win32API]::keybd_event([Win32API]::VK_C, 0, 0, 0)
-        [Win32API]::keybd_event([Win32API]::VK_L, 0, [Win32API]::KEYEVENTF_KEYUP, 0)
-        [Win32API]::keybd_event([Win32API]::VK_C, 0, [Win32API]::KEYEVENTF_KEYUP, 0)
-        [Win32API]::keybd_event([Win32API]::VK_CONTROL, 0, [Win32API]::KEYEVENTF_KEYUP, 0)
-        Start-Sleep -Milliseconds 50
-        
-        try {
-            $url = [System.Windows.Forms.Clipboard]::GetText().Trim()
-            if ($url -and (($url -match '^https?://') -or ($url -match '^file://'))) {
-                Write-Host "Fast method success: $url" -ForegroundColor Green
-                # Escape key
-                [Win32API]::keybd_event([Win32API]::VK_ESCAPE, 0, 0, 0)
-                Start-Sleep -Milliseconds 50
-                [Win32API]::keybd_event([Win32API]::VK_ESCAPE, 0, [Win32API]::KEYEVENTF_KEYUP, 0)
-                # Restore clipboard
-                try {
-                    if ($originalClipboard) {
-                        [System.Windows.Forms.Clipboard]::SetText($originalClipboard)
-                    }
-                } catch { }
-                return $url
+        # Method 1: configured shortcut -> Ctrl+C (fast)
+        foreach ($chord in $Shortcuts.Split(',')) {
+            if (-not (Send-Chord $chord)) {
+                Write-Host "Unknown shortcut: $chord" -ForegroundColor Yellow
+                continue
+            }
+            Start-Sleep -Milliseconds 30
+            [Win32API]::keybd_event([Win32API]::VK_CONTROL, 0, 0, 0)
+            [Win32API]::keybd_event([Win32API]::VK_C, 0, 0, 0)
+            [Win32API]::keybd_event([Win32API]::VK_C, 0, [Win32API]::KEYEVENTF_KEYUP, 0)
+            [Win32API]::keybd_event([Win32API]::VK_CONTROL, 0, [Win32API]::KEYEVENTF_KEYUP, 0)
+            Start-Sleep -Milliseconds 50
+
+            try {
+                $url = [System.Windows.Forms.Clipboard]::GetText().Trim()
+                if ($url -and (($url -match '^https?://') -or ($url -match '^file://'))) {
+                    Write-Host "Fast method success ($chord): $url" -ForegroundColor Green
+                    # Escape key
+                    [Win32API]::keybd_event([Win32API]::VK_ESCAPE, 0, 0, 0)
+                    Start-Sleep -Milliseconds 50
+                    [Win32API]::keybd_event([Win32API]::VK_ESCAPE, 0, [Win32API]::KEYEVENTF_KEYUP, 0)
+                    # Restore clipboard
+                    try {
+                        if ($originalClipboard) {
+                            [System.Windows.Forms.Clipboard]::SetText($originalClipboard)
+                        }
+                    } catch { }
+                    return $url
+                }
+            } catch {
+                Write-Host "Fast method failed ($chord)" -ForegroundColor Yellow
             }
-        } catch {
-            Write-Host "Fast method failed" -ForegroundColor Yellow
         }
 
         
@@ -215,7 +253,7 @@ function Get-BrowserInfoKeyboard {
         Write-Output "$finalUrl|$cleanTitle|$processName"
         
     } catch {
-        Write-Output "ERROR|$($_.Exception.Message)|unknown"
+        Write-Output "ERROR|$($_.Exception.Message)|unknown|$($_.Exception.HResult)"
     }
 }
 
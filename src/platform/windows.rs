@@ -2,113 +2,171 @@
 // src/platform/windows.rs - ローカルscriptsディレクトリ対応
 // ================================================================================================
 
-use crate::{BrowserInfoError, BrowserType};
-use active_win_pos_rs::ActiveWindow;
-use std::path::Path;
-use std::process::Command;
-use std::time::{Duration, Instant};
+use crate::WindowHandleInfo;
+use crate::audit::{self, AuditAction};
+use crate::focus::{self, BrowserFocusTarget};
+use crate::process_runner::ProcessRunner;
+use crate::script_error;
+use crate::shortcut::{KeyChord, ShortcutKey};
+use crate::temp_script::TempScript;
+use crate::url_extraction::AttemptOutcome;
+use crate::url_extraction::{ExtractionAttempt, UrlSource};
+use crate::{BrowserInfoError, BrowserType, ExtractionConfig};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Windows環境でのURL抽出メイン関数
+///
+/// Only accurate methods run here; title guessing is left to the caller.
 pub fn extract_url(
-    window: &ActiveWindow,
-    _browser_type: &BrowserType,
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+    config: &ExtractionConfig,
+    attempts: &mut Vec<ExtractionAttempt>,
 ) -> Result<String, BrowserInfoError> {
-    println!(
-        "🔍 Windows URL extraction for: {app_name}",
+    log::debug!(
+        "Windows URL extraction for: {app_name}",
         app_name = window.app_name
     );
 
-    // ローカルPowerShellスクリプトを実行
-    if let Ok(url) = try_local_powershell_script() {
-        println!("✅ Local PowerShell script succeeded: {url}");
-        return Ok(url);
+    // 認証ダイアログ等にキー入力を送らない
+    if let Some(dialog) = crate::dialog::blocking_dialog(window) {
+        attempts.push(ExtractionAttempt {
+            source: UrlSource::PowerShell,
+            outcome: AttemptOutcome::Skipped(format!("{dialog:?} dialog is open")),
+        });
+        return Err(BrowserInfoError::BlockedByDialog(dialog));
     }
 
-    // フォールバック: 内蔵スクリプト
-    if let Ok(url) = try_embedded_powershell_script() {
-        println!("✅ Embedded PowerShell script succeeded: {url}");
+    // UI Automation / MSAA はキー入力なしで読めるので最初に試す。
+    // Firefox はページ (Document) 自体が URL を公開していて、アドレスバーが
+    // 隠れる全画面表示でも読める。UIA の ValuePattern を返さない Chromium
+    // ビルドには MSAA / IAccessible2 を使う
+    if let Some(url) = accessible_url(window, browser_type, attempts) {
         return Ok(url);
     }
 
-    // 最終フォールバック: タイトルベース
-    println!("⚠️  PowerShell extraction failed, using title fallback");
-    extract_url_from_title(&window.title)
-}
+    // 両スクリプトとも Ctrl+L / Ctrl+C を送信するため、無効時は実行しない
+    if !config.allow_keyboard_simulation {
+        attempts.push(ExtractionAttempt {
+            source: UrlSource::PowerShell,
+            outcome: AttemptOutcome::Skipped("keyboard simulation disabled".to_string()),
+        });
+        return Err(BrowserInfoError::ExtractionRefused(
+            "PowerShell extraction requires keyboard simulation".to_string(),
+        ));
+    }
+
+    // 入力中のアドレスバーを Ctrl+L / Ctrl+C で上書きしない
+    if config.detect_focus_target && focus::focus_target() == Some(BrowserFocusTarget::AddressBar) {
+        attempts.push(ExtractionAttempt {
+            source: UrlSource::PowerShell,
+            outcome: AttemptOutcome::Skipped("user is typing in the address bar".to_string()),
+        });
+        return Err(BrowserInfoError::ExtractionRefused(
+            "keyboard extraction would overwrite the address bar input".to_string(),
+        ));
+    }
 
-/// ローカルPowerShellスクリプトを実行
-fn try_local_powershell_script() -> Result<String, BrowserInfoError> {
-    // ローカルスクリプトパスの候補
-    let script_paths = [
-        // メインの場所
-        "src/platform/scripts/windows_get_url.ps1",
-        // 開発時の相対パス
-        "platform/scripts/windows_get_url.ps1",
-        "scripts/windows_get_url.ps1",
-        // 実行時の相対パス（targetディレクトリから）
-        "../src/platform/scripts/windows_get_url.ps1",
-        "../../src/platform/scripts/windows_get_url.ps1",
-        "../../../src/platform/scripts/windows_get_url.ps1",
+    // Both scripts start PowerShell, press Ctrl+L/Ctrl+C and read the clipboard
+    let intrusive_actions = [
+        AuditAction::ScriptExecution,
+        AuditAction::KeyboardSimulation,
+        AuditAction::ClipboardAccess,
     ];
 
-    for script_path in &script_paths {
-        if Path::new(script_path).exists() {
-            println!("📁 Found PowerShell script at: {script_path}");
-            return execute_powershell_file(script_path);
-        }
+    // ローカルPowerShellスクリプトを実行
+    audit::record(
+        config,
+        &intrusive_actions,
+        "powershell_local_script",
+        window.process_id,
+    );
+    let shortcuts = config.address_bar_shortcuts_for(browser_type);
+    let result = try_local_powershell_script(config, &shortcuts);
+    ExtractionAttempt::record(attempts, UrlSource::PowerShell, &result);
+    if let Ok(url) = result {
+        log::debug!("Local PowerShell script succeeded: {url}");
+        return Ok(url);
     }
 
-    Err(BrowserInfoError::PlatformError(
-        "PowerShell script not found. Expected at: src/platform/scripts/windows_get_url.ps1"
-            .to_string(),
-    ))
+    // フォールバック: 内蔵スクリプト
+    audit::record(
+        config,
+        &intrusive_actions,
+        "powershell_embedded_script",
+        window.process_id,
+    );
+    let result = try_embedded_powershell_script(&shortcuts);
+    ExtractionAttempt::record(attempts, UrlSource::PowerShell, &result);
+    if let Ok(url) = &result {
+        log::debug!("Embedded PowerShell script succeeded: {url}");
+    } else {
+        log::warn!("PowerShell extraction failed");
+    }
+    result
 }
 
-/// PowerShellファイルを実行
-fn execute_powershell_file(script_path: &str) -> Result<String, BrowserInfoError> {
-    let start_time = Instant::now();
-    let timeout = Duration::from_secs(10);
-
-    println!("🔧 Executing PowerShell file: {script_path}");
-
-    let output = Command::new("powershell")
-        .args([
-            "-ExecutionPolicy",
-            "Bypass",
-            "-NoProfile",
-            "-File",
-            script_path,
-        ])
-        .output()
-        .map_err(|e| {
-            BrowserInfoError::PlatformError(format!("PowerShell file execution error: {e}"))
-        })?;
-
-    if start_time.elapsed() > timeout {
-        return Err(BrowserInfoError::Timeout);
-    }
+/// 同梱PowerShellスクリプトを一時ファイルとして実行
+///
+/// カレントディレクトリのスクリプトは実行しない（差し替えられる恐れがあるため）
+fn try_local_powershell_script(
+    config: &ExtractionConfig,
+    shortcuts: &[KeyChord],
+) -> Result<String, BrowserInfoError> {
+    let script = TempScript::write(config, "ps1", include_str!("scripts/windows_get_url.ps1"))?;
+    log::debug!(
+        "PowerShell script materialized at: {}",
+        script.path().display()
+    );
+    let shortcuts: Vec<String> = shortcuts.iter().map(KeyChord::to_string).collect();
+    execute_powershell_file(script.path(), &shortcuts.join(","))
+}
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if !stderr.is_empty() {
-        println!("⚠️ PowerShell stderr: {stderr}");
-    }
+/// Windows PowerShell by absolute path
+///
+/// Independent of the host's `PATH`. x64 builds running emulated on Windows on
+/// ARM see the same System32 directory, so this also finds the native build there.
+fn powershell() -> ProcessRunner {
+    let exe = std::env::var_os("SystemRoot")
+        .map(|root| PathBuf::from(root).join(r"System32\WindowsPowerShell\v1.0\powershell.exe"))
+        .filter(|exe| exe.is_file())
+        .unwrap_or_else(|| PathBuf::from("powershell"));
+    ProcessRunner::new(exe)
+}
 
-    if !output.status.success() {
-        return Err(BrowserInfoError::PlatformError(format!(
-            "PowerShell script failed with exit code: {}",
-            output.status
-        )));
-    }
+/// PowerShellファイルを実行
+fn execute_powershell_file(
+    script_path: &Path,
+    shortcuts: &str,
+) -> Result<String, BrowserInfoError> {
+    log::debug!("Executing PowerShell file: {}", script_path.display());
 
-    let stdout = String::from_utf8(output.stdout).map_err(|e| {
-        BrowserInfoError::PlatformError(format!("PowerShell output parsing error: {e}"))
-    })?;
+    // OsStr のまま渡す（空白・非ASCII・長いパスでも壊れない）
+    let stdout = powershell()
+        .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-File"])
+        .arg(script_path)
+        .args(["-Shortcuts", shortcuts])
+        .timeout(Duration::from_secs(10))
+        .run()?
+        .into_stdout("PowerShell script")?;
 
     parse_atode_powershell_output(&stdout)
 }
 
 /// 内蔵PowerShellスクリプト（フォールバック）
-fn try_embedded_powershell_script() -> Result<String, BrowserInfoError> {
-    println!("🔧 Falling back to embedded PowerShell script...");
+///
+/// Only the first configured shortcut is pressed.
+fn try_embedded_powershell_script(shortcuts: &[KeyChord]) -> Result<String, BrowserInfoError> {
+    log::debug!("Falling back to embedded PowerShell script...");
+
+    let keys: Vec<String> = shortcuts
+        .first()
+        .map(virtual_keys)
+        .unwrap_or_default()
+        .iter()
+        .map(|key| format!("0x{key:02X}"))
+        .collect();
 
     let script = r#"
         [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
@@ -121,7 +179,6 @@ fn try_embedded_powershell_script() -> Result<String, BrowserInfoError> {
                 [DllImport("user32.dll")] public static extern void keybd_event(byte bVk, byte bScan, int dwFlags, int dwExtraInfo);
                 public const int KEYEVENTF_KEYUP = 0x0002;
                 public const byte VK_CONTROL = 0x11;
-                public const byte VK_L = 0x4C;
                 public const byte VK_C = 0x43;
                 public const byte VK_ESCAPE = 0x1B;
             }
@@ -131,12 +188,14 @@ fn try_embedded_powershell_script() -> Result<String, BrowserInfoError> {
             $originalClipboard = ""
             try { $originalClipboard = [System.Windows.Forms.Clipboard]::GetText() } catch {}
             
-            # Ctrl+L -> Ctrl+C
-            [BrowserAPI]::keybd_event([BrowserAPI]::VK_CONTROL, 0, 0, 0)
-            [BrowserAPI]::keybd_event([BrowserAPI]::VK_L, 0, 0, 0)
+            # Address-bar shortcut -> Ctrl+C
+            $keys = @(__SHORTCUT_KEYS__)
+            foreach ($key in $keys) { [BrowserAPI]::keybd_event($key, 0, 0, 0) }
+            [array]::Reverse($keys)
+            foreach ($key in $keys) { [BrowserAPI]::keybd_event($key, 0, [BrowserAPI]::KEYEVENTF_KEYUP, 0) }
             Start-Sleep -Milliseconds 50
+            [BrowserAPI]::keybd_event([BrowserAPI]::VK_CONTROL, 0, 0, 0)
             [BrowserAPI]::keybd_event([BrowserAPI]::VK_C, 0, 0, 0)
-            [BrowserAPI]::keybd_event([BrowserAPI]::VK_L, 0, [BrowserAPI]::KEYEVENTF_KEYUP, 0)
             [BrowserAPI]::keybd_event([BrowserAPI]::VK_C, 0, [BrowserAPI]::KEYEVENTF_KEYUP, 0)
             [BrowserAPI]::keybd_event([BrowserAPI]::VK_CONTROL, 0, [BrowserAPI]::KEYEVENTF_KEYUP, 0)
             Start-Sleep -Milliseconds 100
@@ -156,19 +215,40 @@ fn try_embedded_powershell_script() -> Result<String, BrowserInfoError> {
                 Write-Output "FAILED|Invalid URL format: $url|embedded"
             }
         } catch {
-            Write-Output "ERROR|$($_.Exception.Message)|embedded"
+            Write-Output "ERROR|$($_.Exception.Message)|embedded|$($_.Exception.HResult)"
         }
     "#;
 
-    execute_embedded_powershell_script(script)
+    execute_embedded_powershell_script(&script.replace("__SHORTCUT_KEYS__", &keys.join(", ")))
+}
+
+/// Virtual-key codes of `chord`, modifiers first
+fn virtual_keys(chord: &KeyChord) -> Vec<u8> {
+    const VK_SHIFT: u8 = 0x10;
+    const VK_CONTROL: u8 = 0x11;
+    const VK_MENU: u8 = 0x12;
+
+    let mut keys = Vec::new();
+    if chord.primary {
+        keys.push(VK_CONTROL);
+    }
+    if chord.alt {
+        keys.push(VK_MENU);
+    }
+    if chord.shift {
+        keys.push(VK_SHIFT);
+    }
+    keys.push(match chord.key {
+        // VK_A-VK_Z / VK_0-VK_9 は ASCII 大文字と同じ値
+        ShortcutKey::Char(key) => key.to_ascii_uppercase() as u8,
+        ShortcutKey::Function(number) => 0x6F + number.clamp(1, 12),
+    });
+    keys
 }
 
 /// 内蔵PowerShellスクリプト実行
 fn execute_embedded_powershell_script(script: &str) -> Result<String, BrowserInfoError> {
-    let start_time = Instant::now();
-    let timeout = Duration::from_secs(5);
-
-    let output = Command::new("powershell")
+    let stdout = powershell()
         .args([
             "-ExecutionPolicy",
             "Bypass",
@@ -176,31 +256,15 @@ fn execute_embedded_powershell_script(script: &str) -> Result<String, BrowserInf
             "-Command",
             script,
         ])
-        .output()
-        .map_err(|e| {
-            BrowserInfoError::PlatformError(format!("Embedded PowerShell execution error: {e}"))
-        })?;
-
-    if start_time.elapsed() > timeout {
-        return Err(BrowserInfoError::Timeout);
-    }
-
-    if !output.status.success() {
-        return Err(BrowserInfoError::PlatformError(
-            "Embedded PowerShell script failed".to_string(),
-        ));
-    }
-
-    let stdout = String::from_utf8(output.stdout).map_err(|e| {
-        BrowserInfoError::PlatformError(format!("Embedded script output parsing error: {e}"))
-    })?;
+        .run()?
+        .into_stdout("Embedded PowerShell script")?;
 
     parse_simple_powershell_output(&stdout)
 }
 
 /// AtodeスタイルのPowerShell出力解析
 fn parse_atode_powershell_output(output: &str) -> Result<String, BrowserInfoError> {
-    println!("🔍 Parsing Atode-style PowerShell output...");
+    log::debug!("Parsing Atode-style PowerShell output...");
 
     let lines: Vec<&str> = output.lines().collect();
 
@@ -218,7 +282,7 @@ fn parse_atode_powershell_output(output: &str) -> Result<String, BrowserInfoErro
         ));
     }
 
-    println!("📤 PowerShell result line: {result_line}");
+    log::debug!("PowerShell result line: {result_line}");
 
     let parts: Vec<&str> = result_line.split('|').collect();
 
@@ -228,7 +292,10 @@ fn parse_atode_powershell_output(output: &str) -> Result<String, BrowserInfoErro
         // エラーチェック
         if url.starts_with("ERROR") {
             let error_msg = parts.get(1).unwrap_or(&"Unknown error").trim();
-            return Err(BrowserInfoError::PlatformError(error_msg.to_string()));
+            return Err(script_error::from_script_error(
+                error_msg,
+                parts.get(3).copied(),
+            ));
         }
 
         if url.starts_with("NOT_BROWSER") {
@@ -240,7 +307,7 @@ fn parse_atode_powershell_output(output: &str) -> Result<String, BrowserInfoErro
             let title = parts.get(1).unwrap_or(&"").trim();
             let process = parts.get(2).unwrap_or(&"").trim();
 
-            println!("✅ Parsed - URL: {url}, Title: {title}, Process: {process}",);
+            log::debug!("Parsed - URL: {url}, Title: {title}, Process: {process}",);
             Ok(url.to_string())
         } else {
             Err(BrowserInfoError::InvalidUrl(format!(
@@ -283,7 +350,10 @@ fn parse_simple_powershell_output(output: &str) -> Result<String, BrowserInfoErr
                 }
             }
             "FAILED" => Err(BrowserInfoError::UrlExtractionFailed(parts[1].to_string())),
-            "ERROR" => Err(BrowserInfoError::PlatformError(parts[1].to_string())),
+            "ERROR" => Err(script_error::from_script_error(
+                parts[1],
+                parts.get(3).copied(),
+            )),
             _ => Err(BrowserInfoError::UrlExtractionFailed(
                 "Unknown embedded script output format".to_string(),
             )),
@@ -295,25 +365,970 @@ fn parse_simple_powershell_output(output: &str) -> Result<String, BrowserInfoErr
     }
 }
 
-/// タイトルからのURL推測（最終フォールバック）
-fn extract_url_from_title(title: &str) -> Result<String, BrowserInfoError> {
-    println!("🔍 Final fallback: extracting URL from title: {title}");
-
-    let title_lower = title.to_lowercase();
-
-    if title_lower.contains("claude") {
-        Ok("https://claude.ai/chat".to_string())
-    } else if title_lower.contains("github") {
-        Ok("https://github.com".to_string())
-    } else if title_lower.contains("google") {
-        Ok("https://www.google.com".to_string())
-    } else if title_lower.contains("youtube") {
-        Ok("https://www.youtube.com".to_string())
-    } else if title_lower.contains("stackoverflow") {
-        Ok("https://stackoverflow.com".to_string())
-    } else {
-        Err(BrowserInfoError::UrlExtractionFailed(format!(
-            "Cannot determine URL from title: {title}",
-        )))
+/// Accessibility read of one [`accessible_url`] run, in the order they are tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccessibleRead {
+    /// Firefox のページ要素 (ControlType.Document) の ValuePattern = 表示中の文書の URL
+    ///
+    /// Background tabs keep their documents in the tree but offscreen, and
+    /// iframes are nested documents, so the first onscreen document in tree
+    /// order is the selected tab. Unlike the URL bar, it is there in
+    /// fullscreen and kiosk mode.
+    Document,
+    /// UI Automation でアドレスバーの値を読む
+    ///
+    /// Firefox's URL bar has a stable AutomationId; the Chromium-based
+    /// browsers don't, but the omnibox is the first Edit control of the window.
+    AddressBar,
+    /// MSAA (IAccessible / IAccessible2) でオムニボックスの値を読む
+    ///
+    /// Some Chromium builds expose the omnibox without a UIA value pattern but
+    /// still answer MSAA, where it is the first editable text
+    /// (`ROLE_SYSTEM_TEXT`) with a value in the window's accessibility tree.
+    /// Querying the tree also switches on Chromium's accessibility support.
+    Msaa,
+}
+
+impl AccessibleRead {
+    /// Reads for `browser_type`; empty when there is no query for it
+    fn for_browser(browser_type: &BrowserType) -> &'static [AccessibleRead] {
+        match browser_type {
+            BrowserType::Firefox | BrowserType::Tor => {
+                &[AccessibleRead::Document, AccessibleRead::AddressBar]
+            }
+            chromium if is_chromium(chromium) => {
+                &[AccessibleRead::AddressBar, AccessibleRead::Msaa]
+            }
+            _ => &[],
+        }
+    }
+
+    /// Prefix of the script's output line carrying this read's value
+    fn tag(self) -> &'static str {
+        match self {
+            AccessibleRead::Document => "DOC|",
+            AccessibleRead::AddressBar => "UIA|",
+            AccessibleRead::Msaa => "MSAA|",
+        }
+    }
+
+    fn source(self) -> UrlSource {
+        match self {
+            AccessibleRead::Document | AccessibleRead::AddressBar => UrlSource::UiAutomation,
+            AccessibleRead::Msaa => UrlSource::Msaa,
+        }
+    }
+
+    /// Script block; runs only while no earlier read found a value
+    fn script(self, browser_type: &BrowserType, handle: isize) -> String {
+        match self {
+            AccessibleRead::Document => r#"
+        if (-not $found -and $root) {
+            $condition = New-Object System.Windows.Automation.PropertyCondition([System.Windows.Automation.AutomationElement]::ControlTypeProperty, [System.Windows.Automation.ControlType]::Document)
+            foreach ($document in $root.FindAll([System.Windows.Automation.TreeScope]::Descendants, $condition)) {
+                if ($document.Current.IsOffscreen) { continue }
+                $pattern = $null
+                if ($document.TryGetCurrentPattern([System.Windows.Automation.ValuePattern]::Pattern, [ref]$pattern)) {
+                    $value = $pattern.Current.Value
+                    if ($value) { Write-Output "DOC|$value"; $found = $true }
+                    break
+                }
+            }
+        }
+"#
+            .to_string(),
+            AccessibleRead::AddressBar => {
+                let condition = if is_chromium(browser_type) {
+                    "New-Object System.Windows.Automation.PropertyCondition([System.Windows.Automation.AutomationElement]::ControlTypeProperty, [System.Windows.Automation.ControlType]::Edit)"
+                } else {
+                    "New-Object System.Windows.Automation.PropertyCondition([System.Windows.Automation.AutomationElement]::AutomationIdProperty, 'urlbar-input')"
+                };
+                format!(
+                    r#"
+        if (-not $found -and $root) {{
+            $bar = $root.FindFirst([System.Windows.Automation.TreeScope]::Descendants, ({condition}))
+            $pattern = $null
+            if ($bar -and $bar.TryGetCurrentPattern([System.Windows.Automation.ValuePattern]::Pattern, [ref]$pattern)) {{
+                $value = $pattern.Current.Value
+                if ($value) {{ Write-Output "UIA|$value"; $found = $true }}
+            }}
+        }}
+"#
+                )
+            }
+            // The C# helper is compiled only when UIA found nothing
+            AccessibleRead::Msaa => format!(
+                r#"
+        if (-not $found) {{
+            Add-Type -ReferencedAssemblies Accessibility -TypeDefinition @"
+            using System;
+            using System.Runtime.InteropServices;
+            using Accessibility;
+
+            public static class Msaa {{
+                [DllImport("oleacc.dll")]
+                static extern int AccessibleObjectFromWindow(IntPtr hwnd, uint id, ref Guid iid,
+                    [MarshalAs(UnmanagedType.IUnknown)] out object accessible);
+                [DllImport("oleacc.dll")]
+                static extern int AccessibleChildren(IAccessible parent, int start, int count,
+                    [Out] object[] children, out int obtained);
+
+                const uint OBJID_CLIENT = 0xFFFFFFFC;
+                const int ROLE_SYSTEM_TEXT = 0x2A;
+
+                public static string AddressBar(IntPtr hwnd) {{
+                    Guid iid = new Guid("618736E0-3C3D-11CF-810C-00AA00389B71");
+                    object root;
+                    if (AccessibleObjectFromWindow(hwnd, OBJID_CLIENT, ref iid, out root) != 0) return null;
+                    return Find(root as IAccessible, 0);
+                }}
+
+                static string Find(IAccessible parent, int depth) {{
+                    if (parent == null || depth > 16) return null;
+                    int count;
+                    try {{ count = parent.accChildCount; }} catch {{ return null; }}
+                    if (count == 0) return null;
+                    object[] children = new object[count];
+                    int obtained;
+                    if (AccessibleChildren(parent, 0, count, children, out obtained) != 0) return null;
+                    for (int i = 0; i < obtained; i++) {{
+                        IAccessible child = children[i] as IAccessible;
+                        if (child == null) continue;
+                        try {{
+                            object role = child.get_accRole(0);
+                            string value = child.get_accValue(0);
+                            if (role is int && (int)role == ROLE_SYSTEM_TEXT && !String.IsNullOrEmpty(value)) return value;
+                        }} catch {{ }}
+                        string found = Find(child, depth + 1);
+                        if (found != null) return found;
+                    }}
+                    return null;
+                }}
+            }}
+"@
+            $value = [Msaa]::AddressBar([IntPtr]{handle})
+            if ($value) {{ Write-Output "MSAA|$value"; $found = $true }}
+        }}
+"#
+            ),
+        }
+    }
+
+    /// The value this read produced, checked for being a URL
+    fn url(self, value: &str) -> Result<String, BrowserInfoError> {
+        match self {
+            // 文書の値は常に完全な URL (about:, moz-extension: を含む)
+            AccessibleRead::Document if !value.contains(':') => Err(BrowserInfoError::InvalidUrl(
+                format!("Document value is not a URL: {value}"),
+            )),
+            AccessibleRead::Document => Ok(value.to_string()),
+            AccessibleRead::AddressBar | AccessibleRead::Msaa => address_bar_url(value),
+        }
+    }
+
+    fn not_found(self) -> BrowserInfoError {
+        BrowserInfoError::UrlExtractionFailed(
+            match self {
+                AccessibleRead::Document => "Document URL not found via UI Automation",
+                AccessibleRead::AddressBar => "Address bar not found via UI Automation",
+                AccessibleRead::Msaa => "Address bar not found via MSAA",
+            }
+            .to_string(),
+        )
+    }
+}
+
+/// UI Automation / MSAA でページの URL を読む（キー入力・クリップボード不使用）
+///
+/// All reads for the browser run in one PowerShell process, each only while
+/// the earlier ones found nothing; every read that ran is recorded in
+/// `attempts`. A read that found a value which is not a URL (the user's
+/// unfinished input) ends the run, since the later reads see the same field.
+fn accessible_url(
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+    attempts: &mut Vec<ExtractionAttempt>,
+) -> Option<String> {
+    let reads = AccessibleRead::for_browser(browser_type);
+    if reads.is_empty() {
+        ExtractionAttempt::record(
+            attempts,
+            UrlSource::UiAutomation,
+            &Err(BrowserInfoError::UrlExtractionFailed(format!(
+                "No UI Automation query for {browser_type:?}"
+            ))),
+        );
+        return None;
+    }
+    let Some(handle) = window_handle(window) else {
+        ExtractionAttempt::record(
+            attempts,
+            reads[0].source(),
+            &Err(BrowserInfoError::PlatformError(format!(
+                "Unexpected window id: {}",
+                window.window_id
+            ))),
+        );
+        return None;
+    };
+
+    let steps: String = reads
+        .iter()
+        .map(|read| read.script(browser_type, handle))
+        .collect();
+    let script = format!(
+        r#"
+        [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+        Add-Type -AssemblyName UIAutomationClient
+        Add-Type -AssemblyName UIAutomationTypes
+        $root = [System.Windows.Automation.AutomationElement]::FromHandle([IntPtr]{handle})
+        $found = $false
+{steps}
+    "#
+    );
+
+    let output = match powershell().args(["-NoProfile", "-Command", &script]).run() {
+        Ok(output) => output.stdout,
+        Err(e) => {
+            ExtractionAttempt::record(attempts, reads[0].source(), &Err(e));
+            return None;
+        }
+    };
+
+    for read in reads {
+        let value = output
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix(read.tag()))
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        let result = value.map_or_else(|| Err(read.not_found()), |value| read.url(value));
+        ExtractionAttempt::record(attempts, read.source(), &result);
+        match result {
+            Ok(url) => {
+                log::debug!("{read:?} read succeeded: {url}");
+                return Some(url);
+            }
+            // 後続の読み取りは同じ入力欄を見るだけ
+            Err(_) if value.is_some() => return None,
+            Err(_) => {}
+        }
+    }
+    None
+}
+
+fn is_chromium(browser_type: &BrowserType) -> bool {
+    matches!(
+        browser_type,
+        BrowserType::Chrome
+            | BrowserType::Edge
+            | BrowserType::Brave
+            | BrowserType::Opera
+            | BrowserType::Vivaldi
+    )
+}
+
+/// Chromium hides the scheme in the omnibox and it cannot be told from the
+/// text, so such a value is returned as shown (`example.com/path`); anything
+/// that isn't a URL is the user's unfinished input
+fn address_bar_url(value: &str) -> Result<String, BrowserInfoError> {
+    if value.contains("://") || value.starts_with("about:") {
+        return Ok(value.to_string());
+    }
+    let host = value.split(['/', '?', '#']).next().unwrap_or_default();
+    if value.contains(' ') || !host.contains('.') {
+        return Err(BrowserInfoError::InvalidUrl(format!(
+            "Address bar does not hold a URL: {value}"
+        )));
+    }
+    Ok(value.to_string())
+}
+
+/// Command lines of the `msedgewebview2.exe` processes of a window
+///
+/// The window may belong to the WebView2 browser process itself or to the host
+/// app that started it, so both the process and its children are checked.
+pub fn webview2_command_lines(process_id: u64) -> Vec<String> {
+    let script = format!(
+        r#"
+        [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+        Get-CimInstance Win32_Process -Filter "Name = 'msedgewebview2.exe' AND (ProcessId = {process_id} OR ParentProcessId = {process_id})" |
+            ForEach-Object {{ if ($_.CommandLine) {{ Write-Output "CMD|$($_.CommandLine)" }} }}
+    "#
+    );
+
+    let Ok(output) = powershell()
+        .args(["-NoProfile", "-Command", &script])
+        .timeout(Duration::from_secs(5))
+        .run()
+    else {
+        return Vec::new();
+    };
+    output
+        .stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("CMD|"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// UI Automationでフォーカス中の要素を取得 (control type, name, automation id)
+pub fn focused_element() -> Option<(String, String, String)> {
+    let script = r#"
+        [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+        Add-Type -AssemblyName UIAutomationClient
+        $e = [System.Windows.Automation.AutomationElement]::FocusedElement
+        if ($e) {
+            Write-Output "$($e.Current.ControlType.ProgrammaticName)|$($e.Current.Name)|$($e.Current.AutomationId)"
+        }
+    "#;
+
+    let output = powershell()
+        .args(["-NoProfile", "-Command", script])
+        .run()
+        .ok()?;
+    if !output.success() {
+        return None;
+    }
+
+    let mut parts = output
+        .stdout
+        .lines()
+        .rev()
+        .find(|line| line.contains('|'))?
+        .split('|');
+    Some((
+        parts.next()?.trim().to_string(),
+        parts.next().unwrap_or_default().trim().to_string(),
+        parts.next().unwrap_or_default().trim().to_string(),
+    ))
+}
+
+/// Windows Audio Session API で音声セッションを列挙し、指定プロセス名の状態を集計
+pub fn audio_state(executable: &str) -> Option<crate::audio::BrowserAudioState> {
+    let script = r#"
+        [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+        Add-Type -TypeDefinition @"
+            using System;
+            using System.Diagnostics;
+            using System.Runtime.InteropServices;
+
+            [StructLayout(LayoutKind.Sequential)]
+            public struct PropertyKey { public Guid fmtid; public int pid; }
+
+            [StructLayout(LayoutKind.Sequential)]
+            public struct PropVariant { public ushort vt; public ushort r1, r2, r3; public IntPtr p; public IntPtr p2; }
+
+            [ComImport, Guid("BCDE0395-E52F-467C-8E3D-C4579291692E")] class MMDeviceEnumerator {}
+
+            [Guid("A95664D2-9614-4F35-A746-DE8DB63617E6"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+            interface IMMDeviceEnumerator {
+                int EnumAudioEndpoints(int dataFlow, int stateMask, out IMMDeviceCollection devices);
+            }
+
+            [Guid("0BD7A1BE-7A1A-44DB-8397-CC5392387B5E"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+            interface IMMDeviceCollection {
+                int GetCount(out int count);
+                int Item(int index, out IMMDevice device);
+            }
+
+            [Guid("D666063F-1587-4E43-81F1-B948E807363F"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+            interface IMMDevice {
+                int Activate(ref Guid iid, int clsCtx, IntPtr activationParams, [MarshalAs(UnmanagedType.IUnknown)] out object iface);
+                int OpenPropertyStore(int access, out IPropertyStore store);
+            }
+
+            [Guid("886d8eeb-8cf2-4446-8d02-cdba1dbdcf99"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+            interface IPropertyStore {
+                int GetCount(out int count);
+                int GetAt(int index, out PropertyKey key);
+                int GetValue(ref PropertyKey key, out PropVariant value);
+            }
+
+            [Guid("77AA99A0-1BD6-484F-8BC7-2C654C9A9B6F"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+            interface IAudioSessionManager2 {
+                int GetAudioSessionControl(IntPtr groupingParam, int flags, out IntPtr control);
+                int GetSimpleAudioVolume(IntPtr groupingParam, int flags, out IntPtr volume);
+                int GetSessionEnumerator(out IAudioSessionEnumerator sessions);
+            }
+
+            [Guid("E2F5BB11-0570-40CA-ACDD-3AA01277DEE8"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+            interface IAudioSessionEnumerator {
+                int GetCount(out int count);
+                int GetSession(int index, out IAudioSessionControl2 session);
+            }
+
+            [Guid("bfb7ff88-7239-4fc9-8fa2-07c950be9c6d"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+            interface IAudioSessionControl2 {
+                int GetState(out int state);
+                int GetDisplayName(out IntPtr name);
+                int SetDisplayName(IntPtr name, IntPtr context);
+                int GetIconPath(out IntPtr path);
+                int SetIconPath(IntPtr path, IntPtr context);
+                int GetGroupingParam(out Guid param);
+                int SetGroupingParam(IntPtr param, IntPtr context);
+                int RegisterAudioSessionNotification(IntPtr client);
+                int UnregisterAudioSessionNotification(IntPtr client);
+                int GetSessionIdentifier(out IntPtr id);
+                int GetSessionInstanceIdentifier(out IntPtr id);
+                int GetProcessId(out int pid);
+            }
+
+            [Guid("87CE5498-68D6-44E5-9215-6DA47EF883D8"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+            interface ISimpleAudioVolume {
+                int SetMasterVolume(float level, IntPtr context);
+                int GetMasterVolume(out float level);
+                int SetMute(bool mute, IntPtr context);
+                int GetMute(out bool mute);
+            }
+
+            [Guid("C02216F6-8C67-4B5B-9D00-D008E73E0064"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+            interface IAudioMeterInformation {
+                int GetPeakValue(out float peak);
+            }
+
+            public static class AudioSessions {
+                public static void Dump() {
+                    var enumerator = (IMMDeviceEnumerator)new MMDeviceEnumerator();
+                    IMMDeviceCollection devices;
+                    enumerator.EnumAudioEndpoints(0 /* eRender */, 1 /* ACTIVE */, out devices);
+                    int deviceCount; devices.GetCount(out deviceCount);
+                    var managerIid = typeof(IAudioSessionManager2).GUID;
+                    var nameKey = new PropertyKey { fmtid = new Guid("a45c254e-df1c-4efd-8020-67d146a850e0"), pid = 14 };
+
+                    for (int d = 0; d < deviceCount; d++) {
+                        IMMDevice device; devices.Item(d, out device);
+                        IPropertyStore store; device.OpenPropertyStore(0, out store);
+                        PropVariant name; store.GetValue(ref nameKey, out name);
+                        string deviceName = name.vt == 31 ? Marshal.PtrToStringUni(name.p) : "";
+
+                        object o; device.Activate(ref managerIid, 23 /* CLSCTX_ALL */, IntPtr.Zero, out o);
+                        IAudioSessionEnumerator sessions;
+                        ((IAudioSessionManager2)o).GetSessionEnumerator(out sessions);
+                        int count; sessions.GetCount(out count);
+
+                        for (int i = 0; i < count; i++) {
+                            IAudioSessionControl2 session; sessions.GetSession(i, out session);
+                            int pid; session.GetProcessId(out pid);
+                            if (pid == 0) continue;
+                            string process;
+                            try { process = Process.GetProcessById(pid).ProcessName; } catch { continue; }
+                            float peak; ((IAudioMeterInformation)session).GetPeakValue(out peak);
+                            float volume; ((ISimpleAudioVolume)session).GetMasterVolume(out volume);
+                            bool muted; ((ISimpleAudioVolume)session).GetMute(out muted);
+                            Console.WriteLine("AUDIO|" + process + "|" + peak.ToString(System.Globalization.CultureInfo.InvariantCulture)
+                                + "|" + volume.ToString(System.Globalization.CultureInfo.InvariantCulture)
+                                + "|" + muted + "|" + deviceName);
+                        }
+                    }
+                }
+            }
+"@
+        [AudioSessions]::Dump()
+    "#;
+
+    let output = powershell()
+        .args(["-NoProfile", "-Command", script])
+        .run()
+        .ok()?;
+    if !output.success() {
+        return None;
+    }
+
+    // "AUDIO|chrome|0.1234|1|False|Speakers (Realtek Audio)"
+    let stdout = output.stdout;
+    let mut state: Option<crate::audio::BrowserAudioState> = None;
+    for line in stdout.lines() {
+        let parts: Vec<&str> = line.trim().splitn(6, '|').collect();
+        if parts.len() < 6 || parts[0] != "AUDIO" || !parts[1].eq_ignore_ascii_case(executable) {
+            continue;
+        }
+        let peak: f32 = parts[2].parse().unwrap_or(0.0);
+        let volume: Option<f32> = parts[3].parse().ok();
+        let muted = parts[4].eq_ignore_ascii_case("true");
+        let device = Some(parts[5].to_string()).filter(|name| !name.is_empty());
+        let playing = peak > 0.001;
+
+        // 複数セッションがある場合は再生中のものを優先
+        let session = state.get_or_insert(crate::audio::BrowserAudioState {
+            is_playing: false,
+            muted: true,
+            volume: None,
+            device: None,
+        });
+        if playing && !session.is_playing {
+            session.device = device.clone();
+        }
+        session.is_playing |= playing;
+        session.muted &= muted;
+        session.volume = session.volume.max(volume);
+        session.device = session.device.take().or(device);
+    }
+    state
+}
+
+/// プロセスがまだ終了していないか
+pub fn is_process_running(process_id: u64) -> bool {
+    use winapi::shared::winerror::WAIT_TIMEOUT;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::synchapi::WaitForSingleObject;
+    use winapi::um::winnt::SYNCHRONIZE;
+
+    let handle = unsafe { OpenProcess(SYNCHRONIZE, 0, process_id as u32) };
+    if handle.is_null() {
+        // 存在しない PID は ERROR_INVALID_PARAMETER、権限不足なら存在はしている
+        return std::io::Error::last_os_error().raw_os_error() != Some(87);
+    }
+    // 終了済みのプロセスはシグナル状態
+    let running = unsafe { WaitForSingleObject(handle, 0) } == WAIT_TIMEOUT;
+    unsafe { CloseHandle(handle) };
+    running
+}
+
+/// メッセージを処理していない (応答なしの) ウィンドウか
+pub fn is_hung_window(window: &WindowHandleInfo, timeout: std::time::Duration) -> bool {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        IsHungAppWindow, IsWindow, SMTO_ABORTIFHUNG, SMTO_BLOCK, SendMessageTimeoutW, WM_NULL,
+    };
+
+    let Some(handle) = window_handle(window) else {
+        return false;
+    };
+    let hwnd = handle as HWND;
+    // 5 秒以上メッセージを処理していないと OS がハングと判定済み
+    if unsafe { IsHungAppWindow(hwnd) } != 0 {
+        return true;
+    }
+
+    let mut result = 0;
+    let answered = unsafe {
+        SendMessageTimeoutW(
+            hwnd,
+            WM_NULL,
+            0,
+            0,
+            SMTO_ABORTIFHUNG | SMTO_BLOCK,
+            timeout.as_millis() as u32,
+            &mut result,
+        )
+    };
+    // 閉じられたウィンドウも失敗するので、存在する場合だけハングとみなす
+    answered == 0 && unsafe { IsWindow(hwnd) } != 0
+}
+
+/// 拡張ウィンドウスタイル (GWL_EXSTYLE)
+pub(crate) fn window_ex_style(window: &WindowHandleInfo) -> Option<u32> {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{GWL_EXSTYLE, GetWindowLongPtrW};
+
+    let handle = window_handle(window)?;
+    let style = unsafe { GetWindowLongPtrW(handle as HWND, GWL_EXSTYLE) };
+    Some(style as u32)
+}
+
+/// active-win-pos-rs formats the handle as "HWND(123456)"
+fn window_handle(window: &WindowHandleInfo) -> Option<isize> {
+    window
+        .window_id
+        .strip_prefix("HWND(")
+        .and_then(|id| id.strip_suffix(')'))
+        .and_then(|id| id.parse().ok())
+}
+
+/// ウィンドウクラス名 ("Chrome_WidgetWin_1", "#32770", ...)
+pub(crate) fn window_class(window: &WindowHandleInfo) -> Option<String> {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::GetClassNameW;
+
+    let handle = window_handle(window)? as HWND;
+    let mut buffer = [0u16; 256];
+    let len = unsafe { GetClassNameW(handle, buffer.as_mut_ptr(), buffer.len() as i32) };
+    (len > 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+/// ブラウザウィンドウが所有するモーダルダイアログのタイトル
+pub fn owned_dialog_title(window: &WindowHandleInfo) -> Option<String> {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{GW_ENABLEDPOPUP, GetWindow, GetWindowTextW, IsWindowVisible};
+
+    let handle = window_handle(window)? as HWND;
+
+    // 所有ウィンドウが有効なポップアップを持つ = モーダル表示中
+    let popup = unsafe { GetWindow(handle, GW_ENABLEDPOPUP) };
+    if popup.is_null() || popup == handle || unsafe { IsWindowVisible(popup) } == 0 {
+        return None;
+    }
+    let mut buffer = [0u16; 256];
+    let len = unsafe { GetWindowTextW(popup, buffer.as_mut_ptr(), buffer.len() as i32) };
+    Some(String::from_utf16_lossy(&buffer[..len.max(0) as usize]))
+}
+
+/// プロセスのメインウィンドウを前面に出す
+pub fn focus_process_window(process_id: u64) -> Result<(), BrowserInfoError> {
+    use winapi::shared::minwindef::{BOOL, FALSE, LPARAM, TRUE};
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        EnumWindows, GW_OWNER, GetWindow, GetWindowTextLengthW, GetWindowThreadProcessId, IsIconic,
+        IsWindowVisible, SW_RESTORE, SetForegroundWindow, ShowWindow,
+    };
+
+    // 所有者なし・可視・タイトルありのトップレベルウィンドウ = メインウィンドウ
+    unsafe extern "system" fn find(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        unsafe {
+            let search = &mut *(lparam as *mut (u32, HWND));
+            let mut pid = 0;
+            GetWindowThreadProcessId(hwnd, &mut pid);
+            if pid == search.0
+                && IsWindowVisible(hwnd) != 0
+                && GetWindow(hwnd, GW_OWNER).is_null()
+                && GetWindowTextLengthW(hwnd) > 0
+            {
+                search.1 = hwnd;
+                return FALSE;
+            }
+            TRUE
+        }
+    }
+
+    let mut search: (u32, HWND) = (process_id as u32, std::ptr::null_mut());
+    unsafe { EnumWindows(Some(find), &mut search as *mut _ as LPARAM) };
+    let hwnd = search.1;
+    if hwnd.is_null() {
+        return Err(BrowserInfoError::WindowNotFound);
+    }
+
+    unsafe {
+        if IsIconic(hwnd) != 0 {
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+        if SetForegroundWindow(hwnd) == 0 {
+            return Err(BrowserInfoError::PlatformError(
+                "SetForegroundWindow was refused".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 可視・所有者なし・タイトルありのトップレベルウィンドウ一覧 (Zオーダー順)
+pub fn top_level_windows() -> Vec<WindowHandleInfo> {
+    use winapi::shared::minwindef::{BOOL, LPARAM, TRUE};
+    use winapi::shared::windef::{HWND, RECT};
+    use winapi::um::winuser::{
+        GW_OWNER, GetWindow, GetWindowRect, GetWindowTextLengthW, GetWindowTextW,
+        GetWindowThreadProcessId, IsWindowVisible,
+    };
+
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        unsafe {
+            let windows = &mut *(lparam as *mut Vec<WindowHandleInfo>);
+            let len = GetWindowTextLengthW(hwnd);
+            if IsWindowVisible(hwnd) == 0 || !GetWindow(hwnd, GW_OWNER).is_null() || len == 0 {
+                return TRUE;
+            }
+            let mut buffer = vec![0u16; len as usize + 1];
+            let copied = GetWindowTextW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32);
+            let mut pid = 0;
+            GetWindowThreadProcessId(hwnd, &mut pid);
+            let mut rect: RECT = std::mem::zeroed();
+            GetWindowRect(hwnd, &mut rect);
+
+            let process_path = process_image_path(pid).unwrap_or_default();
+            let app_name = process_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            windows.push(WindowHandleInfo {
+                title: String::from_utf16_lossy(&buffer[..copied.max(0) as usize]),
+                app_name,
+                process_path,
+                process_id: pid as u64,
+                // active-win-pos-rs と同じ表記
+                window_id: format!("HWND({})", hwnd as isize),
+                position: crate::WindowPosition::new(
+                    rect.left as f64,
+                    rect.top as f64,
+                    (rect.right - rect.left) as f64,
+                    (rect.bottom - rect.top) as f64,
+                ),
+            });
+            TRUE
+        }
+    }
+
+    let mut windows: Vec<WindowHandleInfo> = Vec::new();
+    unsafe { winapi::um::winuser::EnumWindows(Some(collect), &mut windows as *mut _ as LPARAM) };
+    windows
+}
+
+/// 画面ロック中か: 入力デスクトップが "Default" 以外（Winlogon）なら開けないか名前が違う
+pub fn session_locked() -> bool {
+    use winapi::shared::minwindef::FALSE;
+    use winapi::um::winuser::{
+        CloseDesktop, DESKTOP_SWITCHDESKTOP, GetUserObjectInformationW, OpenInputDesktop, UOI_NAME,
+    };
+
+    let desktop = unsafe { OpenInputDesktop(0, FALSE, DESKTOP_SWITCHDESKTOP) };
+    if desktop.is_null() {
+        return true;
+    }
+    let mut name = [0u16; 256];
+    let mut needed = 0;
+    let ok = unsafe {
+        GetUserObjectInformationW(
+            desktop.cast(),
+            UOI_NAME,
+            name.as_mut_ptr().cast(),
+            (name.len() * 2) as u32,
+            &mut needed,
+        )
+    };
+    unsafe { CloseDesktop(desktop) };
+    if ok == 0 {
+        return false;
+    }
+    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    !String::from_utf16_lossy(&name[..len]).eq_ignore_ascii_case("Default")
+}
+
+/// 全プロセスの (PID, 実行ファイル, ワーキングセット) 一覧
+///
+/// Processes that cannot be opened (other users, protected) are skipped.
+pub fn process_memory() -> Vec<(u64, std::path::PathBuf, u64)> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::psapi::{EnumProcesses, GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use winapi::um::winnt::{PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_VM_READ};
+
+    let mut pids = vec![0u32; 4096];
+    let mut needed = 0;
+    let size = (pids.len() * std::mem::size_of::<u32>()) as u32;
+    if unsafe { EnumProcesses(pids.as_mut_ptr(), size, &mut needed) } == 0 {
+        return Vec::new();
+    }
+    pids.truncate(needed as usize / std::mem::size_of::<u32>());
+
+    pids.into_iter()
+        .filter_map(|pid| {
+            let path = process_image_path(pid)?;
+            let handle =
+                unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_VM_READ, 0, pid) };
+            if handle.is_null() {
+                return None;
+            }
+            let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+            counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+            let ok = unsafe { GetProcessMemoryInfo(handle, &mut counters, counters.cb) };
+            unsafe { CloseHandle(handle) };
+            (ok != 0).then(|| (pid as u64, path, counters.WorkingSetSize as u64))
+        })
+        .collect()
+}
+
+/// ウィンドウの内容を RGBA で取得（最小化・非表示なら `None`）
+///
+/// PrintWindow は他のウィンドウに隠れていても描画内容を返す。
+pub fn capture_window(window_id: &str) -> Result<Option<(u32, u32, Vec<u8>)>, BrowserInfoError> {
+    use std::ptr::null_mut;
+    use winapi::shared::windef::{HWND, RECT};
+    use winapi::um::wingdi::{
+        BI_RGB, BITMAPINFO, BITMAPINFOHEADER, CreateCompatibleBitmap, CreateCompatibleDC,
+        DIB_RGB_COLORS, DeleteDC, DeleteObject, GetDIBits, SelectObject,
+    };
+    use winapi::um::winuser::{
+        GetDC, GetWindowRect, IsIconic, IsWindow, IsWindowVisible, PrintWindow, ReleaseDC,
+    };
+
+    /// Chromium の GPU 描画も取り込む (PW_RENDERFULLCONTENT)
+    const PW_RENDERFULLCONTENT: u32 = 2;
+
+    let handle = window_handle(&WindowHandleInfo {
+        window_id: window_id.to_string(),
+        ..Default::default()
+    })
+    .ok_or(BrowserInfoError::WindowNotFound)? as HWND;
+    unsafe {
+        if IsWindow(handle) == 0 {
+            return Err(BrowserInfoError::WindowNotFound);
+        }
+        if IsIconic(handle) != 0 || IsWindowVisible(handle) == 0 {
+            return Ok(None);
+        }
+
+        let mut rect: RECT = std::mem::zeroed();
+        GetWindowRect(handle, &mut rect);
+        let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+        if width <= 0 || height <= 0 {
+            return Ok(None);
+        }
+
+        let screen = GetDC(null_mut());
+        let dc = CreateCompatibleDC(screen);
+        let bitmap = CreateCompatibleBitmap(screen, width, height);
+        let previous = SelectObject(dc, bitmap as _);
+        let printed = PrintWindow(handle, dc, PW_RENDERFULLCONTENT);
+
+        let mut info: BITMAPINFO = std::mem::zeroed();
+        info.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+        info.bmiHeader.biWidth = width;
+        // 負の高さ = 上から下への行順
+        info.bmiHeader.biHeight = -height;
+        info.bmiHeader.biPlanes = 1;
+        info.bmiHeader.biBitCount = 32;
+        info.bmiHeader.biCompression = BI_RGB;
+        let mut pixels = vec![0u8; width as usize * height as usize * 4];
+        let lines = GetDIBits(
+            dc,
+            bitmap,
+            0,
+            height as u32,
+            pixels.as_mut_ptr() as _,
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+
+        SelectObject(dc, previous);
+        DeleteObject(bitmap as _);
+        DeleteDC(dc);
+        ReleaseDC(null_mut(), screen);
+
+        if printed == 0 || lines == 0 {
+            return Err(BrowserInfoError::PlatformError(
+                "PrintWindow failed".to_string(),
+            ));
+        }
+        // BGRA → RGBA（アルファは未定義なので不透明にする）
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 255;
+        }
+        Ok(Some((width as u32, height as u32, pixels)))
+    }
+}
+
+/// 実行ファイルのフルパス（権限の低いプロセスからも取得できる API を使う）
+fn process_image_path(process_id: u32) -> Option<std::path::PathBuf> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::winbase::QueryFullProcessImageNameW;
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id) };
+    if handle.is_null() {
+        return None;
+    }
+    let mut buffer = [0u16; 1024];
+    let mut len = buffer.len() as u32;
+    let ok = unsafe { QueryFullProcessImageNameW(handle, 0, buffer.as_mut_ptr(), &mut len) };
+    unsafe { CloseHandle(handle) };
+    (ok != 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]).into())
+}
+
+/// フォアグラウンド切替とフォーカス中ウィンドウのタイトル変更を WinEvent フックで通知
+///
+/// `keep_going` が false を返すまで呼び出しスレッドでメッセージを処理する。
+/// `installed` にはフックを設置できたかどうかが一度だけ渡される。
+pub fn watch_foreground(
+    keep_going: impl Fn() -> bool,
+    on_change: impl Fn() + 'static,
+    installed: impl FnOnce(bool),
+) {
+    use std::cell::RefCell;
+    use winapi::shared::minwindef::{DWORD, FALSE};
+    use winapi::shared::ntdef::LONG;
+    use winapi::shared::windef::{HWINEVENTHOOK, HWND};
+    use winapi::um::winuser::{
+        CHILDID_SELF, DispatchMessageW, EVENT_OBJECT_NAMECHANGE, EVENT_SYSTEM_FOREGROUND,
+        GetForegroundWindow, MSG, MsgWaitForMultipleObjects, OBJID_WINDOW, PM_REMOVE, PeekMessageW,
+        QS_ALLINPUT, SetWinEventHook, TranslateMessage, UnhookWinEvent, WINEVENT_OUTOFCONTEXT,
+        WINEVENT_SKIPOWNPROCESS,
+    };
+
+    thread_local! {
+        static ON_CHANGE: RefCell<Option<Box<dyn Fn()>>> = RefCell::new(None);
+    }
+
+    unsafe extern "system" fn callback(
+        _hook: HWINEVENTHOOK,
+        event: DWORD,
+        hwnd: HWND,
+        id_object: LONG,
+        id_child: LONG,
+        _thread: DWORD,
+        _time: DWORD,
+    ) {
+        // タイトル変更はフォーカス中のトップレベルウィンドウのものだけ
+        if event == EVENT_OBJECT_NAMECHANGE
+            && (id_object != OBJID_WINDOW
+                || id_child != CHILDID_SELF
+                || hwnd != unsafe { GetForegroundWindow() })
+        {
+            return;
+        }
+        ON_CHANGE.with(|on_change| {
+            if let Some(on_change) = on_change.borrow().as_ref() {
+                on_change();
+            }
+        });
+    }
+
+    ON_CHANGE.with(|slot| *slot.borrow_mut() = Some(Box::new(on_change)));
+    let flags = WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS;
+    let hooks = [EVENT_SYSTEM_FOREGROUND, EVENT_OBJECT_NAMECHANGE].map(|event| unsafe {
+        SetWinEventHook(
+            event,
+            event,
+            std::ptr::null_mut(),
+            Some(callback),
+            0,
+            0,
+            flags,
+        )
+    });
+
+    let ok = hooks.iter().all(|hook| !hook.is_null());
+    installed(ok);
+
+    if ok {
+        // Out-of-context callbacks are delivered while this thread pumps messages
+        let mut msg: MSG = unsafe { std::mem::zeroed() };
+        while keep_going() {
+            unsafe {
+                while PeekMessageW(&mut msg, std::ptr::null_mut(), 0, 0, PM_REMOVE) != 0 {
+                    TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+                MsgWaitForMultipleObjects(0, std::ptr::null(), FALSE, 250, QS_ALLINPUT);
+            }
+        }
+    }
+
+    for hook in hooks.into_iter().filter(|hook| !hook.is_null()) {
+        unsafe { UnhookWinEvent(hook) };
+    }
+    ON_CHANGE.with(|slot| slot.borrow_mut().take());
+}
+
+/// PowerShell が起動でき、スクリプトを実行できる言語モードか
+///
+/// AppLocker / WDAC 配下では `ConstrainedLanguage` になり、抽出スクリプトの
+/// .NET 呼び出しが失敗する。
+pub fn probe_powershell() -> Result<(), BrowserInfoError> {
+    let mode = powershell()
+        .args([
+            "-NoProfile",
+            "-Command",
+            "$ExecutionContext.SessionState.LanguageMode",
+        ])
+        .timeout(Duration::from_secs(10))
+        .run()?
+        .into_stdout("PowerShell probe")?;
+    match mode.trim() {
+        "FullLanguage" => Ok(()),
+        mode => Err(BrowserInfoError::PlatformError(format!(
+            "PowerShell runs in {mode} mode (AppLocker / WDAC policy)"
+        ))),
     }
 }
@@ -0,0 +1,245 @@
+//! Machine-level privacy policy set by IT administrators.
+//!
+//! Enterprise deployments can force privacy settings for every application
+//! embedding this crate, independently of what the application asks for:
+//!
+//! - Windows: registry values under `HKLM\SOFTWARE\Policies\frkavka\browser-info`
+//! - macOS: managed preferences `/Library/Managed Preferences/com.frkavka.browser-info.plist`
+//! - Linux / other: JSON file `/etc/browser-info/policy.json`
+//!
+//! Recognised keys (same names on every platform):
+//!
+//! | key                           | value                                     |
+//! |-------------------------------|-------------------------------------------|
+//! | `url_privacy`                 | `full`, `redacted` or `domain_only`       |
+//! | `disable_keyboard_simulation` | boolean (`REG_DWORD` 1 on Windows)        |
+//! | `disable_tor`                 | boolean, refuse Tor even if allowed       |
+//! | `disabled_browsers`           | `;`-separated names, e.g. `safari;opera`  |
+//!
+//! The policy is read once per process and cached.
+
+use crate::BrowserType;
+use crate::config::{ExtractionConfig, UrlPrivacy};
+use std::sync::OnceLock;
+
+/// Settings enforced by the machine policy
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MachinePolicy {
+    /// Minimum URL privacy level
+    pub url_privacy: Option<UrlPrivacy>,
+    /// Forbid simulated keystrokes
+    pub disable_keyboard_simulation: bool,
+    /// Forbid Tor Browser extraction regardless of the caller's opt-in
+    pub disable_tor: bool,
+    /// Browsers treated as non-browsers everywhere (see
+    /// [`ExtractionConfig::disable_browser`])
+    pub disabled_browsers: Vec<BrowserType>,
+}
+
+impl MachinePolicy {
+    /// Build a policy from raw key/value pairs, ignoring unknown keys
+    pub fn from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut policy = MachinePolicy::default();
+        for (key, value) in pairs {
+            let flag = matches!(value, "true" | "1" | "0x1");
+            match key {
+                "url_privacy" => {
+                    policy.url_privacy = match value {
+                        "full" => Some(UrlPrivacy::Full),
+                        "redacted" => Some(UrlPrivacy::Redacted),
+                        "domain_only" => Some(UrlPrivacy::DomainOnly),
+                        _ => None,
+                    }
+                }
+                "disable_keyboard_simulation" => policy.disable_keyboard_simulation = flag,
+                "disable_tor" => policy.disable_tor = flag,
+                "disabled_browsers" => {
+                    policy.disabled_browsers = value.split(';').filter_map(browser_named).collect();
+                }
+                _ => {}
+            }
+        }
+        policy
+    }
+
+    /// Tighten `config` according to this policy
+    pub fn apply(&self, mut config: ExtractionConfig) -> ExtractionConfig {
+        if let Some(privacy) = self.url_privacy {
+            config.url_privacy = config.url_privacy.max(privacy);
+        }
+        if self.disable_keyboard_simulation {
+            config.allow_keyboard_simulation = false;
+        }
+        if self.disable_tor {
+            config.allow_tor = false;
+        }
+        for browser_type in &self.disabled_browsers {
+            config = config.disable_browser(browser_type.clone());
+        }
+        config
+    }
+}
+
+/// Browser for a policy name (`chrome`, `edge`, `safari`, ...); unknown names are ignored
+fn browser_named(name: &str) -> Option<BrowserType> {
+    match name.trim().to_lowercase().as_str() {
+        "chrome" => Some(BrowserType::Chrome),
+        "firefox" => Some(BrowserType::Firefox),
+        "edge" => Some(BrowserType::Edge),
+        "safari" => Some(BrowserType::Safari),
+        "brave" => Some(BrowserType::Brave),
+        "opera" => Some(BrowserType::Opera),
+        "vivaldi" => Some(BrowserType::Vivaldi),
+        "tor" => Some(BrowserType::Tor),
+        "embedded" => Some(BrowserType::Embedded),
+        _ => None,
+    }
+}
+
+/// The cached machine policy (empty when none is installed)
+pub fn machine_policy() -> &'static MachinePolicy {
+    static POLICY: OnceLock<MachinePolicy> = OnceLock::new();
+    POLICY.get_or_init(load_machine_policy)
+}
+
+/// Read the machine policy from its platform location, bypassing the cache
+pub fn load_machine_policy() -> MachinePolicy {
+    match read_platform_policy() {
+        Ok(policy) => policy,
+        Err(e) => {
+            log::warn!("Ignoring unreadable machine policy: {e}");
+            MachinePolicy::default()
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn read_platform_policy() -> Result<MachinePolicy, String> {
+    use crate::process_runner::ProcessRunner;
+
+    const POLICY_KEY: &str = r"HKLM\SOFTWARE\Policies\frkavka\browser-info";
+
+    let output = ProcessRunner::new("reg")
+        .args(["query", POLICY_KEY])
+        .run()
+        .map_err(|e| e.to_string())?;
+
+    // Missing key: no policy installed
+    if !output.success() {
+        return Ok(MachinePolicy::default());
+    }
+
+    // Value lines look like: "    url_privacy    REG_SZ    domain_only"
+    let pairs = output.stdout.lines().filter_map(|line| {
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [name, _kind, value] => Some((*name, *value)),
+            _ => None,
+        }
+    });
+
+    Ok(MachinePolicy::from_pairs(pairs))
+}
+
+#[cfg(target_os = "macos")]
+fn read_platform_policy() -> Result<MachinePolicy, String> {
+    use crate::process_runner::ProcessRunner;
+
+    const POLICY_PLIST: &str = "/Library/Managed Preferences/com.frkavka.browser-info.plist";
+
+    if !std::path::Path::new(POLICY_PLIST).exists() {
+        return Ok(MachinePolicy::default());
+    }
+
+    let output = ProcessRunner::new("plutil")
+        .args(["-convert", "json", "-o", "-", POLICY_PLIST])
+        .run()
+        .map_err(|e| e.to_string())?;
+
+    if !output.success() {
+        return Err(output.stderr);
+    }
+
+    let pairs = json_pairs(&output.stdout)?;
+    Ok(MachinePolicy::from_pairs(
+        pairs
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str())),
+    ))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn read_platform_policy() -> Result<MachinePolicy, String> {
+    const POLICY_FILE: &str = "/etc/browser-info/policy.json";
+
+    match std::fs::read_to_string(POLICY_FILE) {
+        Ok(content) => {
+            let pairs = json_pairs(&content)?;
+            Ok(MachinePolicy::from_pairs(
+                pairs
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.as_str())),
+            ))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(MachinePolicy::default()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Key/value pairs of a JSON policy object
+///
+/// Booleans and numbers become `"true"` / `"1"` as in the registry, arrays of
+/// names are joined with `;`. Anything else (malformed JSON, nested objects)
+/// rejects the whole file rather than enforcing part of it.
+#[cfg(not(target_os = "windows"))]
+fn json_pairs(content: &str) -> Result<Vec<(String, String)>, String> {
+    use serde_json::Value;
+
+    let object: serde_json::Map<String, Value> =
+        serde_json::from_str(content).map_err(|e| format!("invalid policy JSON: {e}"))?;
+    object
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value,
+                Value::Bool(flag) => flag.to_string(),
+                Value::Number(number) => number.to_string(),
+                Value::Array(names) => names
+                    .iter()
+                    .map(|name| name.as_str().ok_or(()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("{key}: expected a list of names"))?
+                    .join(";"),
+                other => return Err(format!("{key}: unsupported value {other}")),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_json_is_parsed_or_rejected_whole() {
+        let pairs = json_pairs(
+            r#"{"url_privacy": "domain_only", "disable_tor": true, "disabled_browsers": ["safari", "opera"]}"#,
+        )
+        .unwrap();
+        let policy = MachinePolicy::from_pairs(
+            pairs
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
+        assert_eq!(policy.url_privacy, Some(UrlPrivacy::DomainOnly));
+        assert!(policy.disable_tor);
+        assert_eq!(
+            policy.disabled_browsers,
+            [BrowserType::Safari, BrowserType::Opera]
+        );
+
+        // 壊れたファイルや入れ子の値は一部だけ適用せず、全体を拒否する
+        assert!(json_pairs(r#"{"disabled_browsers": "safari;opera", "url_privacy": "#).is_err());
+        assert!(json_pairs(r#"{"url_privacy": {"level": "redacted"}}"#).is_err());
+    }
+}
@@ -0,0 +1,435 @@
+// ================================================================================================
+// Child process runner - 外部プロセス実行 (PowerShell / osascript / xprop)
+// ================================================================================================
+//
+// Every script backend goes through `ProcessRunner`, so they share the same
+// guarantees: the child is killed when the deadline passes, the caller cancels
+// or its output grows past a cap, no more than a few children run at once, and
+// the child only inherits the environment variables it needs.
+//
+// The caps are process-wide `ProcessLimits`; applications tune them with
+// `set_process_limits`.
+
+// Linux の xprop 呼び出しはまだ一部しか使わない
+#![cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+
+use crate::BrowserInfoError;
+use crate::cancel::CancellationToken;
+use crate::script_error;
+use std::cell::{Cell, RefCell};
+use std::ffi::OsStr;
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Runtime a child gets unless the caller says otherwise
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the runner checks for exit, deadline and cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Variables passed through to children; everything else is dropped so that
+/// e.g. a planted `PSModulePath` or `OSA_LIBRARY_PATH` cannot change what runs
+const INHERITED_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    // Windows
+    "SystemRoot",
+    "SystemDrive",
+    "windir",
+    "ComSpec",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PATHEXT",
+    "PROCESSOR_ARCHITECTURE",
+    // X11 / Wayland / D-Bus
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XAUTHORITY",
+    "XDG_RUNTIME_DIR",
+    "DBUS_SESSION_BUS_ADDRESS",
+];
+
+/// Caps on the PowerShell / osascript / xprop children this crate starts
+///
+/// Limits apply to the whole process. A child that breaks one of them is
+/// killed and the call fails with a typed error:
+///
+/// | Limit | Error |
+/// |-------|-------|
+/// | `max_output` | [`BrowserInfoError::OutputLimitExceeded`] |
+/// | `max_runtime` | [`BrowserInfoError::Timeout`] |
+/// | `max_concurrent` | [`BrowserInfoError::TooManyProcesses`] |
+///
+/// ```rust
+/// use browser_info::{ProcessLimits, set_process_limits};
+/// use std::time::Duration;
+///
+/// set_process_limits(
+///     ProcessLimits::new()
+///         .max_output(64 * 1024)
+///         .max_runtime(Duration::from_secs(3))
+///         .max_concurrent(2),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessLimits {
+    max_output: usize,
+    max_runtime: Duration,
+    max_concurrent: usize,
+}
+
+impl ProcessLimits {
+    /// 1 MiB per stream, 30 s per child, 4 children at once
+    pub const DEFAULT: Self = Self {
+        max_output: 1024 * 1024,
+        max_runtime: Duration::from_secs(30),
+        max_concurrent: 4,
+    };
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes a child may write to stdout or stderr
+    pub fn max_output(mut self, bytes: usize) -> Self {
+        self.max_output = bytes;
+        self
+    }
+
+    /// Longest a child may run; shorter per-call deadlines still apply
+    pub fn max_runtime(mut self, runtime: Duration) -> Self {
+        self.max_runtime = runtime;
+        self
+    }
+
+    /// Children allowed to run at the same time (at least 1)
+    pub fn max_concurrent(mut self, children: usize) -> Self {
+        self.max_concurrent = children.max(1);
+        self
+    }
+
+    pub fn output_limit(&self) -> usize {
+        self.max_output
+    }
+
+    pub fn runtime_limit(&self) -> Duration {
+        self.max_runtime
+    }
+
+    pub fn concurrency_limit(&self) -> usize {
+        self.max_concurrent
+    }
+}
+
+impl Default for ProcessLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static LIMITS: RwLock<ProcessLimits> = RwLock::new(ProcessLimits::DEFAULT);
+
+/// Children currently running
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+/// Use `limits` for every child process started from now on
+pub fn set_process_limits(limits: ProcessLimits) {
+    *LIMITS.write().unwrap_or_else(PoisonError::into_inner) = limits;
+}
+
+/// The limits currently in force
+pub fn process_limits() -> ProcessLimits {
+    *LIMITS.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Slot in the concurrency limit, released on drop
+struct RunningSlot;
+
+impl RunningSlot {
+    fn acquire(limit: usize) -> Result<Self, BrowserInfoError> {
+        RUNNING
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |running| {
+                (running < limit).then_some(running + 1)
+            })
+            .map(|_| RunningSlot)
+            .map_err(|_| BrowserInfoError::TooManyProcesses(limit))
+    }
+}
+
+impl Drop for RunningSlot {
+    fn drop(&mut self) {
+        RUNNING.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// One child process run with a deadline, an output cap and a scrubbed environment
+pub(crate) struct ProcessRunner {
+    command: Command,
+    timeout: Duration,
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+thread_local! {
+    /// Deadline of the enclosing [`with_deadline`] call on this thread
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Run `f` with every child it starts on this thread killed at `deadline`
+///
+/// Children started after the deadline fail with `Timeout` without running.
+/// Nested calls keep the earlier deadline.
+pub(crate) fn with_deadline<T>(deadline: Instant, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Instant>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DEADLINE.set(self.0);
+        }
+    }
+
+    let previous = DEADLINE.get();
+    let _restore = Restore(previous);
+    DEADLINE.set(Some(
+        previous.map_or(deadline, |previous| previous.min(deadline)),
+    ));
+    f()
+}
+
+thread_local! {
+    /// Token of the enclosing [`with_cancellation`] call on this thread
+    static CANCELLATION: RefCell<Option<CancellationToken>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with every child it starts on this thread killed once `token` is cancelled
+pub(crate) fn with_cancellation<T>(token: &CancellationToken, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<CancellationToken>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CANCELLATION.set(self.0.take());
+        }
+    }
+
+    let _restore = Restore(CANCELLATION.replace(Some(token.clone())));
+    f()
+}
+
+/// What a finished child left behind
+#[derive(Debug)]
+pub(crate) struct ProcessOutput {
+    pub status: ExitStatus,
+    /// Lossily decoded; never longer than the output limit
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ProcessRunner {
+    pub(crate) fn new(program: impl AsRef<OsStr>) -> Self {
+        Self::from_command(Command::new(program))
+    }
+
+    /// Wrap a prepared command (e.g. one with a resolved interpreter path)
+    pub(crate) fn from_command(mut command: Command) -> Self {
+        command.env_clear();
+        for name in INHERITED_ENV {
+            if let Some(value) = std::env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+        Self {
+            command,
+            timeout: DEFAULT_TIMEOUT,
+            cancel: None,
+        }
+    }
+
+    pub(crate) fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.command.arg(arg);
+        self
+    }
+
+    pub(crate) fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.command.args(args);
+        self
+    }
+
+    /// Kill the child once it ran this long (capped by [`ProcessLimits::max_runtime`])
+    pub(crate) fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Kill the child as soon as `flag` is set
+    #[allow(dead_code)]
+    pub(crate) fn cancel_on(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    /// Run to completion, or kill the child on deadline, cancellation or
+    /// runaway output
+    ///
+    /// A non-zero exit status is not an error here; see
+    /// [`ProcessOutput::into_stdout`].
+    pub(crate) fn run(mut self) -> Result<ProcessOutput, BrowserInfoError> {
+        let program = self.command.get_program().to_string_lossy().into_owned();
+        let limits = process_limits();
+        let mut timeout = self.timeout.min(limits.max_runtime);
+        if let Some(deadline) = DEADLINE.get() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(BrowserInfoError::Timeout);
+            }
+            timeout = timeout.min(remaining);
+        }
+        let ambient_cancel = CANCELLATION.with_borrow(Clone::clone);
+        if ambient_cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(BrowserInfoError::Cancelled);
+        }
+        let _slot = RunningSlot::acquire(limits.max_concurrent)?;
+        let started = Instant::now();
+        let mut child = self
+            .command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| BrowserInfoError::PlatformError(format!("Cannot start {program}: {e}")))?;
+
+        // 上限を超えたらフラグを立て、子プロセスを止めるまで読み捨てる (パイプを詰まらせない)
+        let overflow = Arc::new(AtomicBool::new(false));
+        let stdout = drain(child.stdout.take(), limits.max_output, &overflow);
+        let stderr = drain(child.stderr.take(), limits.max_output, &overflow);
+
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) => {}
+                Err(e) => {
+                    kill(&mut child);
+                    return Err(BrowserInfoError::PlatformError(format!(
+                        "Waiting for {program} failed: {e}"
+                    )));
+                }
+            }
+            if self
+                .cancel
+                .as_ref()
+                .is_some_and(|flag| flag.load(Ordering::Relaxed))
+                || ambient_cancel
+                    .as_ref()
+                    .is_some_and(CancellationToken::is_cancelled)
+            {
+                kill(&mut child);
+                return Err(BrowserInfoError::Cancelled);
+            }
+            if overflow.load(Ordering::Relaxed) {
+                kill(&mut child);
+                log::warn!(
+                    "{program} killed after writing more than {} bytes",
+                    limits.max_output
+                );
+                return Err(BrowserInfoError::OutputLimitExceeded(limits.max_output));
+            }
+            if started.elapsed() >= timeout {
+                kill(&mut child);
+                log::warn!("{program} killed after {timeout:?}");
+                return Err(BrowserInfoError::Timeout);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+
+        // 終了直前に上限を超えた出力も見逃さない
+        let stdout = join(stdout);
+        let stderr = join(stderr);
+        if overflow.load(Ordering::Relaxed) {
+            return Err(BrowserInfoError::OutputLimitExceeded(limits.max_output));
+        }
+        Ok(ProcessOutput {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+impl ProcessOutput {
+    pub(crate) fn success(&self) -> bool {
+        self.status.success()
+    }
+
+    /// stdout of a successful run; `label` names the script in the error otherwise
+    ///
+    /// Failures are classified by [`script_error::from_failed_run`], so a
+    /// missing permission becomes [`BrowserInfoError::PermissionDenied`] in any
+    /// UI language.
+    pub(crate) fn into_stdout(self, label: &str) -> Result<String, BrowserInfoError> {
+        if !self.stderr.trim().is_empty() {
+            log::warn!("{label} stderr: {}", self.stderr.trim());
+        }
+        if !self.success() {
+            return Err(script_error::from_failed_run(label, &self));
+        }
+        Ok(self.stdout)
+    }
+}
+
+fn kill(child: &mut Child) {
+    let _ = child.kill();
+    // Reap the process so no zombie is left behind
+    let _ = child.wait();
+}
+
+type Drain = Option<JoinHandle<Vec<u8>>>;
+
+/// Read `stream` to its end on a thread, keeping the first `limit` bytes and
+/// setting `overflow` once there is more
+fn drain(
+    stream: Option<impl Read + Send + 'static>,
+    limit: usize,
+    overflow: &Arc<AtomicBool>,
+) -> Drain {
+    let mut stream = stream?;
+    let overflow = Arc::clone(overflow);
+    Some(std::thread::spawn(move || {
+        let mut kept = Vec::new();
+        let mut buffer = [0u8; 8192];
+        while let Ok(read) = stream.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            let room = limit.saturating_sub(kept.len());
+            kept.extend_from_slice(&buffer[..read.min(room)]);
+            if read > room {
+                overflow.store(true, Ordering::Relaxed);
+            }
+        }
+        kept
+    }))
+}
+
+fn join(drain: Drain) -> String {
+    let bytes = drain
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -0,0 +1,52 @@
+// ================================================================================================
+// Browser profile locations - プロファイルの場所
+// ================================================================================================
+
+use crate::BrowserType;
+use std::path::PathBuf;
+
+/// Default profile directory of every Chromium-based browser this crate knows
+pub(crate) fn chromium_profiles() -> Vec<(BrowserType, PathBuf)> {
+    let mut profiles = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    if let Some(local) = std::env::var_os("LOCALAPPDATA").map(PathBuf::from) {
+        for (browser_type, dir) in [
+            (BrowserType::Chrome, r"Google\Chrome\User Data"),
+            (BrowserType::Edge, r"Microsoft\Edge\User Data"),
+            (BrowserType::Brave, r"BraveSoftware\Brave-Browser\User Data"),
+            (BrowserType::Vivaldi, r"Vivaldi\User Data"),
+        ] {
+            profiles.push((browser_type, local.join(dir).join("Default")));
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        let support = home.join("Library/Application Support");
+        for (browser_type, dir) in [
+            (BrowserType::Chrome, "Google/Chrome"),
+            (BrowserType::Edge, "Microsoft Edge"),
+            (BrowserType::Brave, "BraveSoftware/Brave-Browser"),
+            (BrowserType::Vivaldi, "Vivaldi"),
+        ] {
+            profiles.push((browser_type, support.join(dir).join("Default")));
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        let config = home.join(".config");
+        for (browser_type, dir) in [
+            (BrowserType::Chrome, "google-chrome"),
+            (BrowserType::Unknown("Chromium".to_string()), "chromium"),
+            (BrowserType::Edge, "microsoft-edge"),
+            (BrowserType::Brave, "BraveSoftware/Brave-Browser"),
+            (BrowserType::Vivaldi, "vivaldi"),
+        ] {
+            profiles.push((browser_type, config.join(dir).join("Default")));
+        }
+    }
+
+    profiles
+}
@@ -0,0 +1,119 @@
+// ================================================================================================
+// Browser resource usage - ブラウザのメモリ使用量
+// ================================================================================================
+
+use crate::browser_detection::classify_browser;
+use crate::{BrowserInfoError, BrowserType, WindowHandleInfo};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Memory of one browser, summed over all its processes
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BrowserMemory {
+    pub browser_type: BrowserType,
+    /// Browser, renderer, GPU and helper processes counted
+    pub process_count: u32,
+    /// Resident memory (working set on Windows), in bytes
+    ///
+    /// Shared pages are counted once per process, so this overstates what
+    /// closing the browser would free; it is meant for trends and thresholds.
+    pub resident_bytes: u64,
+}
+
+/// Memory used by each running browser, largest first
+///
+/// Processes are attributed by executable, the same way windows are
+/// classified, so helper processes named after the browser (`chrome`,
+/// `Google Chrome Helper (Renderer)`, `msedge`) are included. WebView2 hosts
+/// are left out. Only processes of the current user are visible on Windows
+/// and Linux.
+///
+/// ```rust,no_run
+/// for browser in browser_info::resources::browser_memory()? {
+///     println!(
+///         "{:?}: {} MiB in {} processes",
+///         browser.browser_type,
+///         browser.resident_bytes / (1024 * 1024),
+///         browser.process_count
+///     );
+/// }
+/// # Ok::<(), browser_info::BrowserInfoError>(())
+/// ```
+pub fn browser_memory() -> Result<Vec<BrowserMemory>, BrowserInfoError> {
+    let mut browsers: Vec<BrowserMemory> = Vec::new();
+    for (process_id, process_path, bytes) in process_memory()? {
+        let window = WindowHandleInfo {
+            app_name: process_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            process_path,
+            process_id,
+            ..WindowHandleInfo::default()
+        };
+        let Ok(browser_type) = classify_browser(&window) else {
+            continue;
+        };
+        if browser_type == BrowserType::Embedded {
+            continue;
+        }
+        match browsers
+            .iter_mut()
+            .find(|browser| browser.browser_type == browser_type)
+        {
+            Some(browser) => {
+                browser.process_count += 1;
+                browser.resident_bytes += bytes;
+            }
+            None => browsers.push(BrowserMemory {
+                browser_type,
+                process_count: 1,
+                resident_bytes: bytes,
+            }),
+        }
+    }
+    browsers.sort_by_key(|browser| std::cmp::Reverse(browser.resident_bytes));
+    Ok(browsers)
+}
+
+#[cfg(target_os = "windows")]
+fn process_memory() -> Result<Vec<(u64, std::path::PathBuf, u64)>, BrowserInfoError> {
+    Ok(crate::platform::windows::process_memory())
+}
+
+#[cfg(target_os = "macos")]
+use crate::platform::macos::process_memory;
+
+/// `/proc/<pid>/exe` and `VmRSS` of `/proc/<pid>/status`
+#[cfg(target_os = "linux")]
+fn process_memory() -> Result<Vec<(u64, std::path::PathBuf, u64)>, BrowserInfoError> {
+    let entries =
+        std::fs::read_dir("/proc").map_err(|e| BrowserInfoError::PlatformError(e.to_string()))?;
+    Ok(entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let process_id = entry.file_name().to_str()?.parse().ok()?;
+            // 他ユーザーのプロセスは exe を読めないので飛ばす
+            let path = std::fs::read_link(entry.path().join("exe")).ok()?;
+            let status = std::fs::read_to_string(entry.path().join("status")).ok()?;
+            let rss_kib: u64 = status
+                .lines()
+                .find_map(|line| line.strip_prefix("VmRSS:"))?
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse()
+                .ok()?;
+            Some((process_id, path, rss_kib * 1024))
+        })
+        .collect())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn process_memory() -> Result<Vec<(u64, std::path::PathBuf, u64)>, BrowserInfoError> {
+    Err(BrowserInfoError::PlatformError(
+        "Process memory is not available on this platform".to_string(),
+    ))
+}
@@ -0,0 +1,57 @@
+// ================================================================================================
+// Hung browser detection - 応答なしの検出
+// ================================================================================================
+//
+// A browser that stopped pumping messages swallows keystrokes and AppleEvents
+// until their own timeouts (several seconds each) run out. One cheap probe up
+// front lets extraction fail fast with `BrowserInfoError::BrowserNotResponding`.
+
+use crate::WindowHandleInfo;
+use std::time::Duration;
+
+/// How long the browser gets to answer the probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Whether the browser owning `window` still answers
+///
+/// Windows sends `WM_NULL` with `SendMessageTimeout` (and asks
+/// `IsHungAppWindow`); macOS asks System Events for the process's front window
+/// and gives up after a second. Always `true` on other platforms.
+pub fn is_responding(window: &WindowHandleInfo) -> bool {
+    #[cfg(target_os = "windows")]
+    return !crate::platform::windows::is_hung_window(window, PROBE_TIMEOUT);
+
+    #[cfg(target_os = "macos")]
+    return crate::platform::macos::is_process_responding(window.process_id, PROBE_TIMEOUT);
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        let _ = (window, PROBE_TIMEOUT);
+        true
+    }
+}
+
+/// Whether the process `process_id` still exists
+///
+/// Used to tell a closed browser from one that merely lost the focus. Unknown
+/// ids (`0`) and failed checks count as running.
+pub fn is_process_running(process_id: u64) -> bool {
+    if process_id == 0 {
+        return true;
+    }
+
+    #[cfg(target_os = "windows")]
+    return crate::platform::windows::is_process_running(process_id);
+
+    #[cfg(target_os = "linux")]
+    return std::path::Path::new(&format!("/proc/{process_id}")).exists();
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        // ps は対象がなければ終了コード 1
+        crate::process_runner::ProcessRunner::new("ps")
+            .args(["-p", &process_id.to_string()])
+            .run()
+            .map_or(true, |output| output.success())
+    }
+}
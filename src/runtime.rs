@@ -0,0 +1,65 @@
+//! Running the DevTools I/O under any async executor.
+//!
+//! reqwest and tokio-tungstenite need a tokio reactor and timer. When the
+//! caller is already inside a tokio runtime the DevTools futures simply run
+//! there. Under any other executor (async-std, smol, `futures::executor`,
+//! a hand-written `block_on`) they are handed to a small runtime this crate
+//! starts on first use: one worker thread, named `browser-info-io`, that
+//! lives for the rest of the process. The caller awaits the result through a
+//! channel, which works on every executor.
+//!
+//! Only the DevTools steps move; the native steps (PowerShell / osascript)
+//! still run on the caller's thread, so deadlines and cancellation tokens
+//! keep applying to them.
+
+// DevTools の呼び出し元は Windows にしかない
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+use std::future::Future;
+use std::sync::OnceLock;
+use tokio::runtime::{Handle, Runtime};
+
+/// Await `future` on tokio, whatever executor polls the caller
+///
+/// Dropping the returned future aborts the task, as dropping `future` itself
+/// would have.
+pub(crate) async fn on_tokio<F>(future: F) -> F::Output
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    if Handle::try_current().is_ok() {
+        return future.await;
+    }
+
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let task = io_runtime().spawn(async move {
+        let _ = sender.send(future.await);
+    });
+    let _abort = AbortOnDrop(task.abort_handle());
+    receiver
+        .await
+        .expect("browser-info I/O task ended without a result")
+}
+
+/// The runtime started for callers outside tokio
+fn io_runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("browser-info-io")
+            .enable_all()
+            .build()
+            .expect("failed to build the browser-info I/O runtime")
+    })
+}
+
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
@@ -0,0 +1,382 @@
+//! Time-scheduled site rules (bedtime blocks, school hours).
+//!
+//! A [`Schedule`] maps domain categories to domains and holds
+//! [`ScheduleRule`]s such as "social media is blocked 22:00–07:00". It can be
+//! built in code or loaded from a config file (with the `serde` feature):
+//!
+//! ```json
+//! {
+//!   "utc_offset_minutes": 540,
+//!   "categories": { "social": ["x.com", "instagram.com", "tiktok.com"] },
+//!   "rules": [
+//!     { "name": "bedtime", "categories": ["social"], "from": "22:00", "until": "07:00" },
+//!     { "name": "school", "domains": ["youtube.com"], "from": "08:30", "until": "15:00",
+//!       "days": ["mon", "tue", "wed", "thu", "fri"], "action": { "redirect": "https://khanacademy.org" } }
+//!   ]
+//! }
+//! ```
+//!
+//! [`ScheduleEnforcer`] evaluates the schedule against the pages reported by a
+//! [`BrowserWatcher`] and calls back with a [`Violation`] for every page that
+//! breaks an active rule. With the `control` feature, a rule with a
+//! [`ScheduleAction::Redirect`] also sends the tab elsewhere.
+//!
+//! ```rust
+//! use browser_info::schedule::{Schedule, ScheduleRule, TimeOfDay};
+//! use std::time::{Duration, SystemTime};
+//!
+//! let schedule = Schedule::new()
+//!     .category("social", ["x.com", "instagram.com"])
+//!     .rule(
+//!         ScheduleRule::new("bedtime", TimeOfDay::new(22, 0), TimeOfDay::new(7, 0))
+//!             .category("social"),
+//!     );
+//!
+//! // 1970-01-01 23:30 UTC
+//! let late = SystemTime::UNIX_EPOCH + Duration::from_secs(23 * 3600 + 1800);
+//! assert_eq!(
+//!     schedule.violated_rule("https://x.com/home", late).map(|rule| rule.name.as_str()),
+//!     Some("bedtime")
+//! );
+//! assert!(schedule.violated_rule("https://docs.rs", late).is_none());
+//! ```
+//!
+//! Times are wall-clock times at [`Schedule::utc_offset_minutes`] (UTC by
+//! default); rules are evaluated when the watcher reports a page, so a page
+//! left open when a rule starts is reported at its next change.
+
+use crate::url_extraction::{domain_matches, domain_of};
+use crate::watcher::{BrowserWatcher, WatchEventKind, WatchOptions};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Wall-clock time, written `"HH:MM"` in config files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct TimeOfDay {
+    minutes: u16,
+}
+
+impl TimeOfDay {
+    /// Panics when `hour` > 23 or `minute` > 59
+    pub const fn new(hour: u8, minute: u8) -> Self {
+        assert!(hour < 24 && minute < 60, "invalid time of day");
+        Self {
+            minutes: hour as u16 * 60 + minute as u16,
+        }
+    }
+
+    pub fn hour(&self) -> u8 {
+        (self.minutes / 60) as u8
+    }
+
+    pub fn minute(&self) -> u8 {
+        (self.minutes % 60) as u8
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid time of day: {text:?} (expected HH:MM)");
+        let (hour, minute) = text.trim().split_once(':').ok_or_else(invalid)?;
+        let hour: u8 = hour.parse().map_err(|_| invalid())?;
+        let minute: u8 = minute.parse().map_err(|_| invalid())?;
+        if hour > 23 || minute > 59 {
+            return Err(invalid());
+        }
+        Ok(Self::new(hour, minute))
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour(), self.minute())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+
+    /// Weekday of day `days` since 1970-01-01 (a Thursday)
+    fn from_epoch_day(days: i64) -> Self {
+        Self::ALL[(days + 3).rem_euclid(7) as usize]
+    }
+}
+
+/// What happens besides the [`Violation`] callback
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ScheduleAction {
+    #[default]
+    Notify,
+    /// Load this URL in the active tab (needs the `control` feature; only
+    /// reported otherwise)
+    Redirect(String),
+}
+
+/// Domains blocked during a daily time window
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScheduleRule {
+    pub name: String,
+    /// Domains (and their subdomains) covered by the rule
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub domains: Vec<String>,
+    /// Categories from [`Schedule::category`] covered by the rule
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub categories: Vec<String>,
+    pub from: TimeOfDay,
+    /// End of the window; earlier than `from` for windows across midnight
+    pub until: TimeOfDay,
+    /// Days on which the window starts; empty means every day
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub days: Vec<Weekday>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub action: ScheduleAction,
+}
+
+impl ScheduleRule {
+    pub fn new(name: impl Into<String>, from: TimeOfDay, until: TimeOfDay) -> Self {
+        Self {
+            name: name.into(),
+            domains: Vec::new(),
+            categories: Vec::new(),
+            from,
+            until,
+            days: Vec::new(),
+            action: ScheduleAction::default(),
+        }
+    }
+
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domains.push(normalize(domain.into()));
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.categories.push(category.into());
+        self
+    }
+
+    /// Only start the window on `day` (may be called for several days)
+    pub fn on(mut self, day: Weekday) -> Self {
+        self.days.push(day);
+        self
+    }
+
+    pub fn action(mut self, action: ScheduleAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Whether the window is open at `minute` of local day `day`
+    fn is_active(&self, day: i64, minute: u16) -> bool {
+        let starts_on =
+            |day: i64| self.days.is_empty() || self.days.contains(&Weekday::from_epoch_day(day));
+        let (from, until) = (self.from.minutes, self.until.minutes);
+        if from == until {
+            starts_on(day)
+        } else if from < until {
+            starts_on(day) && (from..until).contains(&minute)
+        } else {
+            // 日付をまたぐ窓: 前日に始まった分も含める
+            (minute >= from && starts_on(day)) || (minute < until && starts_on(day - 1))
+        }
+    }
+}
+
+/// Category definitions and rules, serializable as a config file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Schedule {
+    /// Offset of the wall-clock times from UTC (e.g. 540 for JST)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub utc_offset_minutes: i32,
+    /// Category name → domains
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub categories: BTreeMap<String, Vec<String>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rules: Vec<ScheduleRule>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn utc_offset_minutes(mut self, minutes: i32) -> Self {
+        self.utc_offset_minutes = minutes;
+        self
+    }
+
+    /// Define (or extend) a domain category
+    pub fn category(
+        mut self,
+        name: impl Into<String>,
+        domains: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.categories
+            .entry(name.into())
+            .or_default()
+            .extend(domains.into_iter().map(|domain| normalize(domain.into())));
+        self
+    }
+
+    pub fn rule(mut self, rule: ScheduleRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// First rule that `url` breaks at `at`
+    pub fn violated_rule(&self, url: &str, at: SystemTime) -> Option<&ScheduleRule> {
+        let domain = domain_of(url)?;
+        let local = at
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs() as i64)
+            .unwrap_or_default()
+            + self.utc_offset_minutes as i64 * 60;
+        let (day, minute) = (
+            local.div_euclid(86_400),
+            (local.rem_euclid(86_400) / 60) as u16,
+        );
+
+        self.rules
+            .iter()
+            .find(|rule| rule.is_active(day, minute) && self.rule_covers(rule, &domain))
+    }
+
+    fn rule_covers(&self, rule: &ScheduleRule, domain: &str) -> bool {
+        let categories = rule
+            .categories
+            .iter()
+            .filter_map(|category| self.categories.get(category))
+            .flatten();
+        rule.domains
+            .iter()
+            .chain(categories)
+            // 設定ファイルの値は正規化されていない
+            .any(|pattern| domain_matches(domain, &normalize(pattern.clone())))
+    }
+}
+
+/// A page that broke a scheduled rule
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Violation {
+    /// [`ScheduleRule::name`]
+    pub rule: String,
+    pub url: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_time::rfc3339"))]
+    pub timestamp: SystemTime,
+    pub action: ScheduleAction,
+}
+
+/// Checks every page the watcher reports against a [`Schedule`]; stops when dropped
+#[derive(Debug)]
+pub struct ScheduleEnforcer {
+    _watcher: BrowserWatcher,
+}
+
+impl ScheduleEnforcer {
+    pub fn spawn<F>(schedule: Schedule, on_violation: F) -> Self
+    where
+        F: FnMut(Violation) + Send + 'static,
+    {
+        Self::spawn_with_options(schedule, WatchOptions::new(), on_violation)
+    }
+
+    pub fn spawn_with_options<F>(
+        schedule: Schedule,
+        options: WatchOptions,
+        mut on_violation: F,
+    ) -> Self
+    where
+        F: FnMut(Violation) + Send + 'static,
+    {
+        let mut reported: Option<(String, String)> = None;
+        let watcher = BrowserWatcher::spawn_with_options(options, move |event| {
+            let WatchEventKind::Changed(info) = event.kind else {
+                return;
+            };
+            let Some(rule) = schedule.violated_rule(&info.url, event.timestamp) else {
+                reported = None;
+                return;
+            };
+            // 同じページのタイトル変化では繰り返さない
+            let key = (rule.name.clone(), info.url.clone());
+            if reported.as_ref() == Some(&key) {
+                return;
+            }
+            reported = Some(key);
+
+            log::info!("{} violates {:?}", info.url, rule.name);
+            enforce(&rule.action);
+            on_violation(Violation {
+                rule: rule.name.clone(),
+                url: info.url,
+                timestamp: event.timestamp,
+                action: rule.action.clone(),
+            });
+        });
+        Self { _watcher: watcher }
+    }
+}
+
+#[cfg(feature = "control")]
+fn enforce(action: &ScheduleAction) {
+    if let ScheduleAction::Redirect(url) = action
+        && let Err(e) = crate::control::navigate_active_tab_blocking(url)
+    {
+        log::warn!("Could not redirect: {e}");
+    }
+}
+
+#[cfg(not(feature = "control"))]
+fn enforce(_action: &ScheduleAction) {}
+
+fn normalize(domain: String) -> String {
+    domain.trim_start_matches("www.").to_lowercase()
+}
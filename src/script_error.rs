@@ -0,0 +1,139 @@
+// ================================================================================================
+// Script error mapping - スクリプトのエラー分類
+// ================================================================================================
+//
+// osascript and PowerShell print their errors in the user's language, so the
+// message text cannot tell a missing permission from any other failure. The
+// mapping looks at locale-independent data first: the AppleScript error number
+// (`execution error: ... (-1743)`), the PowerShell `FullyQualifiedErrorId` and
+// HRESULT, and the error code our own scripts append to `ERROR|...` lines.
+// Only when none of these is present is the message checked against a table of
+// known localized permission errors.
+
+use crate::BrowserInfoError;
+use crate::process_runner::ProcessOutput;
+
+/// AppleScript error numbers that mean a missing privacy permission
+///
+/// -1743 Apple events to the app not authorized (Automation),
+/// -1719 / -25211 assistive access not allowed (Accessibility),
+/// -10004 privilege violation
+const APPLESCRIPT_PERMISSION_ERRORS: &[i64] = &[-1743, -1719, -25211, -10004];
+
+/// AppleScript "user canceled" (e.g. the consent prompt was dismissed)
+const APPLESCRIPT_CANCELLED: i64 = -128;
+
+/// AppleScript "AppleEvent timed out"
+const APPLESCRIPT_TIMEOUT: i64 = -1712;
+
+/// E_ACCESSDENIED / UnauthorizedAccessException
+const HRESULT_ACCESS_DENIED: i64 = 0x8007_0005_u32 as i32 as i64;
+
+/// PowerShell error ids for blocked scripts (execution policy, AppLocker, ...)
+const POWERSHELL_PERMISSION_ERRORS: &[&str] = &["UnauthorizedAccess", "PSSecurityException"];
+
+/// Localized permission errors seen from osascript, System Events and PowerShell
+///
+/// Fallback for output without an error code; matched case-insensitively.
+const LOCALIZED_PERMISSION_ERRORS: &[(&str, &str)] = &[
+    ("en", "not authorized to send apple events"),
+    ("en", "not allowed assistive access"),
+    ("en", "access is denied"),
+    ("ja", "apple イベントを送信する権限がありません"),
+    ("ja", "補助アクセスは許可されていません"),
+    ("ja", "アクセスが拒否されました"),
+    ("de", "keine berechtigung"),
+    ("de", "ist nicht berechtigt"),
+    ("de", "zugriff verweigert"),
+    ("fr", "n’est pas autorisé"),
+    ("fr", "n'est pas autorisé"),
+    ("fr", "accès refusé"),
+    ("es", "no tiene permiso"),
+    ("es", "acceso denegado"),
+];
+
+/// Error for a child that exited with a non-zero status
+pub(crate) fn from_failed_run(label: &str, output: &ProcessOutput) -> BrowserInfoError {
+    let stderr = output.stderr.trim();
+    if let Some(code) = applescript_error_number(stderr)
+        && let Some(error) = from_applescript_number(code)
+    {
+        return error;
+    }
+    if powershell_error_id(stderr).is_some_and(|id| {
+        POWERSHELL_PERMISSION_ERRORS
+            .iter()
+            .any(|known| id.starts_with(known))
+    }) || hresult(stderr) == Some(HRESULT_ACCESS_DENIED)
+        || is_localized_permission_error(stderr)
+    {
+        return BrowserInfoError::PermissionDenied;
+    }
+    BrowserInfoError::PlatformError(format!("{label} failed with exit code: {}", output.status))
+}
+
+/// Error for an `ERROR|message|method|code` line printed by one of our scripts
+///
+/// `code` is the AppleScript error number or the .NET `HResult`; older
+/// scripts leave it out.
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+pub(crate) fn from_script_error(message: &str, code: Option<&str>) -> BrowserInfoError {
+    let code = code.and_then(|code| code.trim().parse::<i64>().ok());
+    if let Some(error) = code.and_then(from_applescript_number) {
+        return error;
+    }
+    if code == Some(HRESULT_ACCESS_DENIED) || is_localized_permission_error(message) {
+        return BrowserInfoError::PermissionDenied;
+    }
+    BrowserInfoError::PlatformError(message.to_string())
+}
+
+fn from_applescript_number(code: i64) -> Option<BrowserInfoError> {
+    if APPLESCRIPT_PERMISSION_ERRORS.contains(&code) {
+        Some(BrowserInfoError::PermissionDenied)
+    } else if code == APPLESCRIPT_CANCELLED {
+        Some(BrowserInfoError::Cancelled)
+    } else if code == APPLESCRIPT_TIMEOUT {
+        Some(BrowserInfoError::Timeout)
+    } else {
+        None
+    }
+}
+
+/// "... execution error: <localized text> (-1743)"
+fn applescript_error_number(stderr: &str) -> Option<i64> {
+    let line = stderr
+        .lines()
+        .rev()
+        .find(|line| line.contains("execution error"))?;
+    let (_, code) = line.trim_end().strip_suffix(')')?.rsplit_once('(')?;
+    code.parse().ok()
+}
+
+/// "+ FullyQualifiedErrorId : UnauthorizedAccess"
+fn powershell_error_id(stderr: &str) -> Option<&str> {
+    stderr
+        .lines()
+        .find_map(|line| line.split_once("FullyQualifiedErrorId :"))
+        .map(|(_, id)| id.trim())
+}
+
+/// "HRESULT: 0x80070005" / "(0x80070005)"
+fn hresult(stderr: &str) -> Option<i64> {
+    let (_, rest) = stderr.split_once("0x")?;
+    let hex: String = rest
+        .chars()
+        .take_while(char::is_ascii_hexdigit)
+        .take(8)
+        .collect();
+    u32::from_str_radix(&hex, 16)
+        .ok()
+        .map(|value| value as i32 as i64)
+}
+
+fn is_localized_permission_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    LOCALIZED_PERMISSION_ERRORS
+        .iter()
+        .any(|(_, fragment)| message.contains(fragment))
+}
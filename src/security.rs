@@ -0,0 +1,124 @@
+// ================================================================================================
+// Page security state - HTTPS / 証明書状態
+// ================================================================================================
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// How safe the connection of the current page is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SecurityState {
+    /// HTTPS (with a valid certificate, when confirmed through DevTools)
+    Secure,
+    /// Plain HTTP
+    Insecure,
+    /// HTTPS with a certificate or connection error the user clicked through
+    CertificateError,
+    /// Not a network page (`file://`, `about:`, browser-internal pages)
+    Neutral,
+}
+
+impl SecurityState {
+    /// State derived from the URL scheme alone
+    ///
+    /// Certificate problems are invisible here; HTTPS is always reported as
+    /// [`Secure`](SecurityState::Secure).
+    ///
+    /// ```rust
+    /// use browser_info::SecurityState;
+    ///
+    /// assert_eq!(SecurityState::from_url("http://example.com"), SecurityState::Insecure);
+    /// assert_eq!(SecurityState::from_url("chrome://settings"), SecurityState::Neutral);
+    /// ```
+    pub fn from_url(url: &str) -> Self {
+        let scheme = url
+            .split_once(':')
+            .map(|(scheme, _)| scheme.to_ascii_lowercase())
+            .unwrap_or_default();
+        match scheme.as_str() {
+            "https" | "wss" => SecurityState::Secure,
+            "http" | "ws" | "ftp" => SecurityState::Insecure,
+            _ => SecurityState::Neutral,
+        }
+    }
+
+    /// Map a CDP `Security.SecurityState` value
+    ///
+    /// `unknown` yields `None` so callers can fall back to [`from_url`](Self::from_url).
+    pub fn from_cdp(state: &str) -> Option<Self> {
+        match state {
+            "secure" => Some(SecurityState::Secure),
+            "insecure" => Some(SecurityState::Insecure),
+            "insecure-broken" => Some(SecurityState::CertificateError),
+            "neutral" | "info" => Some(SecurityState::Neutral),
+            _ => None,
+        }
+    }
+
+    /// `true` for plaintext and certificate-error pages
+    pub fn is_risky(&self) -> bool {
+        matches!(
+            self,
+            SecurityState::Insecure | SecurityState::CertificateError
+        )
+    }
+}
+
+/// Server certificate of the page's connection, as reported by DevTools
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CertificateInfo {
+    /// Subject common name, e.g. `*.example.com`
+    pub subject: String,
+    /// Issuer common name, e.g. `R11`
+    pub issuer: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_time::rfc3339"))]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub valid_from: SystemTime,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_time::rfc3339"))]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub valid_to: SystemTime,
+    /// Connection protocol, e.g. `TLS 1.3`
+    pub protocol: Option<String>,
+}
+
+impl CertificateInfo {
+    /// Whether the certificate is outside its validity period right now
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now();
+        now < self.valid_from || now > self.valid_to
+    }
+
+    /// Parse a CDP `Security.CertificateSecurityState`
+    #[cfg(any(
+        all(feature = "devtools", target_os = "windows"),
+        all(doc, feature = "devtools")
+    ))]
+    pub(crate) fn from_cdp(state: &serde_json::Value) -> Option<Self> {
+        use std::time::Duration;
+
+        // validFrom / validTo are seconds since the Unix epoch
+        let time = |key: &str| {
+            state[key]
+                .as_f64()
+                .filter(|seconds| *seconds >= 0.0)
+                .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs_f64(seconds))
+        };
+
+        Some(Self {
+            subject: state["subjectName"].as_str()?.to_string(),
+            issuer: state["issuer"].as_str()?.to_string(),
+            valid_from: time("validFrom")?,
+            valid_to: time("validTo")?,
+            protocol: state["protocol"]
+                .as_str()
+                .filter(|protocol| !protocol.is_empty())
+                .map(str::to_string),
+        })
+    }
+}
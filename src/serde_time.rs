@@ -0,0 +1,101 @@
+//! Serde helpers for time values.
+//!
+//! Every timestamp this crate serializes is an RFC3339 string in UTC with
+//! millisecond precision (`"2025-01-31T08:15:00.250Z"`), and every duration is
+//! an integer number of milliseconds. Use these modules with `#[serde(with = ...)]`
+//! to give your own types the same representation.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use std::time::{Duration, SystemTime};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Session {
+//!     #[serde(with = "browser_info::serde_time::rfc3339")]
+//!     start: SystemTime,
+//!     #[serde(with = "browser_info::serde_time::millis")]
+//!     length: Duration,
+//! }
+//!
+//! let session = Session {
+//!     start: SystemTime::UNIX_EPOCH + Duration::from_secs(86_400),
+//!     length: Duration::from_secs(90),
+//! };
+//! let json = serde_json::to_string(&session).unwrap();
+//! assert_eq!(json, r#"{"start":"1970-01-02T00:00:00.000Z","length":90000}"#);
+//! ```
+
+/// `SystemTime` as an RFC3339 string
+pub mod rfc3339 {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use std::time::SystemTime;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&humantime::format_rfc3339_millis(*time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        humantime::parse_rfc3339_weak(&text).map_err(D::Error::custom)
+    }
+}
+
+/// `Option<SystemTime>` as an RFC3339 string or `null`
+pub mod rfc3339_option {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use std::time::SystemTime;
+
+    pub fn serialize<S: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => super::rfc3339::serialize(time, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| humantime::parse_rfc3339_weak(&text).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+/// `Duration` as whole milliseconds
+pub mod millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis().try_into().unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+/// `Option<Duration>` as whole milliseconds or `null`
+pub mod millis_option {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => crate::serde_time::millis::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<u64>::deserialize(deserializer).map(|millis| millis.map(Duration::from_millis))
+    }
+}
@@ -0,0 +1,175 @@
+//! Warm state for polling the active browser several times per second.
+//!
+//! The free functions ([`get_active_browser_info`](crate::get_active_browser_info)
+//! and friends) start from scratch on every call: they look up the focused
+//! window, classify its process and run the full extraction chain,
+//! which spawns a PowerShell / osascript child. A [`BrowserInfoSession`]
+//! keeps what does not change between calls:
+//!
+//! - the browser classification of each process (by PID and executable path)
+//! - the last result, returned again without running any script while the
+//!   same window shows the same title and the result is younger than
+//!   [`reuse_for`](BrowserInfoSession::reuse_for)
+//!
+//! The DevTools HTTP client is already shared process-wide by the async path,
+//! so the session adds nothing there.
+//!
+//! ```rust,no_run
+//! use browser_info::BrowserInfoSession;
+//! use std::time::Duration;
+//!
+//! let mut session = BrowserInfoSession::new();
+//! loop {
+//!     if let Ok(info) = session.get_active_browser_info() {
+//!         println!("{}", info.url);
+//!     }
+//!     std::thread::sleep(Duration::from_millis(200));
+//! }
+//! ```
+
+use crate::browser_detection::classify_browser;
+use crate::window_provider::active_window_for;
+use crate::{BrowserInfo, BrowserInfoError, BrowserType, ExtractionConfig, WindowHandleInfo};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long an unchanged window's result is reused by default
+const DEFAULT_REUSE: Duration = Duration::from_secs(2);
+
+/// Reusable extraction state; see the [module docs](self)
+#[derive(Debug)]
+pub struct BrowserInfoSession {
+    config: ExtractionConfig,
+    reuse_for: Duration,
+    /// PID → (executable, browser type or `None` for non-browsers); PIDs are
+    /// reused, so the path is checked too
+    classified: HashMap<u64, (PathBuf, Option<BrowserType>)>,
+    last: Option<LastResult>,
+}
+
+#[derive(Debug)]
+struct LastResult {
+    window_id: String,
+    process_id: u64,
+    title: String,
+    at: Instant,
+    info: BrowserInfo,
+}
+
+impl Default for BrowserInfoSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BrowserInfoSession {
+    pub fn new() -> Self {
+        Self::with_config(ExtractionConfig::default())
+    }
+
+    pub fn with_config(config: ExtractionConfig) -> Self {
+        Self {
+            config,
+            reuse_for: DEFAULT_REUSE,
+            classified: HashMap::new(),
+            last: None,
+        }
+    }
+
+    /// Reuse the last result for this long while the focused window and its
+    /// title stay the same (2 s by default; `Duration::ZERO` always extracts)
+    ///
+    /// A page that changes its URL without changing its title (some
+    /// single-page apps) is noticed only once the reuse period is over.
+    pub fn reuse_for(mut self, reuse_for: Duration) -> Self {
+        self.reuse_for = reuse_for;
+        self
+    }
+
+    /// [`get_active_browser_info`](crate::get_active_browser_info) with the session's state
+    pub fn get_active_browser_info(&mut self) -> Result<BrowserInfo, BrowserInfoError> {
+        let (window, browser_type) = self.active_browser_window()?;
+        if let Some(info) = self.reusable(&window) {
+            return Ok(info);
+        }
+        let config = self.config.effective();
+        let info = crate::within_timeout(&config, || {
+            crate::extract_from_window(window.clone(), browser_type, &config)
+        })?;
+        self.remember(window, &info);
+        Ok(info)
+    }
+
+    /// [`get_browser_info`](crate::get_browser_info) with the session's state
+    pub async fn get_browser_info(&mut self) -> Result<BrowserInfo, BrowserInfoError> {
+        let (window, browser_type) = self.active_browser_window()?;
+        if let Some(info) = self.reusable(&window) {
+            return Ok(info);
+        }
+        let config = self.config.effective();
+        let info = crate::extract_from_window_async(window.clone(), browser_type, &config).await?;
+        self.remember(window, &info);
+        Ok(info)
+    }
+
+    /// Forget the cached classifications and the last result
+    pub fn clear(&mut self) {
+        self.classified.clear();
+        self.last = None;
+    }
+
+    /// The focused window and its classification, from the cache when possible
+    fn active_browser_window(
+        &mut self,
+    ) -> Result<(WindowHandleInfo, BrowserType), BrowserInfoError> {
+        let window = active_window_for(&self.config)?;
+        let cached = self
+            .classified
+            .get(&window.process_id)
+            .filter(|(path, _)| *path == window.process_path);
+        let classification = match cached {
+            Some((_, classification)) => classification.clone(),
+            None => {
+                let classification = classify_browser(&window).ok();
+                self.classified.insert(
+                    window.process_id,
+                    (window.process_path.clone(), classification.clone()),
+                );
+                classification
+            }
+        };
+        match classification {
+            Some(browser_type) if !self.config.is_browser_disabled(&browser_type) => {
+                Ok((window, browser_type))
+            }
+            _ => Err(BrowserInfoError::NotABrowser),
+        }
+    }
+
+    fn reusable(&self, window: &WindowHandleInfo) -> Option<BrowserInfo> {
+        let last = self.last.as_ref()?;
+        let unchanged = last.window_id == window.window_id
+            && last.process_id == window.process_id
+            && last.title == window.title
+            && last.at.elapsed() < self.reuse_for;
+        // 推測やダイアログ表示中の結果は使い回さない
+        let settled = last
+            .info
+            .url_source
+            .is_some_and(|source| source.is_accurate())
+            && last.info.blocking_dialog.is_none()
+            && !last.info.not_responding;
+        (unchanged && settled).then(|| last.info.clone())
+    }
+
+    fn remember(&mut self, window: WindowHandleInfo, info: &BrowserInfo) {
+        self.last = Some(LastResult {
+            window_id: window.window_id,
+            process_id: window.process_id,
+            title: window.title,
+            at: Instant::now(),
+            info: info.clone(),
+        });
+    }
+}
@@ -0,0 +1,137 @@
+// ================================================================================================
+// Address-bar shortcuts - アドレスバーへのフォーカスショートカット
+// ================================================================================================
+
+use crate::BrowserType;
+use std::fmt;
+
+/// Key of a [`KeyChord`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutKey {
+    /// Letter or digit, matched case-insensitively
+    Char(char),
+    /// Function key `F1`-`F12`
+    Function(u8),
+}
+
+/// One key press with modifiers, e.g. Ctrl+L
+///
+/// `primary` is Ctrl on Windows and Linux and Cmd on macOS, so the same chord
+/// works on every platform.
+///
+/// ```rust
+/// use browser_info::shortcut::KeyChord;
+///
+/// assert_eq!(KeyChord::primary('l').to_string(), "ctrl+l");
+/// assert_eq!(KeyChord::alt('d').to_string(), "alt+d");
+/// assert_eq!(KeyChord::function(8).to_string(), "f8");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub primary: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub key: ShortcutKey,
+}
+
+impl KeyChord {
+    /// Ctrl+`key` (Cmd+`key` on macOS)
+    pub fn primary(key: char) -> Self {
+        Self {
+            primary: true,
+            alt: false,
+            shift: false,
+            key: ShortcutKey::Char(key.to_ascii_lowercase()),
+        }
+    }
+
+    /// Alt+`key` (Option+`key` on macOS)
+    pub fn alt(key: char) -> Self {
+        Self {
+            primary: false,
+            alt: true,
+            shift: false,
+            key: ShortcutKey::Char(key.to_ascii_lowercase()),
+        }
+    }
+
+    /// A bare function key, e.g. `F6`
+    pub fn function(number: u8) -> Self {
+        Self {
+            primary: false,
+            alt: false,
+            shift: false,
+            key: ShortcutKey::Function(number),
+        }
+    }
+
+    /// The same chord with Shift held
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    /// AppleScript `System Events` statement pressing this chord
+    #[cfg(target_os = "macos")]
+    pub(crate) fn applescript(&self) -> String {
+        let mut modifiers = Vec::new();
+        if self.primary {
+            modifiers.push("command down");
+        }
+        if self.alt {
+            modifiers.push("option down");
+        }
+        if self.shift {
+            modifiers.push("shift down");
+        }
+        let using = if modifiers.is_empty() {
+            String::new()
+        } else {
+            format!(" using {{{}}}", modifiers.join(", "))
+        };
+
+        match self.key {
+            ShortcutKey::Char(key) => format!("keystroke \"{key}\"{using}"),
+            ShortcutKey::Function(number) => {
+                // macOS virtual key codes of F1-F12
+                const CODES: [u8; 12] = [122, 120, 99, 118, 96, 97, 98, 100, 101, 109, 103, 111];
+                let code = CODES[usize::from(number.clamp(1, 12)) - 1];
+                format!("key code {code}{using}")
+            }
+        }
+    }
+}
+
+/// `ctrl+shift+l`, `alt+d`, `f8`: the format the bundled PowerShell script reads
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.primary {
+            f.write_str("ctrl+")?;
+        }
+        if self.alt {
+            f.write_str("alt+")?;
+        }
+        if self.shift {
+            f.write_str("shift+")?;
+        }
+        match self.key {
+            ShortcutKey::Char(key) => write!(f, "{key}"),
+            ShortcutKey::Function(number) => write!(f, "f{number}"),
+        }
+    }
+}
+
+/// Address-bar shortcuts tried for `browser_type` unless configured otherwise
+///
+/// Ctrl+L comes first everywhere. Opera and Vivaldi also keep the F8 of the
+/// old Opera, and Alt+D works in every Chromium-based browser on Windows.
+pub fn default_shortcuts(browser_type: &BrowserType) -> Vec<KeyChord> {
+    match browser_type {
+        BrowserType::Opera | BrowserType::Vivaldi => vec![
+            KeyChord::primary('l'),
+            KeyChord::function(8),
+            KeyChord::alt('d'),
+        ],
+        _ => vec![KeyChord::primary('l')],
+    }
+}
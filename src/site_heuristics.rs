@@ -0,0 +1,311 @@
+// ================================================================================================
+// Site-specific title heuristics - サイト別タイトル解析
+// ================================================================================================
+
+use crate::BrowserInfo;
+use crate::url_extraction::domain_of;
+
+/// Unread count encoded in a page title, e.g. `"(3) WhatsApp"` → `Some(3)`
+///
+/// Recognised patterns:
+/// - leading badge: `(3) WhatsApp`, `(12) Home / X`, `(99+) Facebook`, `[2] Discord`
+/// - Gmail-style inbox count: `Inbox (1,234) - user@example.com - Gmail`
+///
+/// Capped badges such as `99+` return the displayed number.
+///
+/// ```rust
+/// use browser_info::site_heuristics::unread_badge;
+///
+/// assert_eq!(unread_badge("(3) WhatsApp"), Some(3));
+/// assert_eq!(unread_badge("Inbox (1,234) - me@example.com - Gmail"), Some(1234));
+/// assert_eq!(unread_badge("Rust (programming language) - Wikipedia"), None);
+/// ```
+pub fn unread_badge(title: &str) -> Option<u32> {
+    let title = title.trim_start();
+
+    leading_badge(title, '(', ')')
+        .or_else(|| leading_badge(title, '[', ']'))
+        .or_else(|| inbox_count(title))
+}
+
+fn leading_badge(title: &str, open: char, close: char) -> Option<u32> {
+    let rest = title.strip_prefix(open)?;
+    let (badge, after) = rest.split_once(close)?;
+
+    // A badge is followed by the actual title, never the end of the string
+    if after.trim().is_empty() {
+        return None;
+    }
+    parse_count(badge)
+}
+
+fn inbox_count(title: &str) -> Option<u32> {
+    let rest = title.strip_prefix("Inbox (")?;
+    let (count, _) = rest.split_once(')')?;
+    parse_count(count)
+}
+
+fn parse_count(badge: &str) -> Option<u32> {
+    let digits: String = badge
+        .trim()
+        .trim_end_matches('+')
+        .chars()
+        .filter(|c| *c != ',' && *c != '.')
+        .collect();
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Language guessed from the writing system of a title (BCP 47 code)
+///
+/// Only scripts that point to one language with reasonable confidence are
+/// recognised; Latin-script titles return `None`. Japanese is detected by kana,
+/// so a title with only Han characters is taken as Chinese.
+///
+/// ```rust
+/// use browser_info::site_heuristics::language_from_title;
+///
+/// assert_eq!(language_from_title("ニュース - Yahoo!ニュース"), Some("ja"));
+/// assert_eq!(language_from_title("네이버"), Some("ko"));
+/// assert_eq!(language_from_title("GitHub"), None);
+/// ```
+pub fn language_from_title(title: &str) -> Option<&'static str> {
+    let mut han = false;
+    let mut detected = None;
+
+    for c in title.chars() {
+        let language = match c {
+            '\u{3040}'..='\u{30FF}' => return Some("ja"),
+            '\u{4E00}'..='\u{9FFF}' => {
+                han = true;
+                continue;
+            }
+            '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}' => "ko",
+            '\u{0400}'..='\u{04FF}' => "ru",
+            '\u{0370}'..='\u{03FF}' => "el",
+            '\u{0590}'..='\u{05FF}' => "he",
+            '\u{0600}'..='\u{06FF}' => "ar",
+            '\u{0900}'..='\u{097F}' => "hi",
+            '\u{0E00}'..='\u{0E7F}' => "th",
+            _ => continue,
+        };
+        detected.get_or_insert(language);
+    }
+
+    detected.or(han.then_some("zh"))
+}
+
+/// Site names that titles end with, and the hosts serving them
+///
+/// An entry ending in `.` matches any top-level domain (`google.co.jp`).
+const SITE_SUFFIXES: [(&str, &[&str]); 12] = [
+    ("github", &["github.com"]),
+    ("gitlab", &["gitlab.com"]),
+    ("youtube", &["youtube.com", "youtu.be"]),
+    ("stackoverflow", &["stackoverflow.com"]),
+    ("wikipedia", &["wikipedia.org"]),
+    ("gmail", &["mail.google.com"]),
+    ("googlesearch", &["google."]),
+    ("reddit", &["reddit.com"]),
+    ("x", &["x.com", "twitter.com"]),
+    ("claude", &["claude.ai"]),
+    ("chatgpt", &["chatgpt.com", "chat.openai.com"]),
+    ("notion", &["notion.so", "notion.site"]),
+];
+
+/// Separators between a page title and the site name
+const TITLE_SEPARATORS: [&str; 5] = [" - ", " | ", " — ", " – ", " · "];
+
+/// Browser names appended to window titles (`... - Google Chrome`)
+const BROWSER_SUFFIXES: [&str; 8] = [
+    "chrome", "firefox", "edge", "brave", "opera", "vivaldi", "safari", "chromium",
+];
+
+/// Whether `url` plausibly belongs to the site named at the end of `title`
+///
+/// `Some(false)` means the title names a different site than the URL's host,
+/// typically a clipboard race that copied another window's address. `None`
+/// when the title names no site this check recognises: a well-known site name
+/// or a domain such as `example.com`. Browser names appended to window titles
+/// are ignored.
+///
+/// ```rust
+/// use browser_info::site_heuristics::title_matches_url;
+///
+/// let title = "rust-lang/rust - GitHub - Google Chrome";
+/// assert_eq!(title_matches_url(title, "https://github.com/rust-lang/rust"), Some(true));
+/// assert_eq!(title_matches_url(title, "https://www.youtube.com/"), Some(false));
+/// assert_eq!(title_matches_url("Notes | docs.rs", "https://docs.rs/serde"), Some(true));
+/// assert_eq!(title_matches_url("My notes", "https://example.com/"), None);
+/// ```
+pub fn title_matches_url(title: &str, url: &str) -> Option<bool> {
+    let host = domain_of(url)?;
+    let site = title_site(title)?;
+
+    let normalized: String = site
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if let Some((_, hosts)) = SITE_SUFFIXES.iter().find(|(name, _)| *name == normalized) {
+        return Some(hosts.iter().any(|pattern| host_matches(&host, pattern)));
+    }
+
+    // "example.com" のようにドメインそのものが書かれている場合
+    let site = site.to_lowercase();
+    let site = site.strip_prefix("www.").unwrap_or(&site);
+    let is_domain = site.contains('.')
+        && site
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '.' || c == '-');
+    is_domain.then(|| host_matches(&host, site))
+}
+
+/// Last title segment that is not the browser's own name
+fn title_site(title: &str) -> Option<&str> {
+    let mut rest = title.trim();
+    loop {
+        let (page, site) = TITLE_SEPARATORS
+            .iter()
+            .filter_map(|separator| rest.rsplit_once(separator))
+            .max_by_key(|(page, _)| page.len())?;
+        let site = site.trim();
+        let lower = site.to_lowercase();
+        if !BROWSER_SUFFIXES
+            .iter()
+            .any(|browser| lower.contains(browser))
+        {
+            return Some(site);
+        }
+        rest = page.trim_end();
+    }
+}
+
+fn host_matches(host: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix('.') {
+        // "google." matches google.com, www.google.co.jp, ...
+        Some(name) => host.split('.').rev().skip(1).any(|label| label == name),
+        None => host == pattern || host.ends_with(&format!(".{pattern}")),
+    }
+}
+
+/// Title pattern identifying an in-progress file transfer on one site
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferRule {
+    /// Matched case-insensitively against the URL and the title, e.g. `"dropbox"`
+    pub site: String,
+    /// Any of these in the title (case-insensitive) means a transfer is running
+    pub markers: Vec<String>,
+}
+
+impl TransferRule {
+    pub fn new<I, S>(site: impl Into<String>, markers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            site: site.into().to_lowercase(),
+            markers: markers
+                .into_iter()
+                .map(|marker| marker.into().to_lowercase())
+                .collect(),
+        }
+    }
+
+    fn matches(&self, url: &str, title: &str) -> bool {
+        (url.contains(&self.site) || title.contains(&self.site))
+            && self.markers.iter().any(|marker| title.contains(marker))
+    }
+}
+
+/// Detects web file-transfer pages (Drive, Dropbox, WeTransfer, ...) mid-transfer
+///
+/// Starts with built-in rules; add your own for other services:
+///
+/// ```rust
+/// use browser_info::site_heuristics::{TransferDetector, TransferRule};
+///
+/// let detector = TransferDetector::new()
+///     .rule(TransferRule::new("onedrive", ["uploading", "downloading"]));
+///
+/// assert!(detector.is_transfer_in_progress(
+///     "https://onedrive.live.com/",
+///     "Uploading 3 items - OneDrive",
+/// ));
+/// assert!(!detector.is_transfer_in_progress("https://onedrive.live.com/", "My files - OneDrive"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferDetector {
+    rules: Vec<TransferRule>,
+}
+
+impl Default for TransferDetector {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                TransferRule::new(
+                    "google drive",
+                    ["uploading", "downloading", "preparing download", "zipping"],
+                ),
+                TransferRule::new("drive.google.com", ["uploading", "downloading", "zipping"]),
+                TransferRule::new("dropbox", ["uploading", "downloading", "% complete"]),
+                TransferRule::new("wetransfer", ["transferring", "uploading", "%"]),
+            ],
+        }
+    }
+}
+
+impl TransferDetector {
+    /// Detector with the built-in rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Detector without any rules
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Add a rule
+    pub fn rule(mut self, rule: TransferRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn rules(&self) -> &[TransferRule] {
+        &self.rules
+    }
+
+    /// Whether the page looks like an upload/download in progress
+    pub fn is_transfer_in_progress(&self, url: &str, title: &str) -> bool {
+        let url = url.to_lowercase();
+        let title = title.to_lowercase();
+        self.rules.iter().any(|rule| rule.matches(&url, &title))
+    }
+}
+
+impl BrowserInfo {
+    /// Unread notification count shown in the page title, if any
+    ///
+    /// The title itself is left untouched. See [`unread_badge`] for the patterns.
+    pub fn unread_badge(&self) -> Option<u32> {
+        unread_badge(&self.title)
+    }
+
+    /// Whether the title names the site the URL points to (see [`title_matches_url`])
+    pub fn title_matches_url(&self) -> Option<bool> {
+        title_matches_url(&self.title, &self.url)
+    }
+
+    /// Hint that the page is a web file transfer in progress (built-in rules)
+    ///
+    /// Backup or shutdown automation can use this to avoid interrupting an
+    /// upload. Use a [`TransferDetector`] directly for custom rules.
+    pub fn is_transfer_in_progress(&self) -> bool {
+        TransferDetector::new().is_transfer_in_progress(&self.url, &self.title)
+    }
+}
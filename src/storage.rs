@@ -0,0 +1,944 @@
+//! Visit storage sink and usage aggregation.
+//!
+//! A [`Visit`] is one continuous stretch of time spent on a URL. Visits are
+//! appended to a [`VisitStore`] — in memory ([`MemoryStore`]) or in a JSON
+//! Lines file ([`JsonlStore`]) — and summarised with [`aggregate_by_domain`].
+//!
+//! A [`JsonlStore`] file starts with a format header. Files written by older
+//! versions of the crate are read as they are and upgraded in place before
+//! the first new visit is appended; [`JsonlStore::check`] and
+//! [`JsonlStore::repair`] deal with lines a crash left half-written.
+//!
+//! Visits carry the id of the device they were recorded on, so stores from
+//! several machines can be merged with [`export_visits`] / [`import_visits`].
+//!
+//! This module is only available with the `storage` feature enabled.
+//!
+//! ```rust
+//! use browser_info::storage::{Bucket, MemoryStore, Visit, VisitStore, aggregate_by_domain};
+//! use browser_info::BrowserType;
+//! use std::time::{Duration, SystemTime};
+//!
+//! let now = SystemTime::now();
+//! let mut store = MemoryStore::default();
+//! store.append(&Visit::new(
+//!     "https://www.youtube.com/watch?v=1",
+//!     "Video - YouTube",
+//!     BrowserType::Chrome,
+//!     now - Duration::from_secs(600),
+//!     Duration::from_secs(300),
+//! ))?;
+//!
+//! let top = aggregate_by_domain(&store, now - Duration::from_secs(3600)..now, Bucket::Total)?;
+//! assert_eq!(top[0].domain, "youtube.com");
+//! assert_eq!(top[0].duration, Duration::from_secs(300));
+//! # Ok::<(), browser_info::BrowserInfoError>(())
+//! ```
+
+use crate::url_extraction::domain_of;
+use crate::{BrowserInfo, BrowserInfoError, BrowserType};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One continuous stretch of focus on a URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Visit {
+    pub url: String,
+    /// Host used for grouping (see [`domain_of`])
+    pub domain: String,
+    pub title: String,
+    pub browser_type: BrowserType,
+    #[serde(with = "crate::serde_time::rfc3339")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub start: SystemTime,
+    /// Milliseconds when serialized
+    #[serde(with = "crate::serde_time::millis")]
+    #[cfg_attr(feature = "schemars", schemars(with = "u64"))]
+    pub duration: Duration,
+    /// Device the visit was recorded on; `None` means this device
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+}
+
+impl Visit {
+    pub fn new(
+        url: impl Into<String>,
+        title: impl Into<String>,
+        browser_type: BrowserType,
+        start: SystemTime,
+        duration: Duration,
+    ) -> Self {
+        let url = url.into();
+        Self {
+            domain: domain_of(&url).unwrap_or_default(),
+            url,
+            title: title.into(),
+            browser_type,
+            start,
+            duration,
+            device_id: None,
+        }
+    }
+
+    /// Visit for a page observed from `start` for `duration`
+    pub fn from_info(info: &BrowserInfo, start: SystemTime, duration: Duration) -> Self {
+        Self::new(
+            info.url.clone(),
+            info.title.clone(),
+            info.browser_type.clone(),
+            start,
+            duration,
+        )
+    }
+
+    /// End of the visit
+    pub fn end(&self) -> SystemTime {
+        self.start + self.duration
+    }
+
+    /// Record the visit as coming from another device
+    pub fn with_device_id(mut self, device_id: impl Into<String>) -> Self {
+        self.device_id = Some(device_id.into());
+        self
+    }
+}
+
+/// Destination for recorded visits
+pub trait VisitStore {
+    /// Persist one visit
+    fn append(&mut self, visit: &Visit) -> Result<(), BrowserInfoError>;
+
+    /// All visits overlapping `range`, in insertion order
+    fn visits(&self, range: Range<SystemTime>) -> Result<Vec<Visit>, BrowserInfoError>;
+}
+
+/// Volatile store, mainly for tests and short-lived tools
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    visits: Vec<Visit>,
+}
+
+impl VisitStore for MemoryStore {
+    fn append(&mut self, visit: &Visit) -> Result<(), BrowserInfoError> {
+        self.visits.push(visit.clone());
+        Ok(())
+    }
+
+    fn visits(&self, range: Range<SystemTime>) -> Result<Vec<Visit>, BrowserInfoError> {
+        Ok(self
+            .visits
+            .iter()
+            .filter(|visit| overlaps(visit, &range))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Append-only JSON Lines file: a format header, then one visit per line
+///
+/// Files of older formats are readable as they are. Before the first visit
+/// is appended they are upgraded in place ([`upgrade`](Self::upgrade)); the
+/// original is kept next to the file as `<name>.v<version>.bak`. Upgrades and
+/// [`repair`](Self::repair) rewrite the whole file, so nothing else should be
+/// writing to it at the time.
+#[derive(Debug, Clone)]
+pub struct JsonlStore {
+    path: PathBuf,
+    /// Whether [`upgrade`](Self::upgrade) already ran for this handle
+    upgraded: bool,
+}
+
+/// Outcome of [`JsonlStore::check`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Format version of the file (the oldest record's for files without header)
+    pub version: u32,
+    /// Readable visits
+    pub records: usize,
+    /// Line numbers (1-based) that are not valid visits, e.g. the last line of
+    /// a write cut short by a crash or power loss
+    pub corrupt_lines: Vec<usize>,
+}
+
+impl IntegrityReport {
+    /// Every line could be read
+    pub fn is_ok(&self) -> bool {
+        self.corrupt_lines.is_empty()
+    }
+}
+
+/// Outcome of [`JsonlStore::upgrade`] and [`JsonlStore::repair`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteReport {
+    pub from_version: u32,
+    /// Visits in the rewritten file
+    pub records: usize,
+    /// Unreadable lines, appended unchanged to [`JsonlStore::corrupt_path`]
+    pub set_aside: usize,
+    /// Copy of the file as it was before
+    pub backup: PathBuf,
+}
+
+/// First line of a visit file
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileHeader {
+    browser_info_visits: u32,
+}
+
+/// Contents of a visit file
+struct VisitFile {
+    /// From the header; `None` for files written before there was one
+    version: Option<u32>,
+    /// Non-empty lines other than the header, with their line numbers
+    lines: Vec<(usize, String)>,
+}
+
+/// Upgrade of one record to the next format version
+type Migration = fn(&mut serde_json::Value) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` turns a version `n + 1` record into version `n + 2`
+const MIGRATIONS: &[Migration] = &[rfc3339_times];
+
+impl JsonlStore {
+    /// Format written by this version of the crate
+    ///
+    /// 1. `start` / `duration` in serde's default representation
+    ///    (`{"secs_since_epoch":…}`, `{"secs":…,"nanos":…}`), no header
+    /// 2. RFC 3339 `start`, `duration` in milliseconds, optional `device_id`;
+    ///    header `{"browser_info_visits":2}` (files from before the header
+    ///    are recognized by their records)
+    pub const FORMAT_VERSION: u32 = 2;
+
+    /// Use (and create on first write) the file at `path`
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            upgraded: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Where [`upgrade`](Self::upgrade) and [`repair`](Self::repair) put
+    /// unreadable lines: `<name>.corrupt`
+    pub fn corrupt_path(&self) -> PathBuf {
+        self.sibling(".corrupt")
+    }
+
+    /// Read every line and report the ones that are not valid visits
+    ///
+    /// ```rust,no_run
+    /// use browser_info::storage::JsonlStore;
+    ///
+    /// let store = JsonlStore::open("visits.jsonl");
+    /// if !store.check()?.is_ok()
+    ///     && let Some(report) = store.repair()?
+    /// {
+    ///     println!("🩹 {} lines moved to {}", report.set_aside, store.corrupt_path().display());
+    /// }
+    /// # Ok::<(), browser_info::BrowserInfoError>(())
+    /// ```
+    pub fn check(&self) -> Result<IntegrityReport, BrowserInfoError> {
+        let Some(file) = self.read()? else {
+            return Ok(IntegrityReport {
+                version: Self::FORMAT_VERSION,
+                ..IntegrityReport::default()
+            });
+        };
+        let mut report = IntegrityReport {
+            version: file.format_version(),
+            ..IntegrityReport::default()
+        };
+        for (number, line) in &file.lines {
+            match read_record(line, file.version) {
+                Ok(_) => report.records += 1,
+                Err(_) => report.corrupt_lines.push(*number),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Rewrite the file without its unreadable lines, in the current format
+    ///
+    /// The removed lines are appended to [`corrupt_path`](Self::corrupt_path)
+    /// and the original is kept as a backup, so nothing is lost. `None` when
+    /// the file is missing or needs no repair.
+    pub fn repair(&self) -> Result<Option<RewriteReport>, BrowserInfoError> {
+        let Some(file) = self.read()? else {
+            return Ok(None);
+        };
+        let damaged = file
+            .lines
+            .iter()
+            .any(|(_, line)| read_record(line, file.version).is_err());
+        if !damaged && file.format_version() == Self::FORMAT_VERSION {
+            return Ok(None);
+        }
+        self.rewrite(file).map(Some)
+    }
+
+    /// Convert a file of an older format to [`FORMAT_VERSION`](Self::FORMAT_VERSION)
+    ///
+    /// Runs by itself before the first [`append`](VisitStore::append); call it
+    /// to upgrade at a time of your choosing. `None` when the file is missing
+    /// or already current. Files of a newer format are refused.
+    pub fn upgrade(&self) -> Result<Option<RewriteReport>, BrowserInfoError> {
+        let Some(file) = self.read()? else {
+            return Ok(None);
+        };
+        if file.format_version() >= Self::FORMAT_VERSION {
+            return Ok(None);
+        }
+        let report = self.rewrite(file)?;
+        log::info!(
+            "Upgraded {} from format {} to {} ({} visits, {} unreadable lines set aside)",
+            self.path.display(),
+            report.from_version,
+            Self::FORMAT_VERSION,
+            report.records,
+            report.set_aside
+        );
+        Ok(Some(report))
+    }
+
+    fn read(&self) -> Result<Option<VisitFile>, BrowserInfoError> {
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(storage_error(e)),
+        };
+
+        let mut file = VisitFile {
+            version: None,
+            lines: Vec::new(),
+        };
+        for (index, line) in bytes.split(|byte| *byte == b'\n').enumerate() {
+            // 壊れた行は不正な UTF-8 を含みうる。読めない行として扱う
+            let line = String::from_utf8_lossy(line);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            // 同時に作成した 2 つのプロセスがどちらもヘッダーを書くことがある
+            match serde_json::from_str::<FileHeader>(line) {
+                Ok(header) => {
+                    file.version.get_or_insert(header.browser_info_visits);
+                }
+                Err(_) => file.lines.push((index + 1, line.to_string())),
+            }
+        }
+        if let Some(version) = file.version
+            && version > Self::FORMAT_VERSION
+        {
+            return Err(storage_error(format!(
+                "visit file format version {version} is newer than supported ({})",
+                Self::FORMAT_VERSION
+            )));
+        }
+        Ok(Some(file))
+    }
+
+    /// Write `file` back in the current format, keeping a backup of the original
+    fn rewrite(&self, file: VisitFile) -> Result<RewriteReport, BrowserInfoError> {
+        let from_version = file.format_version();
+        let mut kept = header_line()?;
+        let mut rejected = String::new();
+        let mut records = 0;
+        for (_, line) in &file.lines {
+            match read_record(line, file.version) {
+                Ok(visit) => {
+                    kept.push_str(&serde_json::to_string(&visit).map_err(storage_error)?);
+                    kept.push('\n');
+                    records += 1;
+                }
+                Err(_) => {
+                    rejected.push_str(line);
+                    rejected.push('\n');
+                }
+            }
+        }
+
+        let backup = self.sibling(&format!(".v{from_version}.bak"));
+        std::fs::copy(&self.path, &backup).map_err(storage_error)?;
+        if !rejected.is_empty() {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.corrupt_path())
+                .and_then(|mut corrupt| corrupt.write_all(rejected.as_bytes()))
+                .map_err(storage_error)?;
+        }
+        // 書き終えてから置き換えるので、途中で落ちても元のファイルは残る
+        let temporary = self.sibling(".tmp");
+        File::create(&temporary)
+            .and_then(|mut out| {
+                out.write_all(kept.as_bytes())?;
+                out.sync_all()
+            })
+            .map_err(storage_error)?;
+        std::fs::rename(&temporary, &self.path).map_err(storage_error)?;
+
+        Ok(RewriteReport {
+            from_version,
+            records,
+            set_aside: rejected.lines().count(),
+            backup,
+        })
+    }
+
+    /// `path` with `suffix` appended to the file name
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(suffix);
+        PathBuf::from(name)
+    }
+}
+
+impl VisitFile {
+    /// The header's version, or that of the oldest record for files without header
+    fn format_version(&self) -> u32 {
+        self.version.unwrap_or_else(|| {
+            self.lines
+                .iter()
+                .filter_map(|(_, line)| serde_json::from_str(line).ok())
+                .map(|record| record_version(&record))
+                .min()
+                .unwrap_or(JsonlStore::FORMAT_VERSION)
+        })
+    }
+}
+
+impl VisitStore for JsonlStore {
+    fn append(&mut self, visit: &Visit) -> Result<(), BrowserInfoError> {
+        if !self.upgraded {
+            self.upgrade()?;
+            self.upgraded = true;
+        }
+        let line = serde_json::to_string(visit).map_err(storage_error)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(storage_error)?;
+        if file.metadata().map_err(storage_error)?.len() == 0 {
+            file.write_all(header_line()?.as_bytes())
+                .map_err(storage_error)?;
+        }
+        writeln!(file, "{line}").map_err(storage_error)
+    }
+
+    fn visits(&self, range: Range<SystemTime>) -> Result<Vec<Visit>, BrowserInfoError> {
+        let Some(file) = self.read()? else {
+            return Ok(Vec::new());
+        };
+
+        let mut visits = Vec::new();
+        for (number, line) in &file.lines {
+            let visit = read_record(line, file.version).map_err(|e| {
+                storage_error(format!(
+                    "{} line {number}: {e} (JsonlStore::repair sets such lines aside)",
+                    self.path.display()
+                ))
+            })?;
+            if overlaps(&visit, &range) {
+                visits.push(visit);
+            }
+        }
+        Ok(visits)
+    }
+}
+
+fn header_line() -> Result<String, BrowserInfoError> {
+    let header = FileHeader {
+        browser_info_visits: JsonlStore::FORMAT_VERSION,
+    };
+    let mut line = serde_json::to_string(&header).map_err(storage_error)?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// One visit line of a file with header `version`, upgraded when it is older
+fn read_record(line: &str, version: Option<u32>) -> Result<Visit, String> {
+    if let Ok(visit) = serde_json::from_str(line) {
+        return Ok(visit);
+    }
+    let mut record: serde_json::Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    // ヘッダーのない古いファイルは新旧の行が混ざりうるので行ごとに見分ける
+    let version = version.unwrap_or_else(|| record_version(&record));
+    for migration in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+        migration(&mut record)?;
+    }
+    serde_json::from_value(record).map_err(|e| e.to_string())
+}
+
+/// Format of a record found in a file without header
+fn record_version(record: &serde_json::Value) -> u32 {
+    if record
+        .get("start")
+        .is_some_and(serde_json::Value::is_object)
+    {
+        1
+    } else {
+        2
+    }
+}
+
+/// Version 1 → 2: `SystemTime` / `Duration` as RFC 3339 / milliseconds
+fn rfc3339_times(record: &mut serde_json::Value) -> Result<(), String> {
+    let fields = record.as_object_mut().ok_or("not a JSON object")?;
+    let start: SystemTime = fields
+        .remove("start")
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| e.to_string())?
+        .ok_or("missing start")?;
+    let duration: Duration = fields
+        .remove("duration")
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| e.to_string())?
+        .ok_or("missing duration")?;
+    fields.insert(
+        "start".to_string(),
+        humantime::format_rfc3339_millis(start).to_string().into(),
+    );
+    fields.insert(
+        "duration".to_string(),
+        u64::try_from(duration.as_millis())
+            .unwrap_or(u64::MAX)
+            .into(),
+    );
+    Ok(())
+}
+
+/// Buffers visits in memory and hands them to `inner` in batches
+///
+/// Meant for always-on agents: the disk is touched once per `max_pending`
+/// visits (or when [`flush`](Self::flush) is called) instead of once per
+/// visit. Pending visits are flushed on drop and are already visible to
+/// [`visits`](VisitStore::visits).
+#[derive(Debug)]
+pub struct BatchedStore<S: VisitStore> {
+    inner: S,
+    pending: Vec<Visit>,
+    max_pending: usize,
+}
+
+impl<S: VisitStore> BatchedStore<S> {
+    /// Write to `inner` every `max_pending` visits
+    pub fn new(inner: S, max_pending: usize) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+            max_pending: max_pending.max(1),
+        }
+    }
+
+    /// Write all pending visits now
+    ///
+    /// Visits that could not be written stay pending for the next attempt.
+    pub fn flush(&mut self) -> Result<(), BrowserInfoError> {
+        let mut written = 0;
+        let result = self
+            .pending
+            .iter()
+            .try_for_each(|visit| self.inner.append(visit).map(|()| written += 1));
+        self.pending.drain(..written);
+        result
+    }
+
+    /// Number of visits not yet written
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: VisitStore> VisitStore for BatchedStore<S> {
+    fn append(&mut self, visit: &Visit) -> Result<(), BrowserInfoError> {
+        self.pending.push(visit.clone());
+        if self.pending.len() >= self.max_pending {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn visits(&self, range: Range<SystemTime>) -> Result<Vec<Visit>, BrowserInfoError> {
+        let mut visits = self.inner.visits(range.clone())?;
+        visits.extend(
+            self.pending
+                .iter()
+                .filter(|visit| overlaps(visit, &range))
+                .cloned(),
+        );
+        Ok(visits)
+    }
+}
+
+impl<S: VisitStore> Drop for BatchedStore<S> {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            log::warn!("Failed to flush {} pending visits: {e}", self.pending.len());
+        }
+    }
+}
+
+/// Time bucketing for aggregation
+///
+/// Hours and days start on the UTC clock with [`aggregate_by_domain`], and on
+/// the local clock with [`aggregate_by_domain_local`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bucket {
+    Hour,
+    Day,
+    /// One bucket spanning the whole requested range
+    Total,
+}
+
+impl Bucket {
+    fn start_of(&self, time: SystemTime, range_start: SystemTime, offset: UtcOffset) -> SystemTime {
+        let size: i64 = match self {
+            Bucket::Hour => 3600,
+            Bucket::Day => 86_400,
+            Bucket::Total => return range_start,
+        };
+        let offset = i64::from(offset.seconds);
+        // 現地時刻で切り捨ててから UTC に戻す
+        let local = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64
+            + offset;
+        let start = local - local.rem_euclid(size) - offset;
+        UNIX_EPOCH + Duration::from_secs(start.max(0) as u64)
+    }
+
+    fn end_of(&self, bucket_start: SystemTime, range_end: SystemTime) -> SystemTime {
+        match self {
+            Bucket::Hour => bucket_start + Duration::from_secs(3600),
+            Bucket::Day => bucket_start + Duration::from_secs(86_400),
+            Bucket::Total => range_end,
+        }
+    }
+}
+
+/// Offset of local time from UTC, so that day buckets start at local midnight
+///
+/// The crate ships no time zone database: pass the offset in effect for the
+/// aggregated range, e.g. `UtcOffset::from_seconds(chrono::Local::now().offset().local_minus_utc())`.
+/// A range spanning a daylight saving change is bucketed with the one offset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct UtcOffset {
+    seconds: i32,
+}
+
+impl UtcOffset {
+    pub const UTC: UtcOffset = UtcOffset { seconds: 0 };
+
+    /// `seconds` east of UTC (negative west of it)
+    pub const fn from_seconds(seconds: i32) -> Self {
+        Self { seconds }
+    }
+
+    /// Whole hours east of UTC, e.g. `from_hours(9)` for Japan
+    pub const fn from_hours(hours: i32) -> Self {
+        Self::from_seconds(hours * 3600)
+    }
+
+    pub const fn seconds(&self) -> i32 {
+        self.seconds
+    }
+}
+
+/// Time spent on one domain within one bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DomainUsage {
+    #[serde(with = "crate::serde_time::rfc3339")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub bucket_start: SystemTime,
+    pub domain: String,
+    /// Milliseconds when serialized
+    #[serde(with = "crate::serde_time::millis")]
+    #[cfg_attr(feature = "schemars", schemars(with = "u64"))]
+    pub duration: Duration,
+    /// Number of visits starting or continuing in this bucket
+    pub visits: u32,
+}
+
+/// Dwell time per domain within `range`, split into UTC hours or days
+///
+/// Visits are clipped to the range and split at bucket boundaries. Results are
+/// ordered by bucket, then by descending duration. Days run from midnight
+/// UTC; use [`aggregate_by_domain_local`] for the user's calendar days.
+pub fn aggregate_by_domain(
+    store: &dyn VisitStore,
+    range: Range<SystemTime>,
+    bucket: Bucket,
+) -> Result<Vec<DomainUsage>, BrowserInfoError> {
+    aggregate_by_domain_local(store, range, bucket, UtcOffset::UTC)
+}
+
+/// [`aggregate_by_domain`] with hours and days on the clock `offset` from UTC
+///
+/// ```rust
+/// use browser_info::BrowserType;
+/// use browser_info::storage::{Bucket, MemoryStore, UtcOffset, Visit, VisitStore, aggregate_by_domain_local};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// // 2024-01-01 23:30 in Japan is still 2024-01-01 14:30 UTC
+/// let start = UNIX_EPOCH + Duration::from_secs(1_704_119_400);
+/// let mut store = MemoryStore::default();
+/// store.append(&Visit::new("https://github.com", "GitHub", BrowserType::Chrome, start, Duration::from_secs(3600)))?;
+///
+/// let days = aggregate_by_domain_local(&store, start..start + Duration::from_secs(3600), Bucket::Day, UtcOffset::from_hours(9))?;
+/// // Split at midnight in Japan: 30 minutes on each local day
+/// assert_eq!(days.len(), 2);
+/// assert_eq!(days[0].duration, Duration::from_secs(1800));
+/// assert_eq!(days[1].bucket_start, UNIX_EPOCH + Duration::from_secs(1_704_121_200));
+/// # Ok::<(), browser_info::BrowserInfoError>(())
+/// ```
+pub fn aggregate_by_domain_local(
+    store: &dyn VisitStore,
+    range: Range<SystemTime>,
+    bucket: Bucket,
+    offset: UtcOffset,
+) -> Result<Vec<DomainUsage>, BrowserInfoError> {
+    let mut totals: HashMap<(SystemTime, String), (Duration, u32)> = HashMap::new();
+
+    for visit in store.visits(range.clone())? {
+        let mut cursor = visit.start.max(range.start);
+        let end = visit.end().min(range.end);
+
+        while cursor < end {
+            let bucket_start = bucket.start_of(cursor, range.start, offset);
+            let slice_end = bucket.end_of(bucket_start, range.end).min(end);
+            let slice = slice_end.duration_since(cursor).unwrap_or_default();
+
+            let entry = totals
+                .entry((bucket_start, visit.domain.clone()))
+                .or_default();
+            entry.0 += slice;
+            entry.1 += 1;
+            cursor = slice_end;
+        }
+    }
+
+    let mut usage: Vec<DomainUsage> = totals
+        .into_iter()
+        .map(|((bucket_start, domain), (duration, visits))| DomainUsage {
+            bucket_start,
+            domain,
+            duration,
+            visits,
+        })
+        .collect();
+    usage.sort_by(|a, b| {
+        a.bucket_start
+            .cmp(&b.bucket_start)
+            .then(b.duration.cmp(&a.duration))
+            .then(a.domain.cmp(&b.domain))
+    });
+    Ok(usage)
+}
+
+/// Stable identifier of this machine, generated on first use
+///
+/// Stored in `browser-info/device_id` under the user's configuration directory
+/// (`%APPDATA%`, `~/Library/Application Support` or `$XDG_CONFIG_HOME`).
+pub fn local_device_id() -> Result<String, BrowserInfoError> {
+    static DEVICE_ID: OnceLock<String> = OnceLock::new();
+
+    if let Some(id) = DEVICE_ID.get() {
+        return Ok(id.clone());
+    }
+    let path = crate::config::data_dir()
+        .ok_or_else(|| storage_error("no configuration directory"))?
+        .join("device_id");
+
+    let id = match std::fs::read_to_string(&path) {
+        Ok(id) if !id.trim().is_empty() => id.trim().to_string(),
+        _ => {
+            let id = generate_device_id();
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(storage_error)?;
+            }
+            std::fs::write(&path, &id).map_err(storage_error)?;
+            id
+        }
+    };
+    Ok(DEVICE_ID.get_or_init(|| id).clone())
+}
+
+/// Visits of one device, ready to be merged into another store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VisitExport {
+    /// Format version, currently 1
+    pub version: u32,
+    /// Device that produced the export
+    pub device_id: String,
+    #[serde(with = "crate::serde_time::rfc3339")]
+    pub exported_at: SystemTime,
+    /// Every visit has its `device_id` set
+    pub visits: Vec<Visit>,
+}
+
+impl VisitExport {
+    pub const VERSION: u32 = 1;
+
+    pub fn to_writer(&self, writer: impl Write) -> Result<(), BrowserInfoError> {
+        serde_json::to_writer(writer, self).map_err(storage_error)
+    }
+
+    pub fn from_reader(reader: impl Read) -> Result<Self, BrowserInfoError> {
+        let export: Self = serde_json::from_reader(reader).map_err(storage_error)?;
+        if export.version > Self::VERSION {
+            return Err(storage_error(format!(
+                "export format version {} is newer than supported ({})",
+                export.version,
+                Self::VERSION
+            )));
+        }
+        Ok(export)
+    }
+}
+
+/// Visits of `range` tagged with this device's id
+pub fn export_visits(
+    store: &dyn VisitStore,
+    range: Range<SystemTime>,
+) -> Result<VisitExport, BrowserInfoError> {
+    let device_id = local_device_id()?;
+    let visits = store
+        .visits(range)?
+        .into_iter()
+        .map(|mut visit| {
+            visit.device_id.get_or_insert_with(|| device_id.clone());
+            visit
+        })
+        .collect();
+
+    Ok(VisitExport {
+        version: VisitExport::VERSION,
+        device_id,
+        exported_at: SystemTime::now(),
+        visits,
+    })
+}
+
+/// Outcome of [`import_visits`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    pub imported: usize,
+    /// Visits the store already had (same device, URL and start)
+    pub duplicates: usize,
+}
+
+/// Merge an export from another device into `store`
+///
+/// Visits are identified by device, URL and start time, so importing the same
+/// export twice, or an export containing this device's own visits, is harmless.
+///
+/// ```rust
+/// use browser_info::storage::{MemoryStore, Visit, VisitExport, VisitStore, import_visits};
+/// use browser_info::BrowserType;
+/// use std::time::{Duration, SystemTime};
+///
+/// let now = SystemTime::now();
+/// let export = VisitExport {
+///     version: VisitExport::VERSION,
+///     device_id: "laptop".to_string(),
+///     exported_at: now,
+///     visits: vec![
+///         Visit::new("https://github.com", "GitHub", BrowserType::Firefox, now, Duration::from_secs(60))
+///             .with_device_id("laptop"),
+///     ],
+/// };
+///
+/// let mut store = MemoryStore::default();
+/// assert_eq!(import_visits(&mut store, &export)?.imported, 1);
+/// assert_eq!(import_visits(&mut store, &export)?.duplicates, 1);
+/// # Ok::<(), browser_info::BrowserInfoError>(())
+/// ```
+pub fn import_visits(
+    store: &mut dyn VisitStore,
+    export: &VisitExport,
+) -> Result<MergeReport, BrowserInfoError> {
+    let mut report = MergeReport::default();
+    let Some(range) = export
+        .visits
+        .iter()
+        .map(|visit| visit.start..visit.end() + Duration::from_millis(1))
+        .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end))
+    else {
+        return Ok(report);
+    };
+
+    // Visits of this device are stored without an id
+    let local_id = local_device_id().ok();
+    let identity = |visit: &Visit| {
+        let device = visit.device_id.clone().or_else(|| local_id.clone());
+        (device, visit.url.clone(), visit.start)
+    };
+
+    let mut known: HashSet<_> = store.visits(range)?.iter().map(identity).collect();
+    for visit in &export.visits {
+        let mut visit = visit.clone();
+        visit
+            .device_id
+            .get_or_insert_with(|| export.device_id.clone());
+        // Our own visits coming back from another machine stay untagged
+        if visit.device_id == local_id {
+            visit.device_id = None;
+        }
+
+        if !known.insert(identity(&visit)) {
+            report.duplicates += 1;
+            continue;
+        }
+        store.append(&visit)?;
+        report.imported += 1;
+    }
+    Ok(report)
+}
+
+fn generate_device_id() -> String {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    // RandomState is seeded from the OS random source
+    let mut halves = [0u64; 2];
+    for half in &mut halves {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        );
+        hasher.write_u32(std::process::id());
+        *half = hasher.finish();
+    }
+    format!("{:016x}{:016x}", halves[0], halves[1])
+}
+
+fn overlaps(visit: &Visit, range: &Range<SystemTime>) -> bool {
+    visit.start < range.end && visit.end() > range.start
+}
+
+fn storage_error(e: impl std::fmt::Display) -> BrowserInfoError {
+    BrowserInfoError::StorageError(e.to_string())
+}
@@ -0,0 +1,239 @@
+//! Per-method success rate and latency, and the learned Auto pipeline order.
+//!
+//! Every extraction records how each method it tried fared, separately for
+//! each [`BrowserType`]: DevTools answering quickly for Chrome says nothing
+//! about Firefox. The statistics live in memory for the process and, with the `serde` feature, are persisted
+//! to `browser-info/strategy_stats.json` under the user's configuration
+//! directory so the learned order survives restarts.
+//!
+//! [`get_browser_info`](crate::get_browser_info) uses them to decide whether
+//! DevTools should be tried before the native methods for the focused browser:
+//! once both have enough samples, the one with the lower expected cost (mean
+//! latency divided by success rate) goes first. Title guessing always stays last.
+
+use crate::BrowserType;
+use crate::url_extraction::{AttemptOutcome, ExtractionAttempt, UrlSource};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+
+/// Where DevTools goes in the async pipeline, relative to the native methods
+///
+/// Title guessing stays last whatever the order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum StrategyOrder {
+    /// DevTools first once it has proven cheaper ([`prefers_devtools`])
+    #[default]
+    Learned,
+    NativeFirst,
+    /// DevTools first; reads the page without a clipboard race and reports
+    /// tabs, window state and certificate
+    DevToolsFirst,
+}
+
+/// Samples a method needs before it takes part in reordering
+const MIN_SAMPLES: u64 = 10;
+
+/// Records between two writes of the stats file
+#[cfg(feature = "serde")]
+const SAVE_EVERY: u64 = 10;
+
+/// Aggregated outcome of one extraction method
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StrategyStats {
+    pub browser_type: BrowserType,
+    pub source: UrlSource,
+    /// Times the method ran (skipped methods are not counted)
+    pub attempts: u64,
+    pub successes: u64,
+    /// Sum of the latencies of all runs
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_time::millis"))]
+    #[cfg_attr(feature = "schemars", schemars(with = "u64"))]
+    pub total_latency: Duration,
+}
+
+impl StrategyStats {
+    fn new(browser_type: BrowserType, source: UrlSource) -> Self {
+        Self {
+            browser_type,
+            source,
+            attempts: 0,
+            successes: 0,
+            total_latency: Duration::ZERO,
+        }
+    }
+
+    /// Share of runs that produced a URL, 0.0 – 1.0
+    pub fn success_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            self.successes as f64 / self.attempts as f64
+        }
+    }
+
+    pub fn mean_latency(&self) -> Option<Duration> {
+        (self.attempts > 0).then(|| self.total_latency / self.attempts as u32)
+    }
+
+    /// Expected time until a URL is obtained; `None` without enough samples
+    fn expected_cost(&self) -> Option<f64> {
+        if self.attempts < MIN_SAMPLES || self.successes == 0 {
+            return None;
+        }
+        Some(self.mean_latency()?.as_secs_f64() / self.success_rate())
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    stats: Vec<StrategyStats>,
+    unsaved: u64,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        Mutex::new(Registry {
+            stats: load(),
+            unsaved: 0,
+        })
+    })
+}
+
+/// Snapshot of the statistics gathered so far (including earlier runs when persisted)
+pub fn strategy_stats() -> Vec<StrategyStats> {
+    registry()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .stats
+        .clone()
+}
+
+/// Forget everything learned and go back to the default order
+pub fn reset_strategy_stats() {
+    let mut registry = registry().lock().unwrap_or_else(PoisonError::into_inner);
+    registry.stats.clear();
+    registry.unsaved = 0;
+    save(&registry.stats);
+}
+
+/// Whether DevTools has proven cheaper than every native method for `browser_type`
+pub fn prefers_devtools(browser_type: &BrowserType) -> bool {
+    let registry = registry().lock().unwrap_or_else(PoisonError::into_inner);
+    let cost = |source: UrlSource| {
+        registry
+            .stats
+            .iter()
+            .find(|stats| stats.browser_type == *browser_type && stats.source == source)
+            .and_then(StrategyStats::expected_cost)
+    };
+
+    let Some(devtools) = cost(UrlSource::DevTools) else {
+        return false;
+    };
+    let native = [
+        UrlSource::PowerShell,
+        UrlSource::UiAutomation,
+        UrlSource::Msaa,
+        UrlSource::AppleScript,
+        UrlSource::Keyboard,
+    ]
+    .into_iter()
+    .filter_map(cost)
+    .reduce(f64::min);
+
+    native.is_some_and(|native| devtools < native)
+}
+
+/// Record the attempts of one pipeline step for `browser_type` that took
+/// `elapsed` in total
+///
+/// The step's time is charged to its last method, the one that decided the
+/// outcome; earlier failures in the step only count towards the success rate.
+pub(crate) fn record(
+    browser_type: &BrowserType,
+    attempts: &[ExtractionAttempt],
+    elapsed: Duration,
+) {
+    let ran: Vec<&ExtractionAttempt> = attempts
+        .iter()
+        .filter(|attempt| !matches!(attempt.outcome, AttemptOutcome::Skipped(_)))
+        .collect();
+    let Some((last, _)) = ran.split_last() else {
+        return;
+    };
+
+    let mut registry = registry().lock().unwrap_or_else(PoisonError::into_inner);
+    for attempt in &ran {
+        let index =
+            match registry.stats.iter().position(|stats| {
+                stats.browser_type == *browser_type && stats.source == attempt.source
+            }) {
+                Some(index) => index,
+                None => {
+                    registry
+                        .stats
+                        .push(StrategyStats::new(browser_type.clone(), attempt.source));
+                    registry.stats.len() - 1
+                }
+            };
+        let stats = &mut registry.stats[index];
+        stats.attempts += 1;
+        if attempt.outcome == AttemptOutcome::Success {
+            stats.successes += 1;
+        }
+        if std::ptr::eq(*attempt, *last) {
+            stats.total_latency += elapsed;
+        }
+    }
+
+    registry.unsaved += 1;
+    #[cfg(feature = "serde")]
+    if registry.unsaved >= SAVE_EVERY {
+        registry.unsaved = 0;
+        save(&registry.stats);
+    }
+}
+
+#[cfg(feature = "serde")]
+fn stats_path() -> Option<std::path::PathBuf> {
+    crate::config::data_dir().map(|dir| dir.join("strategy_stats.json"))
+}
+
+#[cfg(feature = "serde")]
+fn load() -> Vec<StrategyStats> {
+    // Files written before the per-browser split do not parse; learning starts over
+    stats_path()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(not(feature = "serde"))]
+fn load() -> Vec<StrategyStats> {
+    Vec::new()
+}
+
+#[cfg(feature = "serde")]
+fn save(stats: &[StrategyStats]) {
+    let Some(path) = stats_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_vec_pretty(stats)
+        && let Err(e) = std::fs::write(&path, json)
+    {
+        log::warn!("Failed to save strategy stats: {e}");
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn save(_stats: &[StrategyStats]) {}
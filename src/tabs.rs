@@ -0,0 +1,373 @@
+// ================================================================================================
+// Every open tab of every browser - 全タブの一覧
+// ================================================================================================
+
+use crate::url_extraction::{domain_matches, domain_of};
+use crate::{BrowserInfoError, BrowserType};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// One open tab, for session capture and tab switchers
+///
+/// There is no "discarded" flag: Chromium reports it only to extensions
+/// (`chrome.tabs.Tab.discarded`), neither the DevTools target list nor
+/// AppleScript carries it, and probing the page through DevTools would
+/// reload it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TabInfo {
+    pub url: String,
+    pub title: String,
+    /// Whether this is the selected tab of its window
+    pub active: bool,
+    /// Browser window holding the tab (CDP window id, or the AppleScript window id)
+    pub window_id: String,
+    /// Position in the window's tab strip, from 0; `None` through DevTools,
+    /// which does not report it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub index: Option<u32>,
+    pub browser_type: BrowserType,
+    /// Id for `control::focus_tab` / `control::close_tab`; `None` for Safari
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tab_id: Option<String>,
+}
+
+/// Which tabs [`get_tabs`] returns, and which page of them
+///
+/// Filters combine with AND. Pages are taken from the ordered list after
+/// filtering, so `offset` / `limit` stay meaningful between refreshes as
+/// long as the tabs themselves don't change. There is no "audible" filter:
+/// neither the DevTools target list nor AppleScript reports audio per tab.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TabQuery {
+    window_id: Option<String>,
+    domain: Option<String>,
+    active_only: bool,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+impl TabQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only tabs of this window ([`TabInfo::window_id`])
+    pub fn window(mut self, window_id: impl Into<String>) -> Self {
+        self.window_id = Some(window_id.into());
+        self
+    }
+
+    /// Only tabs on this domain or its subdomains
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into().trim_start_matches("www.").to_lowercase());
+        self
+    }
+
+    /// Only the selected tab of each window
+    pub fn active_only(mut self) -> Self {
+        self.active_only = true;
+        self
+    }
+
+    /// Skip this many matching tabs
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Return at most this many tabs
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether `tab` passes the filters (pagination aside)
+    pub fn matches(&self, tab: &TabInfo) -> bool {
+        if self.active_only && !tab.active {
+            return false;
+        }
+        if self
+            .window_id
+            .as_ref()
+            .is_some_and(|window_id| *window_id != tab.window_id)
+        {
+            return false;
+        }
+        match &self.domain {
+            Some(pattern) => {
+                domain_of(&tab.url).is_some_and(|domain| domain_matches(&domain, pattern))
+            }
+            None => true,
+        }
+    }
+
+    /// Filter and paginate an ordered tab list
+    pub fn apply(&self, tabs: Vec<TabInfo>) -> TabPage {
+        let matching: Vec<TabInfo> = tabs.into_iter().filter(|tab| self.matches(tab)).collect();
+        let total = matching.len();
+        let tabs = matching
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        TabPage { tabs, total }
+    }
+}
+
+/// One page of a [`TabQuery`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TabPage {
+    pub tabs: Vec<TabInfo>,
+    /// Tabs matching the filters, on all pages
+    pub total: usize,
+}
+
+/// [`get_all_tabs`], filtered and paginated by `query`
+///
+/// ```rust,no_run
+/// use browser_info::tabs::{TabQuery, get_tabs};
+///
+/// # async fn run() -> Result<(), browser_info::BrowserInfoError> {
+/// let page = get_tabs(&TabQuery::new().domain("github.com").limit(50)).await?;
+/// println!("showing {} of {} GitHub tabs", page.tabs.len(), page.total);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn get_tabs(query: &TabQuery) -> Result<TabPage, BrowserInfoError> {
+    Ok(query.apply(get_all_tabs().await?))
+}
+
+/// List the tabs of every window of every supported browser
+///
+/// Tabs are ordered by browser, then by window id, then by position in the
+/// window (by tab id through DevTools, where positions are unknown), so the
+/// order does not change when the user switches tabs.
+///
+/// On Windows the tabs come from the DevTools endpoint (needs the `devtools`
+/// feature and a Chromium-based browser started with `--remote-debugging-port`);
+/// the active tab of a window is the most recently activated one. On macOS
+/// they come from AppleScript for Safari and the Chromium-based browsers that
+/// are running. Firefox exposes its tabs to neither.
+///
+/// The [machine policy](crate::policy) applies as it does to the active tab:
+/// tabs of disabled browsers are left out and URLs are reduced to the
+/// policy's [`UrlPrivacy`](crate::UrlPrivacy).
+///
+/// ```rust,no_run
+/// # async fn run() -> Result<(), browser_info::BrowserInfoError> {
+/// for tab in browser_info::get_all_tabs().await? {
+///     let marker = if tab.active { "*" } else { " " };
+///     println!("{marker} [{}] {} {}", tab.window_id, tab.title, tab.url);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn get_all_tabs() -> Result<Vec<TabInfo>, BrowserInfoError> {
+    let config = crate::ExtractionConfig::default().effective();
+    Ok(list_tabs()
+        .await?
+        .into_iter()
+        // マシンポリシーで無効化されたブラウザは一覧にも出さない
+        .filter(|tab| config.check_browser(&tab.browser_type).is_ok())
+        .map(|tab| TabInfo {
+            url: config.url_privacy.apply(&tab.url),
+            ..tab
+        })
+        .collect())
+}
+
+/// Tabs as reported by the platform backend, before the machine policy
+async fn list_tabs() -> Result<Vec<TabInfo>, BrowserInfoError> {
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    {
+        use crate::platform::chrome_devtools::{ChromeDevToolsExtractor, DevToolsScan};
+
+        // ブラウザごとに別ポートで起動されていることがある。応答したポートだけ使う
+        let scan = DevToolsScan::new().timeout(std::time::Duration::from_secs(10));
+        let mut tabs = Vec::new();
+        let mut first_error = None;
+        let found = crate::runtime::on_tokio(async move {
+            ChromeDevToolsExtractor::all_tabs_on_ports(&scan).await
+        })
+        .await;
+        for found in found {
+            match found.result {
+                Ok(found) => tabs.push(sorted(found)),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        return match first_error {
+            Some(e) if tabs.is_empty() => Err(e),
+            _ => Ok(tabs.concat()),
+        };
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut tabs = Vec::new();
+        for browser in [
+            BrowserType::Chrome,
+            BrowserType::Edge,
+            BrowserType::Brave,
+            BrowserType::Vivaldi,
+            BrowserType::Opera,
+            BrowserType::Safari,
+        ] {
+            match crate::platform::macos::all_tabs(&browser) {
+                Ok(found) => tabs.extend(sorted(found)),
+                // 一つのブラウザの失敗で全体を失敗させない（権限不足は除く）
+                Err(BrowserInfoError::PermissionDenied) => {
+                    return Err(BrowserInfoError::PermissionDenied);
+                }
+                Err(e) => log::warn!("Could not list {browser:?} tabs: {e}"),
+            }
+        }
+        return Ok(tabs);
+    }
+
+    #[allow(unreachable_code)]
+    Err(BrowserInfoError::PlatformError(
+        "Tab enumeration needs the devtools feature on Windows, or macOS".to_string(),
+    ))
+}
+
+/// Window id (numerically when possible), then tab position, then tab id
+#[allow(dead_code)]
+fn sorted(mut tabs: Vec<TabInfo>) -> Vec<TabInfo> {
+    tabs.sort_by(|a, b| {
+        let window = |tab: &TabInfo| (tab.window_id.parse::<u64>().ok(), tab.window_id.clone());
+        window(a)
+            .cmp(&window(b))
+            .then_with(|| {
+                a.index
+                    .unwrap_or(u32::MAX)
+                    .cmp(&b.index.unwrap_or(u32::MAX))
+            })
+            .then_with(|| a.tab_id.cmp(&b.tab_id))
+    });
+    tabs
+}
+
+/// Tabs of one browser showing the same page
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DuplicateTabs {
+    /// The shared URL, normalized by [`normalize_tab_url`]
+    pub url: String,
+    /// At least two tabs, in [`get_all_tabs`] order
+    pub tabs: Vec<TabInfo>,
+}
+
+impl DuplicateTabs {
+    /// The tab to keep: a selected one if any, otherwise the first
+    pub fn keep(&self) -> &TabInfo {
+        self.tabs
+            .iter()
+            .find(|tab| tab.active)
+            .unwrap_or(&self.tabs[0])
+    }
+
+    /// Every tab except [`keep`](Self::keep), e.g. for `control::close_tab`
+    pub fn redundant(&self) -> impl Iterator<Item = &TabInfo> {
+        let keep = self.keep();
+        self.tabs
+            .iter()
+            .filter(move |tab| !std::ptr::eq(*tab, keep))
+    }
+}
+
+/// Group the open tabs by normalized URL, across windows
+///
+/// Only groups with two or more tabs are returned. Tabs of different browsers
+/// never share a group, since their ids belong to different browsers.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "control")]
+/// # async fn run() -> Result<(), browser_info::BrowserInfoError> {
+/// for group in browser_info::tabs::find_duplicate_tabs().await? {
+///     for tab in group.redundant() {
+///         if let Some(tab_id) = &tab.tab_id {
+///             browser_info::control::close_tab(tab_id).await?;
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn find_duplicate_tabs() -> Result<Vec<DuplicateTabs>, BrowserInfoError> {
+    Ok(duplicate_tabs(get_all_tabs().await?))
+}
+
+/// [`find_duplicate_tabs`] over an already fetched tab list
+pub fn duplicate_tabs(tabs: Vec<TabInfo>) -> Vec<DuplicateTabs> {
+    let mut groups: Vec<DuplicateTabs> = Vec::new();
+    let mut index: std::collections::HashMap<(String, String), usize> = Default::default();
+    for tab in tabs {
+        let url = normalize_tab_url(&tab.url);
+        // BrowserType は Hash を持たないので表示名で区別する
+        let key = (format!("{:?}", tab.browser_type), url.clone());
+        match index.get(&key) {
+            Some(&group) => groups[group].tabs.push(tab),
+            None => {
+                index.insert(key, groups.len());
+                groups.push(DuplicateTabs {
+                    url,
+                    tabs: vec![tab],
+                });
+            }
+        }
+    }
+    groups.retain(|group| group.tabs.len() > 1);
+    groups
+}
+
+/// URL form under which two tabs count as the same page
+///
+/// Scheme and host are lowercased, `www.` is dropped, and so are the
+/// fragment, a trailing `/` and tracking parameters (`utm_*`, `fbclid`,
+/// `gclid`). Other query parameters are kept in their order.
+///
+/// ```rust
+/// use browser_info::tabs::normalize_tab_url;
+///
+/// assert_eq!(
+///     normalize_tab_url("HTTPS://www.Example.com/docs/?utm_source=feed&page=2#intro"),
+///     normalize_tab_url("https://example.com/docs?page=2"),
+/// );
+/// ```
+pub fn normalize_tab_url(url: &str) -> String {
+    let url = url.trim();
+    let url = url.split_once('#').map_or(url, |(url, _)| url);
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    let query: Vec<&str> = query
+        .split('&')
+        .filter(|pair| {
+            let name = pair.split('=').next().unwrap_or_default();
+            !pair.is_empty() && !name.starts_with("utm_") && name != "fbclid" && name != "gclid"
+        })
+        .collect();
+    let mut normalized = format!(
+        "{}://{host}{}",
+        scheme.to_lowercase(),
+        path.trim_end_matches('/')
+    );
+    if !query.is_empty() {
+        normalized.push('?');
+        normalized.push_str(&query.join("&"));
+    }
+    normalized
+}
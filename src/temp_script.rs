@@ -0,0 +1,234 @@
+// ================================================================================================
+// Private temp files for generated scripts - 一時スクリプトファイル
+// ================================================================================================
+//
+// Scripts are never executed from the current working directory (anyone able
+// to write there could plant a replacement). Bundled scripts are written to a
+// per-process directory only the current user can access, and each file is
+// removed as soon as it has run.
+//
+// Script paths are absolute and canonical so that neither the host's working
+// directory nor spaces, non-ASCII or very long components break the
+// interpreter's command line.
+
+use crate::{BrowserInfoError, ExtractionConfig};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A script file that is deleted when dropped
+#[derive(Debug)]
+pub(crate) struct TempScript {
+    path: PathBuf,
+}
+
+impl TempScript {
+    /// Write `contents` to a fresh file with the given extension
+    pub(crate) fn write(
+        config: &ExtractionConfig,
+        extension: &str,
+        contents: &str,
+    ) -> Result<Self, BrowserInfoError> {
+        let dir = private_dir(config)?;
+        let path = dir.join(format!(
+            "script-{}.{extension}",
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let mut options = OpenOptions::new();
+        // create_new: never follow or reuse a file someone else placed there
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(&path).map_err(script_error)?;
+        file.write_all(contents.as_bytes()).map_err(script_error)?;
+
+        Ok(Self {
+            path: interpreter_path(path),
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempScript {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// `<script_dir or temp dir>/browser-info-<pid>`, readable by the owner only
+fn private_dir(config: &ExtractionConfig) -> Result<PathBuf, BrowserInfoError> {
+    let base = config.script_dir.clone().unwrap_or_else(std::env::temp_dir);
+    let dir = base.join(format!("browser-info-{}", std::process::id()));
+
+    match fs::symlink_metadata(&dir) {
+        Ok(metadata) if metadata.is_dir() => {}
+        Ok(_) => {
+            return Err(BrowserInfoError::PermissionDenied);
+        }
+        Err(_) => {
+            let mut builder = fs::DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::DirBuilderExt;
+                builder.mode(0o700);
+            }
+            builder.create(&dir).map_err(script_error)?;
+        }
+    }
+
+    // Reused directories may have been loosened in the meantime
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)).map_err(script_error)?;
+    }
+
+    fs::canonicalize(&dir).map_err(script_error)
+}
+
+/// Path form the script interpreters accept
+///
+/// On Windows `canonicalize` returns verbatim (`\\?\`) paths. They are only
+/// needed past `MAX_PATH` and not every tool understands them, so short paths
+/// are handed over in their plain form.
+#[cfg(target_os = "windows")]
+fn interpreter_path(path: PathBuf) -> PathBuf {
+    const MAX_PATH: usize = 260;
+
+    let text = path.to_string_lossy();
+    if text.len() >= MAX_PATH {
+        return path;
+    }
+    if let Some(unc) = text.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{unc}"));
+    }
+    match text.strip_prefix(r"\\?\") {
+        Some(plain) => PathBuf::from(plain),
+        None => path,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn interpreter_path(path: PathBuf) -> PathBuf {
+    path
+}
+
+fn script_error(e: std::io::Error) -> BrowserInfoError {
+    BrowserInfoError::PlatformError(format!("Failed to prepare script file: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Script directories that used to break the interpreter command line
+    fn awkward_bases(test: &str) -> Vec<PathBuf> {
+        let root = std::env::temp_dir().join(format!("browser-info-{test}-{}", std::process::id()));
+        vec![
+            root.join("with spaces in it"),
+            root.join("ユーザー データ ñ"),
+            root.join("a".repeat(100))
+                .join("b".repeat(100))
+                .join("c".repeat(100)),
+        ]
+    }
+
+    fn cleanup(test: &str) {
+        let root = std::env::temp_dir().join(format!("browser-info-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn private_dir_is_absolute_and_owner_only() {
+        for base in awkward_bases("private-dir") {
+            let config = ExtractionConfig::new().script_dir(&base);
+            let dir = private_dir(&config).unwrap();
+
+            assert!(dir.is_absolute(), "{}", dir.display());
+            assert!(dir.is_dir());
+            assert_eq!(
+                dir,
+                fs::canonicalize(&base)
+                    .unwrap()
+                    .join(dir.file_name().unwrap())
+            );
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                assert_eq!(
+                    fs::metadata(&dir).unwrap().permissions().mode() & 0o777,
+                    0o700
+                );
+            }
+            // 2回目は既存のディレクトリを使う
+            assert_eq!(private_dir(&config).unwrap(), dir);
+        }
+        cleanup("private-dir");
+    }
+
+    #[test]
+    fn script_is_readable_and_removed_on_drop() {
+        for base in awkward_bases("write") {
+            let config = ExtractionConfig::new().script_dir(&base);
+            let script = TempScript::write(&config, "ps1", "Write-Output 'ok'").unwrap();
+            let path = script.path().to_path_buf();
+
+            assert!(path.is_absolute(), "{}", path.display());
+            assert_eq!(fs::read_to_string(&path).unwrap(), "Write-Output 'ok'");
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                assert_eq!(
+                    fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+                    0o600
+                );
+            }
+
+            drop(script);
+            assert!(!path.exists());
+        }
+        cleanup("write");
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn interpreter_path_keeps_unix_paths() {
+        let path = PathBuf::from("/tmp/with spaces/ユーザー/script-0.sh");
+        assert_eq!(interpreter_path(path.clone()), path);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn interpreter_path_strips_verbatim_prefixes_of_short_paths() {
+        assert_eq!(
+            interpreter_path(PathBuf::from(
+                r"\\?\C:\Users\Jane Doe\AppData\Local\Temp\script-0.ps1"
+            )),
+            PathBuf::from(r"C:\Users\Jane Doe\AppData\Local\Temp\script-0.ps1")
+        );
+        assert_eq!(
+            interpreter_path(PathBuf::from(r"\\?\UNC\server\share\ユーザー\script-0.ps1")),
+            PathBuf::from(r"\\server\share\ユーザー\script-0.ps1")
+        );
+        assert_eq!(
+            interpreter_path(PathBuf::from(r"C:\Temp\script-0.ps1")),
+            PathBuf::from(r"C:\Temp\script-0.ps1")
+        );
+
+        // MAX_PATH を超える場合は \\?\ が必要なのでそのまま
+        let long = PathBuf::from(format!(r"\\?\C:\{}\script-0.ps1", "a".repeat(300)));
+        assert_eq!(interpreter_path(long.clone()), long);
+    }
+}
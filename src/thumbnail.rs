@@ -0,0 +1,297 @@
+//! Downscaled captures of browser windows for pickers and previews.
+//!
+//! [`thumbnail_stream`] captures a window on its own thread at a fixed frame
+//! rate and hands out [`Thumbnail`]s no larger than `max_size` on either
+//! side. Capture pauses while the window is minimized (there is nothing
+//! new to show) and the stream ends when the window is closed.
+//!
+//! ```rust,no_run
+//! use browser_info::get_all_browser_windows;
+//! use browser_info::thumbnail::thumbnail_stream;
+//!
+//! let window = &get_all_browser_windows()?[0];
+//! for thumbnail in thumbnail_stream(&window.window_id, 2.0, 320).take(10) {
+//!     println!("🖼️ {}x{}", thumbnail.width, thumbnail.height);
+//! }
+//! # Ok::<(), browser_info::BrowserInfoError>(())
+//! ```
+//!
+//! Platform capture APIs:
+//! - Windows: `PrintWindow`, which also renders windows hidden behind others
+//! - macOS: `CGWindowListCreateImage` (needs the Screen Recording permission);
+//!   window ids from [`get_all_browser_windows`](crate::get_all_browser_windows)
+//!   are empty on macOS, use the focused window's id instead
+//! - Linux/X11: ImageMagick `import`, which reads the screen, so covered
+//!   parts of the window show whatever is on top
+
+use crate::BrowserInfoError;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+/// One captured frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    /// `width * height` RGBA pixels, row by row from the top
+    pub rgba: Vec<u8>,
+    pub captured_at: SystemTime,
+}
+
+/// Capture `window_id` once, downscaled to at most `max_size` pixels per side
+///
+/// Returns `Ok(None)` while the window is minimized or hidden.
+pub fn capture_thumbnail(
+    window_id: &str,
+    max_size: u32,
+) -> Result<Option<Thumbnail>, BrowserInfoError> {
+    let captured_at = SystemTime::now();
+    Ok(capture_window(window_id)?.map(|(width, height, rgba)| {
+        let (width, height, rgba) = downscale(width, height, &rgba, max_size.max(1));
+        Thumbnail {
+            width,
+            height,
+            rgba,
+            captured_at,
+        }
+    }))
+}
+
+/// Frames captured on a background thread; capture stops when dropped
+///
+/// Iterating blocks until the next frame; it ends when the window is closed.
+/// When frames are not picked up in time, the new ones are dropped rather
+/// than queued.
+#[derive(Debug)]
+pub struct ThumbnailStream {
+    frames: Receiver<Thumbnail>,
+    paused: Arc<AtomicBool>,
+    stop: Option<SyncSender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Capture `window_id` `fps` times per second, at most `max_size` pixels per side
+pub fn thumbnail_stream(window_id: &str, fps: f64, max_size: u32) -> ThumbnailStream {
+    let interval = Duration::from_secs_f64(1.0 / fps.clamp(0.01, 60.0));
+    // 最小化中は確認だけなので間隔を空ける
+    let paused_interval = interval.max(Duration::from_secs(1));
+    let (frame_sender, frames) = mpsc::sync_channel(1);
+    let (stop, stopped) = mpsc::sync_channel::<()>(0);
+    let paused = Arc::new(AtomicBool::new(false));
+
+    let window_id = window_id.to_string();
+    let thread_paused = Arc::clone(&paused);
+    let thread = std::thread::Builder::new()
+        .name("browser-info-thumbnail".to_string())
+        .spawn(move || {
+            loop {
+                let wait = match capture_thumbnail(&window_id, max_size) {
+                    Ok(Some(thumbnail)) => {
+                        thread_paused.store(false, Ordering::Relaxed);
+                        // 受信側が追いつかないフレームは捨てる
+                        if let Err(mpsc::TrySendError::Disconnected(_)) =
+                            frame_sender.try_send(thumbnail)
+                        {
+                            return;
+                        }
+                        interval
+                    }
+                    Ok(None) => {
+                        if !thread_paused.swap(true, Ordering::Relaxed) {
+                            log::info!("Window is minimized; thumbnail capture paused");
+                        }
+                        paused_interval
+                    }
+                    Err(BrowserInfoError::WindowNotFound) => return,
+                    Err(e) => {
+                        log::warn!("Thumbnail capture failed: {e}");
+                        paused_interval
+                    }
+                };
+                match stopped.recv_timeout(wait) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => return,
+                }
+            }
+        })
+        .expect("failed to spawn thumbnail thread");
+
+    ThumbnailStream {
+        frames,
+        paused,
+        stop: Some(stop),
+        thread: Some(thread),
+    }
+}
+
+impl ThumbnailStream {
+    /// Wait up to `timeout` for the next frame
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Thumbnail> {
+        self.frames.recv_timeout(timeout).ok()
+    }
+
+    /// The latest frame, if one is waiting
+    pub fn try_recv(&self) -> Option<Thumbnail> {
+        self.frames.try_recv().ok()
+    }
+
+    /// Whether capture is paused because the window is minimized or hidden
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+}
+
+impl Iterator for ThumbnailStream {
+    type Item = Thumbnail;
+
+    fn next(&mut self) -> Option<Thumbnail> {
+        self.frames.recv().ok()
+    }
+}
+
+impl Drop for ThumbnailStream {
+    fn drop(&mut self) {
+        // 送信側を閉じるとキャプチャスレッドの待機が終わる
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Box-filter `rgba` down so that neither side exceeds `max_size`
+fn downscale(width: u32, height: u32, rgba: &[u8], max_size: u32) -> (u32, u32, Vec<u8>) {
+    if width <= max_size && height <= max_size {
+        return (width, height, rgba.to_vec());
+    }
+    let scale = f64::from(max_size) / f64::from(width.max(height));
+    let new_width = ((f64::from(width) * scale).round() as u32).max(1);
+    let new_height = ((f64::from(height) * scale).round() as u32).max(1);
+
+    let mut output = Vec::with_capacity(new_width as usize * new_height as usize * 4);
+    for y in 0..new_height {
+        let (y0, y1) = span(y, new_height, height);
+        for x in 0..new_width {
+            let (x0, x1) = span(x, new_width, width);
+            let mut sum = [0u64; 4];
+            for sy in y0..y1 {
+                let row = sy as usize * width as usize;
+                for sx in x0..x1 {
+                    let offset = (row + sx as usize) * 4;
+                    for (channel, total) in sum.iter_mut().enumerate() {
+                        *total += u64::from(rgba[offset + channel]);
+                    }
+                }
+            }
+            let count = u64::from((y1 - y0) * (x1 - x0));
+            output.extend(sum.iter().map(|total| (total / count) as u8));
+        }
+    }
+    (new_width, new_height, output)
+}
+
+/// Source pixels `start..end` covered by target pixel `index`
+fn span(index: u32, target: u32, source: u32) -> (u32, u32) {
+    let start = (u64::from(index) * u64::from(source) / u64::from(target)) as u32;
+    let end = (u64::from(index + 1) * u64::from(source) / u64::from(target)) as u32;
+    (start, end.max(start + 1).min(source))
+}
+
+#[cfg(target_os = "windows")]
+use crate::platform::windows::capture_window;
+
+#[cfg(target_os = "macos")]
+use crate::platform::macos::capture_window;
+
+/// ImageMagick `import` as PPM; hidden (minimized) windows are skipped
+#[cfg(target_os = "linux")]
+fn capture_window(window_id: &str) -> Result<Option<(u32, u32, Vec<u8>)>, BrowserInfoError> {
+    use crate::process_runner::ProcessRunner;
+
+    let state = ProcessRunner::new("xprop")
+        .args(["-id", window_id, "_NET_WM_STATE"])
+        .run()?;
+    if !state.success() {
+        return Err(BrowserInfoError::WindowNotFound);
+    }
+    if state.stdout.contains("_NET_WM_STATE_HIDDEN") {
+        return Ok(None);
+    }
+
+    // 画像はバイナリかつ出力上限を超えるので、標準出力ではなくファイルで受け取る
+    static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "browser-info-thumbnail-{}-{}.ppm",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let output = ProcessRunner::new("import")
+        .arg("-silent")
+        .arg("-window")
+        .arg(window_id)
+        .arg(path.as_os_str())
+        .run();
+    let data = std::fs::read(&path);
+    let _ = std::fs::remove_file(&path);
+
+    let output = output?;
+    if !output.success() {
+        return Err(BrowserInfoError::PlatformError(format!(
+            "import failed: {}",
+            output.stderr.trim()
+        )));
+    }
+    let data = data.map_err(|e| BrowserInfoError::PlatformError(e.to_string()))?;
+    parse_ppm(&data).map(Some)
+}
+
+/// Binary PPM (`P6`, maxval 255) to RGBA
+#[cfg(target_os = "linux")]
+fn parse_ppm(data: &[u8]) -> Result<(u32, u32, Vec<u8>), BrowserInfoError> {
+    let invalid = || BrowserInfoError::ParseError("unexpected PPM output".to_string());
+    // ヘッダ: "P6" 幅 高さ 最大値 の4トークン（空白区切り）
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    while tokens.len() < 4 {
+        while data.get(position).is_some_and(u8::is_ascii_whitespace) {
+            position += 1;
+        }
+        let start = position;
+        while data
+            .get(position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace())
+        {
+            position += 1;
+        }
+        if start == position {
+            return Err(invalid());
+        }
+        tokens.push(std::str::from_utf8(&data[start..position]).map_err(|_| invalid())?);
+    }
+    let number = |token: &str| token.parse::<u32>().map_err(|_| invalid());
+    let (width, height) = (number(tokens[1])?, number(tokens[2])?);
+    if tokens[0] != "P6" || number(tokens[3])? != 255 {
+        return Err(invalid());
+    }
+
+    let pixels = data.get(position + 1..).ok_or_else(invalid)?;
+    let expected = width as usize * height as usize * 3;
+    if pixels.len() < expected {
+        return Err(invalid());
+    }
+    let rgba = pixels[..expected]
+        .chunks_exact(3)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+        .collect();
+    Ok((width, height, rgba))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn capture_window(_window_id: &str) -> Result<Option<(u32, u32, Vec<u8>)>, BrowserInfoError> {
+    Err(BrowserInfoError::PlatformError(
+        "Window capture is not supported on this platform".to_string(),
+    ))
+}
@@ -1,24 +1,391 @@
-use crate::{BrowserInfoError, BrowserType};
-use active_win_pos_rs::ActiveWindow;
+use crate::WindowHandleInfo;
+use crate::{BrowserInfoError, BrowserType, ExtractionConfig};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A pluggable URL extraction backend
+///
+/// The built-in platform logic is exposed as [`NativeExtractor`]; third-party
+/// backends implement this trait so they can be driven (and verified by the
+/// `conformance` suite) the same way.
+pub trait UrlExtractor {
+    /// Short backend name used in reports
+    fn name(&self) -> &str;
+
+    /// Extract the URL currently displayed in `window`
+    fn extract_url(
+        &self,
+        window: &WindowHandleInfo,
+        browser_type: &BrowserType,
+    ) -> Result<String, BrowserInfoError>;
+}
+
+/// The platform's built-in extraction pipeline
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativeExtractor;
+
+impl UrlExtractor for NativeExtractor {
+    fn name(&self) -> &str {
+        "native"
+    }
+
+    fn extract_url(
+        &self,
+        window: &WindowHandleInfo,
+        browser_type: &BrowserType,
+    ) -> Result<String, BrowserInfoError> {
+        extract_url(window, browser_type)
+    }
+}
+
+/// Where an extracted URL came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum UrlSource {
+    /// PowerShell script (Windows)
+    PowerShell,
+    /// Address-bar value read through UI Automation, without keystrokes (Windows)
+    UiAutomation,
+    /// Address-bar value read through MSAA / IAccessible2, without keystrokes (Windows)
+    Msaa,
+    /// AppleScript / osascript (macOS)
+    AppleScript,
+    /// Simulated address-bar keyboard shortcut
+    Keyboard,
+    /// Chrome DevTools Protocol
+    DevTools,
+    /// Guessed from the window title (not an address-bar read)
+    TitleHeuristic,
+}
+
+impl UrlSource {
+    /// `true` for sources that read the real address (everything except title guessing)
+    pub fn is_accurate(&self) -> bool {
+        !matches!(self, UrlSource::TitleHeuristic)
+    }
+
+    /// Confidence of a URL taken verbatim from this source
+    pub fn confidence(&self) -> UrlConfidence {
+        if self.is_accurate() {
+            UrlConfidence::Exact
+        } else {
+            UrlConfidence::Guessed
+        }
+    }
+}
+
+/// How far an extracted URL can be trusted
+///
+/// Ordered from most to least trustworthy, so `confidence <= UrlConfidence::Derived`
+/// keeps everything that came from the browser itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum UrlConfidence {
+    /// Read from the browser, unchanged
+    Exact,
+    /// Read from the browser but not verbatim: shortened by
+    /// [`UrlPrivacy`](crate::UrlPrivacy), shown without its scheme by the
+    /// address bar (`example.com/path`), or borrowed from the source tab of a
+    /// picture-in-picture window
+    Derived,
+    /// Guessed from the window title (e.g. a "Claude" title mapped to
+    /// `https://claude.ai/chat`); may be wrong or stale
+    Guessed,
+}
+
+impl UrlConfidence {
+    /// Confidence of `url` as produced by `source` and then reduced by `privacy`;
+    /// `None` when no URL was extracted
+    pub(crate) fn of(
+        source: Option<UrlSource>,
+        url: &str,
+        privacy: crate::UrlPrivacy,
+    ) -> Option<UrlConfidence> {
+        let confidence = source?.confidence();
+        if url.is_empty() {
+            return None;
+        }
+        Some(match confidence {
+            UrlConfidence::Exact if !has_scheme(url) || privacy.apply(url) != url => {
+                UrlConfidence::Derived
+            }
+            confidence => confidence,
+        })
+    }
+}
+
+/// `true` when `url` starts with a scheme (`https:`, `about:`), not with a bare
+/// host such as `example.com/path` or `localhost:3000` from an address bar
+/// that hides it
+pub(crate) fn has_scheme(url: &str) -> bool {
+    let Some((scheme, rest)) = url.split_once(':') else {
+        return false;
+    };
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        // host:port
+        && !rest.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Source of the last successful attempt, i.e. the one that produced the URL
+pub(crate) fn successful_source(attempts: &[ExtractionAttempt]) -> Option<UrlSource> {
+    attempts
+        .iter()
+        .rev()
+        .find(|attempt| attempt.outcome == AttemptOutcome::Success)
+        .map(|attempt| attempt.source)
+}
+
+/// Result of a single extraction method
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum AttemptOutcome {
+    Success,
+    Failed(String),
+    /// The method was not run (unavailable, disabled, ...)
+    Skipped(String),
+}
+
+/// One entry of the extraction trace
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ExtractionAttempt {
+    pub source: UrlSource,
+    pub outcome: AttemptOutcome,
+}
+
+impl ExtractionAttempt {
+    pub(crate) fn record(
+        attempts: &mut Vec<ExtractionAttempt>,
+        source: UrlSource,
+        result: &Result<String, BrowserInfoError>,
+    ) {
+        let outcome = match result {
+            Ok(_) => AttemptOutcome::Success,
+            Err(e) => AttemptOutcome::Failed(e.to_string()),
+        };
+        attempts.push(ExtractionAttempt { source, outcome });
+    }
+}
 
 /// Extract URL from the active browser window
+///
+/// Only accurate platform methods are used; see
+/// [`ExtractionConfig::allow_title_heuristics`] for guessing from the title.
 pub fn extract_url(
-    window: &ActiveWindow,
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+) -> Result<String, BrowserInfoError> {
+    extract_url_traced(
+        window,
+        browser_type,
+        &ExtractionConfig::default(),
+        &mut Vec::new(),
+    )
+}
+
+/// Same as [`extract_url`] with explicit settings, recording every method tried into `attempts`
+pub fn extract_url_traced(
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+    config: &ExtractionConfig,
+    attempts: &mut Vec<ExtractionAttempt>,
+) -> Result<String, BrowserInfoError> {
+    let config = &config.effective();
+    config.check_browser(browser_type)?;
+    ensure_responding(window, browser_type, attempts)?;
+    extract_raw_url_traced(window, browser_type, config, attempts)
+        .map(|url| config.url_privacy.apply(&url))
+}
+
+/// [`extract_url_traced`] before [`UrlPrivacy`](crate::UrlPrivacy) is applied
+///
+/// [`UrlConfidence::of`] needs the URL as read from the browser, so callers
+/// building a `BrowserInfo` apply the privacy mode themselves afterwards.
+/// They have also probed the window with
+/// [`is_responding`](crate::responsiveness::is_responding) already, which is
+/// not repeated here.
+pub(crate) fn extract_raw_url_traced(
+    window: &WindowHandleInfo,
     browser_type: &BrowserType,
+    config: &ExtractionConfig,
+    attempts: &mut Vec<ExtractionAttempt>,
+) -> Result<String, BrowserInfoError> {
+    let config = &config.effective();
+    config.check_browser(browser_type)?;
+
+    match extract_raw_url_native(window, browser_type, config, attempts) {
+        Ok(url) => return Ok(url),
+        // DevTools/PiP titles are not page titles either
+        Err(e @ BrowserInfoError::ExtractionRefused(_))
+            if !crate::browser_detection::classify_window_kind(window).has_address_bar() =>
+        {
+            return Err(e);
+        }
+        // The title belongs to the dialog, not the page
+        Err(e @ BrowserInfoError::BlockedByDialog(_)) => return Err(e),
+        // Report the hang instead of hiding it behind a guessed URL
+        Err(e @ BrowserInfoError::BrowserNotResponding) => return Err(e),
+        Err(_) => {}
+    }
+
+    // `sync-only`: DevTools without an async runtime, still before any guessing
+    #[cfg(all(feature = "sync-only", target_os = "windows"))]
+    if let Some(url) =
+        crate::platform::devtools_blocking::active_page_url(window, browser_type, attempts)
+    {
+        return Ok(url);
+    }
+
+    // Embedded web views show app titles, not page titles
+    if *browser_type == BrowserType::Embedded {
+        return Err(embedded_without_address_bar());
+    }
+
+    title_fallback(window, browser_type, config, attempts)
+}
+
+/// Last step once every accurate method failed: the title guess, if enabled
+pub(crate) fn title_fallback(
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+    config: &ExtractionConfig,
+    attempts: &mut Vec<ExtractionAttempt>,
+) -> Result<String, BrowserInfoError> {
+    let result = if config.allow_title_heuristics {
+        let started = std::time::Instant::now();
+        let result = extract_url_from_title_traced(&window.title, attempts);
+        crate::strategy::record(
+            browser_type,
+            &attempts[attempts.len() - 1..],
+            started.elapsed(),
+        );
+        result
+    } else {
+        attempts.push(ExtractionAttempt {
+            source: UrlSource::TitleHeuristic,
+            outcome: AttemptOutcome::Skipped("title heuristics disabled".to_string()),
+        });
+        Err(BrowserInfoError::UrlExtractionFailed(
+            "No accurate method found the URL (title guessing is disabled)".to_string(),
+        ))
+    };
+    if config.passive && result.is_err() {
+        // キー入力・クリップボードを使う手段しか残っていない
+        return Err(BrowserInfoError::ExtractionRefused(
+            "passive mode: no method without keystrokes or clipboard found the URL".to_string(),
+        ));
+    }
+    result
+}
+
+fn embedded_without_address_bar() -> BrowserInfoError {
+    BrowserInfoError::UrlExtractionFailed(
+        "Embedded WebView2 has no address bar; enable the host's DevTools port".to_string(),
+    )
+}
+
+/// Run only the accurate platform-specific methods (no title guessing)
+pub fn extract_url_native(
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+    config: &ExtractionConfig,
+    attempts: &mut Vec<ExtractionAttempt>,
+) -> Result<String, BrowserInfoError> {
+    let config = &config.effective();
+    config.check_browser(browser_type)?;
+    ensure_responding(window, browser_type, attempts)?;
+    extract_raw_url_native(window, browser_type, config, attempts)
+        .map(|url| config.url_privacy.apply(&url))
+}
+
+/// Fail fast on a hung browser; every method would block on it until its timeout
+///
+/// Probed once per extraction, by the public entry points. WebView2 hosts are
+/// left to the native chain, which refuses them without touching the window.
+fn ensure_responding(
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+    attempts: &mut Vec<ExtractionAttempt>,
+) -> Result<(), BrowserInfoError> {
+    if *browser_type != BrowserType::Embedded && !crate::responsiveness::is_responding(window) {
+        attempts.push(skipped_not_responding());
+        return Err(BrowserInfoError::BrowserNotResponding);
+    }
+    Ok(())
+}
+
+/// [`extract_url_native`] before [`UrlPrivacy`](crate::UrlPrivacy) is applied,
+/// for callers that already probed the window (see [`extract_raw_url_traced`])
+pub(crate) fn extract_raw_url_native(
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+    config: &ExtractionConfig,
+    attempts: &mut Vec<ExtractionAttempt>,
+) -> Result<String, BrowserInfoError> {
+    let config = &config.effective();
+    config.check_browser(browser_type)?;
+
+    // Address-bar automation is meaningless inside a WebView2 host
+    if *browser_type == BrowserType::Embedded {
+        attempts.push(ExtractionAttempt {
+            source: UrlSource::Keyboard,
+            outcome: AttemptOutcome::Skipped("embedded web view".to_string()),
+        });
+        return Err(embedded_without_address_bar());
+    }
+
+    // Keystrokes sent to DevTools or an extension popup land in the wrong place
+    let window_kind = crate::browser_detection::classify_window_kind(window);
+    if !window_kind.has_address_bar() {
+        attempts.push(ExtractionAttempt {
+            source: UrlSource::Keyboard,
+            outcome: AttemptOutcome::Skipped(format!("{window_kind:?} window")),
+        });
+        return Err(BrowserInfoError::ExtractionRefused(format!(
+            "{window_kind:?} window has no address bar"
+        )));
+    }
+
+    let first = attempts.len();
+    let started = std::time::Instant::now();
+    let result = extract_url_platform(window, browser_type, config, attempts);
+    crate::strategy::record(browser_type, &attempts[first..], started.elapsed());
+    result
+}
+
+pub(crate) fn skipped_not_responding() -> ExtractionAttempt {
+    ExtractionAttempt {
+        source: UrlSource::Keyboard,
+        outcome: AttemptOutcome::Skipped("browser is not responding".to_string()),
+    }
+}
+
+fn extract_url_platform(
+    window: &WindowHandleInfo,
+    browser_type: &BrowserType,
+    config: &ExtractionConfig,
+    attempts: &mut Vec<ExtractionAttempt>,
 ) -> Result<String, BrowserInfoError> {
     #[cfg(target_os = "windows")]
     {
-        crate::platform::windows::extract_url(window, browser_type)
+        crate::platform::windows::extract_url(window, browser_type, config, attempts)
     }
 
     #[cfg(target_os = "macos")]
     {
-        crate::platform::macos::extract_url(window, browser_type)
+        crate::platform::macos::extract_url(window, browser_type, config, attempts)
     }
 
     #[cfg(target_os = "linux")]
     {
-        let _ = (window, browser_type); // Suppress unused variable warnings
+        let _ = (window, browser_type, config, attempts); // Suppress unused variable warnings
         // TODO: Implement Linux URL extraction
         Err(BrowserInfoError::PlatformError(
             "Linux not yet implemented".to_string(),
@@ -27,9 +394,159 @@ pub fn extract_url(
 
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
-        let _ = (window, browser_type); // Suppress unused variable warnings
+        let _ = (window, browser_type, config, attempts); // Suppress unused variable warnings
         Err(BrowserInfoError::PlatformError(
             "Unsupported platform".to_string(),
         ))
     }
 }
+
+/// Title heuristic step, recorded into `attempts`
+pub fn extract_url_from_title_traced(
+    title: &str,
+    attempts: &mut Vec<ExtractionAttempt>,
+) -> Result<String, BrowserInfoError> {
+    let result = extract_url_from_title(title);
+    ExtractionAttempt::record(attempts, UrlSource::TitleHeuristic, &result);
+    result
+}
+
+/// Host of `url` for grouping: lowercase, without userinfo, port or leading `www.`
+///
+/// ```rust
+/// use browser_info::url_extraction::domain_of;
+///
+/// assert_eq!(domain_of("https://www.YouTube.com:443/watch?v=1").as_deref(), Some("youtube.com"));
+/// assert_eq!(domain_of("about:blank"), None);
+/// // Address bars that hide the scheme
+/// assert_eq!(domain_of("example.com/path").as_deref(), Some("example.com"));
+/// ```
+pub fn domain_of(url: &str) -> Option<String> {
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest,
+        None if !has_scheme(url) => url,
+        None => return None,
+    };
+    let authority = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let host = if host.starts_with('[') {
+        // IPv6 literal such as [::1]:8080
+        host.split_inclusive(']').next().unwrap_or(host)
+    } else {
+        host.split(':').next().unwrap_or(host)
+    };
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// Whether `domain` (as returned by [`domain_of`]) is `pattern` or one of its subdomains
+///
+/// ```rust
+/// use browser_info::url_extraction::domain_matches;
+///
+/// assert!(domain_matches("gist.github.com", "github.com"));
+/// assert!(!domain_matches("notgithub.com", "github.com"));
+/// ```
+pub fn domain_matches(domain: &str, pattern: &str) -> bool {
+    domain == pattern
+        || domain
+            .strip_suffix(pattern)
+            .is_some_and(|sub| sub.ends_with('.'))
+}
+
+/// タイトルからのURL推測（最終フォールバック）
+///
+/// This is a guess based on well-known site names, never an address-bar read.
+pub fn extract_url_from_title(title: &str) -> Result<String, BrowserInfoError> {
+    log::debug!("Final fallback: extracting URL from title: {title}");
+
+    let title_lower = title.to_lowercase();
+
+    if title_lower.contains("claude") {
+        Ok("https://claude.ai/chat".to_string())
+    } else if title_lower.contains("github") {
+        Ok("https://github.com".to_string())
+    } else if title_lower.contains("google") {
+        Ok("https://www.google.com".to_string())
+    } else if title_lower.contains("youtube") {
+        Ok("https://www.youtube.com".to_string())
+    } else if title_lower.contains("stackoverflow") {
+        Ok("https://stackoverflow.com".to_string())
+    } else if title_lower.contains("twitter") || title_lower.contains("x.com") {
+        Ok("https://x.com".to_string())
+    } else if title_lower.contains("reddit") {
+        Ok("https://www.reddit.com".to_string())
+    } else {
+        Err(BrowserInfoError::UrlExtractionFailed(format!(
+            "Cannot determine URL from title: {title}",
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UrlPrivacy;
+
+    const URL: &str = "https://github.com/frkavka/browser-info?tab=readme#usage";
+
+    #[test]
+    fn exact_becomes_derived_only_when_privacy_changes_the_url() {
+        let of = |url, privacy| UrlConfidence::of(Some(UrlSource::DevTools), url, privacy);
+
+        assert_eq!(of(URL, UrlPrivacy::Full), Some(UrlConfidence::Exact));
+        assert_eq!(of(URL, UrlPrivacy::Redacted), Some(UrlConfidence::Derived));
+        assert_eq!(
+            of(URL, UrlPrivacy::DomainOnly),
+            Some(UrlConfidence::Derived)
+        );
+
+        // Nothing to strip: the reduced URL is still the one the browser showed
+        assert_eq!(
+            of("https://github.com/frkavka", UrlPrivacy::Redacted),
+            Some(UrlConfidence::Exact)
+        );
+        assert_eq!(
+            of("https://github.com", UrlPrivacy::DomainOnly),
+            Some(UrlConfidence::Exact)
+        );
+    }
+
+    #[test]
+    fn guesses_stay_guessed_and_empty_urls_have_no_confidence() {
+        for privacy in [
+            UrlPrivacy::Full,
+            UrlPrivacy::Redacted,
+            UrlPrivacy::DomainOnly,
+        ] {
+            assert_eq!(
+                UrlConfidence::of(Some(UrlSource::TitleHeuristic), URL, privacy),
+                Some(UrlConfidence::Guessed)
+            );
+            assert_eq!(
+                UrlConfidence::of(Some(UrlSource::DevTools), "", privacy),
+                None
+            );
+            assert_eq!(UrlConfidence::of(None, URL, privacy), None);
+        }
+    }
+
+    #[test]
+    fn schemeless_address_bar_values_are_derived() {
+        assert!(has_scheme(URL));
+        assert!(has_scheme("about:blank"));
+        assert!(!has_scheme("example.com/path"));
+        assert!(!has_scheme("localhost:3000/api"));
+
+        assert_eq!(
+            UrlConfidence::of(
+                Some(UrlSource::UiAutomation),
+                "example.com/path",
+                UrlPrivacy::Full
+            ),
+            Some(UrlConfidence::Derived)
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Ground-truth accuracy check for URL extraction on the user's machine.
+//!
+//! [`verify_extraction`] serves a tiny test page from `127.0.0.1`, opens it in
+//! the default browser, waits until that page is the active window and then
+//! runs each extraction method on it, comparing the result with the URL it
+//! actually served. Useful for "does it work here?" bug reports and for CI
+//! runners with a desktop session.
+//!
+//! ```rust,no_run
+//! use browser_info::{ExtractionConfig, verify::verify_extraction};
+//!
+//! let report = verify_extraction(&ExtractionConfig::default()).unwrap();
+//! for method in &report.methods {
+//!     println!("{:?}: exact={} ({:?})", method.source, method.exact, method.extracted);
+//! }
+//! ```
+
+use crate::url_extraction::{self, AttemptOutcome, ExtractionAttempt, UrlSource};
+use crate::{BrowserInfoError, BrowserType, ExtractionConfig, browser_detection};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long to wait for the test page to become the active window
+const PAGE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Result of one extraction method against the test page
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodResult {
+    pub source: UrlSource,
+    /// What the method returned, if it succeeded
+    pub extracted: Option<String>,
+    /// Final outcome as recorded in the attempt trace
+    pub outcome: AttemptOutcome,
+    /// `extracted` equals the served URL exactly
+    pub exact: bool,
+    pub elapsed: Duration,
+}
+
+/// Outcome of [`verify_extraction`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationReport {
+    /// URL of the test page that was opened
+    pub expected_url: String,
+    pub browser_type: BrowserType,
+    pub methods: Vec<MethodResult>,
+}
+
+impl VerificationReport {
+    /// At least one accurate method returned the exact URL
+    pub fn passed(&self) -> bool {
+        self.methods
+            .iter()
+            .any(|method| method.exact && method.source.is_accurate())
+    }
+}
+
+/// Open a local test page in the default browser and check every method against it
+///
+/// The native pipeline (with `config`'s restrictions) and the title heuristic
+/// are run separately so each gets its own verdict. Keyboard-based methods will
+/// really press keys in the test tab. Fails with [`BrowserInfoError::Timeout`]
+/// when the page does not come to the front within 15 seconds.
+pub fn verify_extraction(
+    config: &ExtractionConfig,
+) -> Result<VerificationReport, BrowserInfoError> {
+    let token = format!(
+        "{:x}{:x}",
+        std::process::id(),
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos()
+    );
+    let server = TestPageServer::start(&token)?;
+    let expected_url = format!(
+        "http://127.0.0.1:{}/browser-info-self-test?token={token}",
+        server.port
+    );
+
+    open_in_default_browser(&expected_url)?;
+    log::info!("Waiting for the self-test page: {expected_url}");
+
+    let deadline = Instant::now() + PAGE_TIMEOUT;
+    let window = loop {
+        if let Ok(window) = crate::window_provider::active_window_for(config)
+            && window.title.contains(&token)
+        {
+            break window;
+        }
+        if Instant::now() > deadline {
+            return Err(BrowserInfoError::Timeout);
+        }
+        thread::sleep(Duration::from_millis(200));
+    };
+    let browser_type = browser_detection::classify_browser(&window)?;
+
+    let mut methods = Vec::new();
+
+    // Native pipeline: the last successful attempt produced the URL
+    let start = Instant::now();
+    let mut attempts = Vec::new();
+    let result = url_extraction::extract_url_native(&window, &browser_type, config, &mut attempts);
+    let elapsed = start.elapsed();
+    methods.extend(
+        attempts
+            .into_iter()
+            .map(|ExtractionAttempt { source, outcome }| {
+                let extracted = (outcome == AttemptOutcome::Success)
+                    .then(|| result.as_ref().ok().cloned())
+                    .flatten();
+                MethodResult {
+                    source,
+                    exact: extracted.as_deref() == Some(expected_url.as_str()),
+                    extracted,
+                    outcome,
+                    elapsed,
+                }
+            }),
+    );
+
+    // Title heuristic (the test page title carries no URL, so this should fail)
+    let start = Instant::now();
+    let mut attempts = Vec::new();
+    let result = url_extraction::extract_url_from_title_traced(&window.title, &mut attempts);
+    methods.push(MethodResult {
+        source: UrlSource::TitleHeuristic,
+        exact: result.as_deref().ok() == Some(expected_url.as_str()),
+        extracted: result.ok(),
+        outcome: attempts
+            .pop()
+            .map(|attempt| attempt.outcome)
+            .unwrap_or(AttemptOutcome::Failed("not attempted".to_string())),
+        elapsed: start.elapsed(),
+    });
+
+    Ok(VerificationReport {
+        expected_url,
+        browser_type,
+        methods,
+    })
+}
+
+fn open_in_default_browser(url: &str) -> Result<(), BrowserInfoError> {
+    #[cfg(target_os = "windows")]
+    let status = Command::new("cmd").args(["/C", "start", "", url]).status();
+    #[cfg(target_os = "macos")]
+    let status = Command::new("open").arg(url).status();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let status = Command::new("xdg-open").arg(url).status();
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(BrowserInfoError::PlatformError(format!(
+            "Opening the default browser failed: {status}"
+        ))),
+        Err(e) => Err(BrowserInfoError::PlatformError(format!(
+            "Opening the default browser failed: {e}"
+        ))),
+    }
+}
+
+/// Minimal HTTP server answering every request with the test page
+struct TestPageServer {
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+impl TestPageServer {
+    fn start(token: &str) -> Result<Self, BrowserInfoError> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+            .map_err(|e| BrowserInfoError::PlatformError(format!("Self-test server: {e}")))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| BrowserInfoError::PlatformError(format!("Self-test server: {e}")))?
+            .port();
+
+        let body = format!(
+            "<!doctype html><title>browser-info self-test {token}</title>\
+             <p>browser-info is checking URL extraction. You can close this tab.</p>"
+        );
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    thread::sleep(Duration::from_millis(50));
+                    continue;
+                };
+                let _ = stream.set_nonblocking(false);
+                let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+                let mut request = [0u8; 2048];
+                let _ = stream.read(&mut request);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+
+        Ok(Self { port, stop })
+    }
+}
+
+impl Drop for TestPageServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
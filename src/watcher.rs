@@ -0,0 +1,1115 @@
+//! Background polling of the active browser.
+//!
+//! A [`BrowserWatcher`] samples the active browser on its own thread and
+//! reports changes as [`WatchEvent`]s. Monitoring can be suspended at any time
+//! with [`pause`](BrowserWatcher::pause) or [`snooze`](BrowserWatcher::snooze);
+//! while suspended, no extraction runs at all — not even the keyboard or
+//! script based fallbacks.
+//!
+//! ```rust,no_run
+//! use browser_info::watcher::{BrowserWatcher, WatchEventKind};
+//! use std::time::Duration;
+//!
+//! let watcher = BrowserWatcher::spawn(Duration::from_secs(1), |event| match event.kind {
+//!     WatchEventKind::Changed(info) => println!("🌐 {}", info.url),
+//!     WatchEventKind::Paused { .. } => println!("⏸️ Tracking paused"),
+//!     WatchEventKind::Resumed => println!("▶️ Tracking resumed"),
+//!     _ => {}
+//! });
+//!
+//! // "Don't track me for the next 15 minutes"
+//! watcher.snooze(Duration::from_secs(15 * 60));
+//! ```
+//!
+//! Besides the callback, any number of consumers can [`subscribe`](BrowserWatcher::subscribe).
+//! Each subscriber gets its own bounded queue; a slow subscriber loses its own
+//! oldest events and never holds up the watcher or the other subscribers.
+//!
+//! ```rust,no_run
+//! use browser_info::watcher::BrowserWatcher;
+//! use std::time::Duration;
+//!
+//! let watcher = BrowserWatcher::spawn(Duration::from_secs(1), |_| {});
+//! let gui = watcher.subscribe();
+//! let storage = watcher.subscribe_with_capacity(4096);
+//!
+//! std::thread::spawn(move || {
+//!     for event in storage {
+//!         println!("💾 {:?}", event.kind);
+//!     }
+//! });
+//!
+//! while let Some(event) = gui.recv() {
+//!     println!("🖥️ {:?}", event.kind);
+//! }
+//! ```
+
+use crate::url_extraction::domain_of;
+use crate::{
+    BrowserInfo, BrowserInfoError, BrowserType, ExtractionConfig, WindowHandleInfo,
+    browser_detection,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Title of the focused browser window, as reported by [`watch_titles`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TitleChange {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_time::rfc3339"))]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub timestamp: SystemTime,
+    pub title: String,
+    pub browser_name: String,
+    pub browser_type: BrowserType,
+    pub process_id: u64,
+    /// Platform window id; distinguishes two windows of the same browser
+    pub window_id: String,
+}
+
+/// Blocking iterator over browser title changes; see [`watch_titles`]
+#[derive(Debug)]
+pub struct TitleChanges {
+    interval: Duration,
+    config: ExtractionConfig,
+    last: Option<(String, String)>,
+    first: bool,
+}
+
+impl Iterator for TitleChanges {
+    type Item = TitleChange;
+
+    /// Blocks until the focused browser window or its title changes
+    fn next(&mut self) -> Option<TitleChange> {
+        loop {
+            if !self.first {
+                std::thread::sleep(self.interval);
+            }
+            self.first = false;
+
+            let Ok(window) = crate::window_provider::active_window_for(&self.config) else {
+                continue;
+            };
+            let Ok(browser_type) =
+                browser_detection::classify_browser_with_config(&window, &self.config)
+            else {
+                continue;
+            };
+
+            let key = (window.window_id.clone(), window.title.clone());
+            if self.last.as_ref() == Some(&key) {
+                continue;
+            }
+            self.last = Some(key);
+
+            return Some(TitleChange {
+                timestamp: SystemTime::now(),
+                title: window.title,
+                browser_name: window.app_name,
+                browser_type,
+                process_id: window.process_id,
+                window_id: window.window_id,
+            });
+        }
+    }
+}
+
+/// Cheap stream of browser title changes
+///
+/// Only polls the window system: no scripts, no keystrokes, no DevTools. That
+/// makes intervals of 100 ms or less affordable, and a title change is a good
+/// signal that running the full URL extraction is worthwhile. Windows that are
+/// not browsers are ignored.
+///
+/// ```rust,no_run
+/// use browser_info::watcher::watch_titles;
+/// use std::time::Duration;
+///
+/// for change in watch_titles(Duration::from_millis(100)) {
+///     println!("📝 [{:?}] {}", change.browser_type, change.title);
+/// }
+/// ```
+pub fn watch_titles(interval: Duration) -> TitleChanges {
+    watch_titles_with_config(interval, ExtractionConfig::default())
+}
+
+/// [`watch_titles`] with the window provider and disabled browsers of `config`
+pub fn watch_titles_with_config(interval: Duration, config: ExtractionConfig) -> TitleChanges {
+    TitleChanges {
+        interval,
+        config: config.effective(),
+        last: None,
+        first: true,
+    }
+}
+
+/// Something the watcher noticed
+///
+/// Serialized as `{"timestamp": "<RFC3339>", "kind": {"type": "...", "data": ...}}`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WatchEvent {
+    /// When the event was produced
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_time::rfc3339"))]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub timestamp: SystemTime,
+    pub kind: WatchEventKind,
+    /// For [`Changed`](WatchEventKind::Changed): the URL focused before this
+    /// one, across focus changes and browsers (`None` for the first page)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub previous_url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "data", rename_all = "snake_case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+// Changed が大半のイベントなので Box しない
+#[allow(clippy::large_enum_variant)]
+pub enum WatchEventKind {
+    /// A browser gained focus, or its URL, title or browser changed
+    Changed(BrowserInfo),
+    /// No browser window is focused any more
+    FocusLost,
+    /// Sampling failed while a browser was focused
+    Error(String),
+    /// Monitoring was suspended; `until` is set for a snooze
+    Paused {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_time::rfc3339_option"))]
+        #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+        until: Option<SystemTime>,
+    },
+    /// Monitoring continues (explicitly or because a snooze ran out)
+    Resumed,
+    /// The session was locked; nothing is extracted until it is unlocked.
+    /// Consumers should end the current visit here
+    SessionLocked,
+    /// The session was unlocked; the focused page is reported afresh
+    SessionUnlocked,
+    /// The machine went to sleep. Noticed only after waking up, but stamped
+    /// with the start of the wait it slept through: the polling interval, or
+    /// up to 30 s for an idle OS-hook watcher, before the real time.
+    /// Consumers should end the current visit here
+    SystemSleep,
+    /// The machine woke up; DevTools connections are re-established and the
+    /// focused page is reported afresh
+    SystemWake,
+}
+
+impl WatchEvent {
+    fn now(kind: WatchEventKind, previous_url: Option<String>) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            kind,
+            previous_url,
+        }
+    }
+}
+
+/// When the watcher runs the (expensive) URL extraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtractionTrigger {
+    /// Only when the focused window or its title changed since the last
+    /// extraction (the window itself is still polled every tick)
+    #[default]
+    TitleChange,
+    /// On every tick, for pages that change their URL but not their title
+    EveryTick,
+}
+
+/// Settings for [`BrowserWatcher::spawn_with_options`]
+///
+/// ```rust
+/// use browser_info::watcher::{ExtractionTrigger, WatchOptions};
+/// use std::time::Duration;
+///
+/// let options = WatchOptions::new()
+///     .interval(Duration::from_millis(500))
+///     .trigger(ExtractionTrigger::TitleChange)
+///     // Slack keeps its title while switching channels
+///     .always_extract_on("app.slack.com");
+/// ```
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    pub(crate) interval: Duration,
+    pub(crate) config: ExtractionConfig,
+    pub(crate) trigger: ExtractionTrigger,
+    pub(crate) always_extract_domains: Vec<String>,
+    pub(crate) navigation_hooks: bool,
+    pub(crate) os_hooks: bool,
+    pub(crate) suppressed_apps: Vec<String>,
+    pub(crate) pause_while_locked: bool,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            config: ExtractionConfig::default(),
+            trigger: ExtractionTrigger::default(),
+            always_extract_domains: Vec::new(),
+            navigation_hooks: true,
+            os_hooks: true,
+            suppressed_apps: Vec::new(),
+            pause_while_locked: true,
+        }
+    }
+}
+
+impl WatchOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Preset for always-on background agents on battery-powered machines
+    ///
+    /// - OS window hooks instead of polling (see [`os_hooks`](Self::os_hooks));
+    ///   the 5 s interval only applies where titles still have to be polled
+    /// - no keyboard simulation, so nothing flashes in the user's browser
+    /// - no DevTools navigation listener (it keeps a socket and a runtime busy)
+    ///
+    /// Pair it with `storage::BatchedStore` (`storage` feature) so visits are
+    /// not written to disk one by one. Per tick, an idle low-power watcher
+    /// reads the foreground window once; URL extraction only runs after an OS
+    /// event.
+    pub fn low_power() -> Self {
+        Self::default()
+            .interval(Duration::from_secs(5))
+            .config(ExtractionConfig::default().allow_keyboard_simulation(false))
+            .navigation_hooks(false)
+            .os_hooks(true)
+    }
+
+    /// Polling interval (1 s by default)
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Extraction settings used for every sample
+    pub fn config(mut self, config: ExtractionConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// When to re-run URL extraction (on title change by default)
+    pub fn trigger(mut self, trigger: ExtractionTrigger) -> Self {
+        self.trigger = trigger;
+        self
+    }
+
+    /// Extract on every tick while a page of `domain` is focused
+    ///
+    /// For single-page apps whose URL changes without a title change. The
+    /// domain is compared as returned by [`domain_of`].
+    pub fn always_extract_on(mut self, domain: impl Into<String>) -> Self {
+        self.always_extract_domains
+            .push(domain.into().to_lowercase());
+        self
+    }
+
+    /// Listen for client-side navigations over DevTools (enabled by default)
+    ///
+    /// When a DevTools port is reachable (Windows with the `devtools`
+    /// feature), `Page.navigatedWithinDocument` events trigger an immediate
+    /// extraction, so History API route changes are caught even though the
+    /// title stays the same. Without DevTools this has no effect.
+    pub fn navigation_hooks(mut self, enabled: bool) -> Self {
+        self.navigation_hooks = enabled;
+        self
+    }
+
+    /// React to OS window events instead of waiting for the next tick (enabled by default)
+    ///
+    /// On Windows a `SetWinEventHook` for foreground and title changes drives
+    /// the watcher: while nothing happens it only wakes every 30 seconds as a
+    /// safety net, so idle CPU usage drops to almost nothing. On macOS
+    /// `NSWorkspace` app-activation notifications wake it early, but titles
+    /// are still polled every interval; the notifications are only delivered
+    /// while the host application runs its main run loop.
+    pub fn os_hooks(mut self, enabled: bool) -> Self {
+        self.os_hooks = enabled;
+        self
+    }
+
+    /// Back off while `app` is in the foreground (full-screen games, presentations)
+    ///
+    /// Matched case-insensitively against the application name and the
+    /// executable name without extension, e.g. `"PowerPoint"` or `"eldenring"`.
+    /// While such an app is focused nothing is extracted, a single `FocusLost`
+    /// is reported, and the polling interval doubles on every tick up to 30
+    /// seconds. Polling returns to normal as soon as another app is focused;
+    /// with [`os_hooks`](Self::os_hooks) that is noticed right away, without
+    /// them on the next (backed-off) tick.
+    ///
+    /// ```rust
+    /// use browser_info::watcher::WatchOptions;
+    ///
+    /// let options = WatchOptions::new()
+    ///     .suppress_while("PowerPoint")
+    ///     .suppress_while("eldenring");
+    /// ```
+    pub fn suppress_while(mut self, app: impl Into<String>) -> Self {
+        self.suppressed_apps.push(app.into().to_lowercase());
+        self
+    }
+
+    /// Stop extracting while the session is locked (enabled by default)
+    ///
+    /// The lock state ([`is_session_locked`](crate::lock_screen::is_session_locked))
+    /// is checked on every tick; a lock is reported as
+    /// [`WatchEventKind::SessionLocked`] and the unlock as
+    /// [`WatchEventKind::SessionUnlocked`], so locked time is not attributed
+    /// to the last page. An idle OS-hook watcher wakes every 5 s instead of
+    /// every 30 s to notice the lock; disable this for the fewest wake-ups.
+    pub fn pause_while_locked(mut self, enabled: bool) -> Self {
+        self.pause_while_locked = enabled;
+        self
+    }
+
+    fn is_suppressed(&self, window: &WindowHandleInfo) -> bool {
+        if self.suppressed_apps.is_empty() {
+            return false;
+        }
+        let app_name = window.app_name.to_lowercase();
+        let executable = window
+            .process_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase());
+        self.suppressed_apps
+            .iter()
+            .any(|app| *app == app_name || executable.as_deref() == Some(app.as_str()))
+    }
+
+    /// Whether the page reported last needs extraction even with an unchanged title
+    fn always_extracts(&self, last: &WatchEventKind) -> bool {
+        match last {
+            WatchEventKind::Changed(info) => domain_of(&info.url)
+                .is_some_and(|domain| self.always_extract_domains.contains(&domain)),
+            WatchEventKind::FocusLost => false,
+            // Retry failures every tick
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Suspension {
+    Running,
+    Paused,
+    Snoozed(Instant),
+}
+
+#[derive(Debug)]
+struct State {
+    suspension: Suspension,
+    /// Set by the DevTools hook when the page navigated without reloading
+    navigated: bool,
+    /// Set by the OS hook when the focus moved or the focused window was retitled
+    window_changed: bool,
+    /// Bumped on every pause/snooze/resume so the thread reports each one
+    generation: u64,
+    /// Bumped on every wake-up from sleep so the DevTools hook reconnects
+    wakes: u64,
+    stop: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+    subscribers: Mutex<Vec<Arc<Queue>>>,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // A panicking callback must not take pause/resume down with it
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn update(&self, suspension: Suspension) {
+        let mut state = self.lock();
+        state.suspension = suspension;
+        state.generation += 1;
+        self.wake.notify_all();
+    }
+
+    fn broadcast(&self, event: &WatchEvent) {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // The receiving end holds the only other reference; drop queues nobody reads
+        subscribers.retain(|queue| Arc::strong_count(queue) > 1);
+        for queue in subscribers.iter() {
+            queue.push(event.clone());
+        }
+    }
+
+    fn close_subscribers(&self) {
+        let subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for queue in subscribers.iter() {
+            queue.close();
+        }
+    }
+}
+
+/// Per-subscriber bounded queue that drops its oldest event when full
+#[derive(Debug)]
+struct Queue {
+    inner: Mutex<QueueState>,
+    ready: Condvar,
+    capacity: usize,
+    dropped: AtomicU64,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    events: VecDeque<WatchEvent>,
+    closed: bool,
+}
+
+impl Queue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(QueueState {
+                events: VecDeque::with_capacity(capacity.min(1024)),
+                closed: false,
+            }),
+            ready: Condvar::new(),
+            capacity: capacity.max(1),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, event: WatchEvent) {
+        let mut inner = self.lock();
+        if inner.events.len() >= self.capacity {
+            inner.events.pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        inner.events.push_back(event);
+        self.ready.notify_one();
+    }
+
+    fn close(&self) {
+        self.lock().closed = true;
+        self.ready.notify_all();
+    }
+}
+
+/// Receiving end of [`BrowserWatcher::subscribe`]
+///
+/// Iterating blocks for the next event and ends once the watcher is dropped
+/// and the queue has been drained.
+#[derive(Debug)]
+pub struct EventReceiver {
+    queue: Arc<Queue>,
+}
+
+impl EventReceiver {
+    /// Block until an event arrives; `None` once the watcher is gone
+    pub fn recv(&self) -> Option<WatchEvent> {
+        let mut inner = self.queue.lock();
+        loop {
+            if let Some(event) = inner.events.pop_front() {
+                return Some(event);
+            }
+            if inner.closed {
+                return None;
+            }
+            inner = self
+                .queue
+                .ready
+                .wait(inner)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// [`recv`](Self::recv), giving up after `timeout`
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<WatchEvent> {
+        let deadline = Instant::now() + timeout;
+        let mut inner = self.queue.lock();
+        loop {
+            if let Some(event) = inner.events.pop_front() {
+                return Some(event);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if inner.closed || remaining.is_zero() {
+                return None;
+            }
+            inner = self
+                .queue
+                .ready
+                .wait_timeout(inner, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Next queued event without blocking
+    pub fn try_recv(&self) -> Option<WatchEvent> {
+        self.queue.lock().events.pop_front()
+    }
+
+    /// Events discarded so far because this subscriber fell behind
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Iterator for EventReceiver {
+    type Item = WatchEvent;
+
+    fn next(&mut self) -> Option<WatchEvent> {
+        self.recv()
+    }
+}
+
+/// Polls the active browser on a background thread
+///
+/// The thread stops when the watcher is dropped.
+#[derive(Debug)]
+pub struct BrowserWatcher {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+    navigation_hook: Option<JoinHandle<()>>,
+    window_hook: Option<WindowHook>,
+}
+
+impl BrowserWatcher {
+    /// Queue size used by [`subscribe`](Self::subscribe)
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// Watch with the default [`ExtractionConfig`]
+    pub fn spawn<F>(interval: Duration, callback: F) -> Self
+    where
+        F: FnMut(WatchEvent) + Send + 'static,
+    {
+        Self::spawn_with_options(WatchOptions::new().interval(interval), callback)
+    }
+
+    pub fn spawn_with_config<F>(interval: Duration, config: ExtractionConfig, callback: F) -> Self
+    where
+        F: FnMut(WatchEvent) + Send + 'static,
+    {
+        Self::spawn_with_options(
+            WatchOptions::new().interval(interval).config(config),
+            callback,
+        )
+    }
+
+    /// Watch with full control over interval, config and extraction trigger
+    pub fn spawn_with_options<F>(options: WatchOptions, callback: F) -> Self
+    where
+        F: FnMut(WatchEvent) + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                suspension: Suspension::Running,
+                navigated: false,
+                window_changed: false,
+                generation: 0,
+                wakes: 0,
+                stop: false,
+            }),
+            wake: Condvar::new(),
+            subscribers: Mutex::new(Vec::new()),
+        });
+
+        let navigation_hook = if options.navigation_hooks {
+            spawn_navigation_hook(Arc::clone(&shared))
+        } else {
+            None
+        };
+
+        let window_hook = if options.os_hooks {
+            spawn_window_hook(Arc::clone(&shared))
+        } else {
+            None
+        };
+        let event_driven = window_hook.as_ref().is_some_and(|hook| hook.covers_titles);
+
+        let thread_shared = Arc::clone(&shared);
+        let thread = std::thread::Builder::new()
+            .name("browser-info-watcher".to_string())
+            .spawn(move || run(&thread_shared, &options, event_driven, callback))
+            .expect("failed to spawn watcher thread");
+
+        Self {
+            shared,
+            thread: Some(thread),
+            navigation_hook,
+            window_hook,
+        }
+    }
+
+    /// Independent event queue holding up to 256 events
+    ///
+    /// Subscribers only see events produced after they subscribed.
+    pub fn subscribe(&self) -> EventReceiver {
+        self.subscribe_with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    /// Independent event queue keeping at most `capacity` unread events
+    ///
+    /// When the queue is full the oldest event is discarded; see
+    /// [`EventReceiver::dropped`].
+    pub fn subscribe_with_capacity(&self, capacity: usize) -> EventReceiver {
+        let queue = Arc::new(Queue::new(capacity));
+        self.shared
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::clone(&queue));
+        EventReceiver { queue }
+    }
+
+    /// Suspend monitoring until [`resume`](Self::resume) is called
+    pub fn pause(&self) {
+        self.shared.update(Suspension::Paused);
+    }
+
+    /// Continue monitoring after a pause or snooze
+    pub fn resume(&self) {
+        self.shared.update(Suspension::Running);
+    }
+
+    /// Suspend monitoring for `duration`, then resume automatically
+    pub fn snooze(&self, duration: Duration) {
+        self.shared
+            .update(Suspension::Snoozed(Instant::now() + duration));
+    }
+
+    /// Whether monitoring is currently suspended
+    pub fn is_paused(&self) -> bool {
+        match self.shared.lock().suspension {
+            Suspension::Running => false,
+            Suspension::Paused => true,
+            Suspension::Snoozed(until) => Instant::now() < until,
+        }
+    }
+}
+
+impl Drop for BrowserWatcher {
+    fn drop(&mut self) {
+        {
+            let mut state = self.shared.lock();
+            state.stop = true;
+            self.shared.wake.notify_all();
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        if let Some(hook) = self.navigation_hook.take() {
+            let _ = hook.join();
+        }
+        if let Some(thread) = self.window_hook.take().and_then(|hook| hook.thread) {
+            let _ = thread.join();
+        }
+        self.shared.close_subscribers();
+    }
+}
+
+/// How long an event-driven watcher sleeps when no OS event arrives
+const IDLE_HEARTBEAT: Duration = Duration::from_secs(30);
+
+/// Longest polling interval while a suppressed app is in the foreground
+const MAX_SUPPRESSED_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How often an idle event-driven watcher checks the lock state
+const LOCK_CHECK: Duration = Duration::from_secs(5);
+
+/// A wait overrunning its timeout by this much means the machine was asleep
+const SUSPEND_SLACK: Duration = Duration::from_secs(15);
+
+/// `event_driven`: an OS hook reports focus and title changes, so an idle
+/// watcher does not need to poll
+fn run<F>(shared: &Shared, options: &WatchOptions, event_driven: bool, mut callback: F)
+where
+    F: FnMut(WatchEvent),
+{
+    // Navigation graph: the last two distinct URLs reported
+    let mut current_url: Option<String> = None;
+    let mut previous_url: Option<String> = None;
+
+    let mut emit_at = |kind: WatchEventKind, timestamp: SystemTime| {
+        let previous = match &kind {
+            WatchEventKind::Changed(info) => {
+                if current_url.as_deref() != Some(info.url.as_str()) {
+                    previous_url = current_url.replace(info.url.clone());
+                }
+                previous_url.clone()
+            }
+            _ => None,
+        };
+        let event = WatchEvent {
+            timestamp,
+            ..WatchEvent::now(kind, previous)
+        };
+        shared.broadcast(&event);
+        callback(event);
+    };
+
+    let mut seen_generation = 0;
+    let mut paused = false;
+    let mut last: Option<WatchEventKind> = None;
+    let mut last_window: Option<(String, String)> = None;
+    // Consecutive ticks spent behind a suppressed app
+    let mut suppressed_ticks: u32 = 0;
+    let mut session_locked = false;
+
+    let mut state = shared.lock();
+    loop {
+        if state.stop {
+            return;
+        }
+
+        // A snooze that ran out turns into a regular resume
+        if let Suspension::Snoozed(until) = state.suspension
+            && Instant::now() >= until
+        {
+            state.suspension = Suspension::Running;
+            state.generation += 1;
+        }
+
+        if state.generation != seen_generation {
+            seen_generation = state.generation;
+            let kind = match state.suspension {
+                Suspension::Running if !paused => None,
+                Suspension::Running => Some(WatchEventKind::Resumed),
+                Suspension::Paused => Some(WatchEventKind::Paused { until: None }),
+                Suspension::Snoozed(until) => Some(WatchEventKind::Paused {
+                    until: Some(
+                        SystemTime::now() + until.saturating_duration_since(Instant::now()),
+                    ),
+                }),
+            };
+            paused = state.suspension != Suspension::Running;
+
+            if let Some(kind) = kind {
+                drop(state);
+                // Consumers close their sessions on pause; report the page afresh afterwards
+                last = None;
+                last_window = None;
+                emit_at(kind, SystemTime::now());
+                state = shared.lock();
+                continue;
+            }
+        }
+
+        match state.suspension {
+            Suspension::Paused => {
+                state = shared
+                    .wake
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner);
+                continue;
+            }
+            Suspension::Snoozed(until) => {
+                let timeout = until.saturating_duration_since(Instant::now());
+                state = shared
+                    .wake
+                    .wait_timeout(state, timeout)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0;
+                continue;
+            }
+            Suspension::Running => {}
+        }
+
+        let generation = state.generation;
+        let navigated = std::mem::take(&mut state.navigated);
+        state.window_changed = false;
+        drop(state);
+
+        // ロック中は抽出せず、ロックと解除だけを伝える
+        let locked =
+            options.pause_while_locked && crate::lock_screen::is_session_locked() == Some(true);
+        if locked != session_locked {
+            session_locked = locked;
+            last = None;
+            last_window = None;
+            let kind = if locked {
+                WatchEventKind::SessionLocked
+            } else {
+                WatchEventKind::SessionUnlocked
+            };
+            emit_at(kind, SystemTime::now());
+            state = shared.lock();
+            continue;
+        }
+        if locked {
+            state = shared.lock();
+            if state.generation == generation && !state.stop {
+                let timeout = options.interval.max(LOCK_CHECK);
+                let waiting_since = SystemTime::now();
+                state = shared
+                    .wake
+                    .wait_timeout(state, timeout)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0;
+                if overslept(waiting_since, timeout) {
+                    drop(state);
+                    emit_at(WatchEventKind::SystemSleep, waiting_since);
+                    woke_up(shared);
+                    emit_at(WatchEventKind::SystemWake, SystemTime::now());
+                    state = shared.lock();
+                }
+            }
+            continue;
+        }
+
+        // Polling the window is cheap; extraction only runs when it may find something new
+        let active = crate::window_provider::active_window_for(&options.config).ok();
+        let suppressed = active
+            .as_ref()
+            .is_some_and(|window| options.is_suppressed(window));
+        let window = active.map(|window| (window.window_id, window.title));
+        let unchanged = !navigated
+            && options.trigger == ExtractionTrigger::TitleChange
+            && window.is_some()
+            && window == last_window
+            && last
+                .as_ref()
+                .is_some_and(|last| !options.always_extracts(last));
+        let sample = if suppressed {
+            // ゲーム・プレゼン中はブラウザを離れた扱いにして抽出しない
+            Some(WatchEventKind::FocusLost)
+        } else {
+            (!unchanged).then(|| sample(&options.config))
+        };
+        last_window = window;
+
+        state = shared.lock();
+
+        // Paused while extracting: the sample must not leak out
+        if state.generation != generation || state.stop {
+            continue;
+        }
+
+        if let Some(sample) = sample
+            && !last.as_ref().is_some_and(|last| is_same(last, &sample))
+        {
+            last = Some(sample.clone());
+            drop(state);
+            emit_at(sample, SystemTime::now());
+            state = shared.lock();
+        }
+
+        // Nothing can change unnoticed while the OS hook watches the window
+        let idle = event_driven
+            && options.trigger == ExtractionTrigger::TitleChange
+            && last
+                .as_ref()
+                .is_some_and(|last| !options.always_extracts(last));
+        let timeout = if suppressed {
+            suppressed_ticks = suppressed_ticks.saturating_add(1);
+            options
+                .interval
+                .saturating_mul(1 << suppressed_ticks.min(8))
+                .min(MAX_SUPPRESSED_BACKOFF)
+        } else if idle {
+            suppressed_ticks = 0;
+            if options.pause_while_locked {
+                LOCK_CHECK
+            } else {
+                IDLE_HEARTBEAT
+            }
+        } else {
+            suppressed_ticks = 0;
+            options.interval
+        };
+
+        if state.generation == generation && !state.stop && !state.window_changed {
+            let waiting_since = SystemTime::now();
+            state = shared
+                .wake
+                .wait_timeout(state, timeout)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+
+            // The machine slept: end the reported page when it went down, not at wake-up
+            if overslept(waiting_since, timeout) {
+                drop(state);
+                last = None;
+                last_window = None;
+                emit_at(WatchEventKind::SystemSleep, waiting_since);
+                woke_up(shared);
+                emit_at(WatchEventKind::SystemWake, SystemTime::now());
+                state = shared.lock();
+            }
+        }
+    }
+}
+
+/// A wait that started at `waiting_since` overran `timeout` because the machine slept
+///
+/// The wall clock keeps running during sleep on every platform, unlike
+/// `Instant`, so this needs no power notifications (and no message window or
+/// run loop to receive them).
+fn overslept(waiting_since: SystemTime, timeout: Duration) -> bool {
+    SystemTime::now()
+        .duration_since(waiting_since)
+        .is_ok_and(|waited| waited > timeout + SUSPEND_SLACK)
+}
+
+/// Drop connections that died during sleep instead of timing out on them
+fn woke_up(shared: &Shared) {
+    log::info!("System woke up; reconnecting");
+    #[cfg(all(feature = "devtools", target_os = "windows"))]
+    crate::reset_devtools_connections();
+    shared.lock().wakes += 1;
+    shared.wake.notify_all();
+}
+
+/// Background listener turning DevTools navigation events into extraction triggers
+#[cfg(all(feature = "devtools", target_os = "windows"))]
+fn spawn_navigation_hook(shared: Arc<Shared>) -> Option<JoinHandle<()>> {
+    use crate::platform::chrome_devtools::ChromeDevToolsExtractor;
+
+    // The first page target follows the active tab; re-resolve it regularly
+    const SESSION: Duration = Duration::from_secs(5);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .ok()?;
+
+    std::thread::Builder::new()
+        .name("browser-info-navigation-hook".to_string())
+        .spawn(move || {
+            while !shared.lock().stop {
+                let started = Instant::now();
+                // 復帰後は死んだソケットを待たずにつなぎ直す
+                let wakes = shared.lock().wakes;
+                let result = runtime.block_on(ChromeDevToolsExtractor::watch_navigations_on_port(
+                    ChromeDevToolsExtractor::DEFAULT_PORT,
+                    || {
+                        let state = shared.lock();
+                        !state.stop && state.wakes == wakes && started.elapsed() < SESSION
+                    },
+                    |_url| {
+                        shared.lock().navigated = true;
+                        shared.wake.notify_all();
+                    },
+                ));
+
+                // No DevTools port: try again later, but leave promptly on stop
+                if result.is_err() {
+                    let state = shared.lock();
+                    if !state.stop {
+                        let _ = shared.wake.wait_timeout(state, SESSION);
+                    }
+                }
+            }
+        })
+        .ok()
+}
+
+#[cfg(not(all(feature = "devtools", target_os = "windows")))]
+fn spawn_navigation_hook(_shared: Arc<Shared>) -> Option<JoinHandle<()>> {
+    None
+}
+
+/// OS notifications waking the watcher as soon as the focus moves
+#[derive(Debug)]
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+struct WindowHook {
+    /// Thread pumping the hook's messages (Windows)
+    thread: Option<JoinHandle<()>>,
+    /// Keeps the notification observer registered (macOS)
+    #[cfg(target_os = "macos")]
+    _observer: crate::platform::macos::ActivationObserver,
+    /// Title changes of the focused window are reported as well
+    covers_titles: bool,
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn on_window_event(shared: &Shared) {
+    shared.lock().window_changed = true;
+    shared.wake.notify_all();
+}
+
+#[cfg(target_os = "windows")]
+fn spawn_window_hook(shared: Arc<Shared>) -> Option<WindowHook> {
+    let (installed, result) = std::sync::mpsc::channel();
+    let thread = std::thread::Builder::new()
+        .name("browser-info-window-hook".to_string())
+        .spawn(move || {
+            let hook_shared = Arc::clone(&shared);
+            crate::platform::windows::watch_foreground(
+                || !shared.lock().stop,
+                move || on_window_event(&hook_shared),
+                |ok| {
+                    let _ = installed.send(ok);
+                },
+            );
+        })
+        .ok()?;
+
+    if result.recv().unwrap_or(false) {
+        Some(WindowHook {
+            thread: Some(thread),
+            covers_titles: true,
+        })
+    } else {
+        let _ = thread.join();
+        None
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn spawn_window_hook(shared: Arc<Shared>) -> Option<WindowHook> {
+    let observer = crate::platform::macos::observe_app_activation(move || on_window_event(&shared));
+    Some(WindowHook {
+        thread: None,
+        _observer: observer,
+        covers_titles: false,
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn spawn_window_hook(_shared: Arc<Shared>) -> Option<WindowHook> {
+    None
+}
+
+fn sample(config: &ExtractionConfig) -> WatchEventKind {
+    match crate::get_active_browser_info_with_config(config) {
+        Ok(info) => WatchEventKind::Changed(info),
+        Err(BrowserInfoError::NotABrowser | BrowserInfoError::WindowNotFound) => {
+            WatchEventKind::FocusLost
+        }
+        Err(e) => WatchEventKind::Error(e.to_string()),
+    }
+}
+
+/// Moving or resizing the window is not a change worth reporting
+fn is_same(a: &WatchEventKind, b: &WatchEventKind) -> bool {
+    match (a, b) {
+        (WatchEventKind::Changed(a), WatchEventKind::Changed(b)) => {
+            a.url == b.url
+                && a.title == b.title
+                && a.browser_type == b.browser_type
+                && a.process_id == b.process_id
+        }
+        _ => a == b,
+    }
+}
@@ -0,0 +1,193 @@
+// ================================================================================================
+// Window kind detection - ウィンドウ種別の判定
+// ================================================================================================
+//
+// Titles alone cannot tell a picture-in-picture window or an extension bubble
+// from a page, so the classification also looks at what the window manager
+// knows about the window. Each platform reports that differently; `imp`
+// normalizes it into `WindowTraits` and everything else stays platform-neutral.
+
+use crate::BrowserWindowKind;
+use crate::WindowHandleInfo;
+
+/// Window-manager properties of a window, normalized across platforms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowTraits {
+    /// Kept above other windows: `WS_EX_TOPMOST` on Windows, the
+    /// `AXFloatingWindow` subrole on macOS, `_NET_WM_STATE_ABOVE` or a utility
+    /// window on Linux
+    pub floating: bool,
+    /// Transient helper window without a taskbar entry: `WS_EX_TOOLWINDOW` on
+    /// Windows, popup-menu/dropdown window types on Linux
+    pub tool: bool,
+    /// Dialog window: class `#32770` on Windows, `AXDialog`/`AXSystemDialog`
+    /// on macOS, `_NET_WM_WINDOW_TYPE_DIALOG` on Linux
+    pub dialog: bool,
+}
+
+/// Query the window manager for the traits of `window`
+///
+/// Properties that cannot be read (no window handle, `xprop` or System Events
+/// unavailable, ...) are reported as `false`.
+pub fn window_traits(window: &WindowHandleInfo) -> WindowTraits {
+    imp::window_traits(window)
+}
+
+/// Classify the kind of browser window (regular, picture-in-picture, DevTools, extension popup)
+pub fn classify_window_kind(window: &WindowHandleInfo) -> BrowserWindowKind {
+    // タイトルで決まる場合はウィンドウマネージャに問い合わせない (macOS では osascript を起動する)
+    if is_devtools_title(&window.title) {
+        return BrowserWindowKind::DevTools;
+    }
+    if is_extension_title(&window.title) {
+        return BrowserWindowKind::ExtensionPopup;
+    }
+    if is_picture_in_picture_title(&window.title) {
+        return BrowserWindowKind::PictureInPicture;
+    }
+
+    let traits = window_traits(window);
+    classify(&window.title, &traits)
+}
+
+/// Classification once the title checks found nothing
+fn classify(title: &str, traits: &WindowTraits) -> BrowserWindowKind {
+    if traits.tool && !traits.dialog {
+        // Chromium のポップアップバブルはツールウィンドウ
+        BrowserWindowKind::ExtensionPopup
+    } else if title.trim().is_empty() && traits.floating {
+        // 無題の最前面ウィンドウは PiP とみなす
+        BrowserWindowKind::PictureInPicture
+    } else {
+        BrowserWindowKind::Normal
+    }
+}
+
+fn is_devtools_title(title: &str) -> bool {
+    // Chrome/Edge: "DevTools - example.com/", Firefox: "Developer Tools — ..." / "Browser Toolbox"
+    let title = title.trim_start().to_lowercase();
+    title.starts_with("devtools - ")
+        || title == "devtools"
+        || title.starts_with("developer tools")
+        || title.starts_with("browser toolbox")
+}
+
+fn is_extension_title(title: &str) -> bool {
+    let title = title.trim_start().to_lowercase();
+    ["chrome-extension://", "moz-extension://", "extension:"]
+        .iter()
+        .any(|prefix| title.starts_with(prefix))
+}
+
+fn is_picture_in_picture_title(title: &str) -> bool {
+    // Chrome/Edge: "Picture in picture", Firefox: "Picture-in-Picture"
+    let title = title.trim().to_lowercase();
+    [
+        "picture in picture",
+        "picture-in-picture",
+        "ピクチャー イン ピクチャー",
+    ]
+    .contains(&title.as_str())
+}
+
+// ================================================================================================
+// Windows: extended window styles and window class
+// ================================================================================================
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use super::WindowTraits;
+    use crate::WindowHandleInfo;
+    use crate::platform::windows;
+
+    pub(super) fn window_traits(window: &WindowHandleInfo) -> WindowTraits {
+        use winapi::um::winuser::{WS_EX_TOOLWINDOW, WS_EX_TOPMOST};
+
+        let style = windows::window_ex_style(window).unwrap_or(0);
+        WindowTraits {
+            floating: style & WS_EX_TOPMOST != 0,
+            tool: style & WS_EX_TOOLWINDOW != 0,
+            // "#32770" はコモンダイアログ（ファイル選択・印刷・メッセージボックス）のクラス
+            dialog: windows::window_class(window).as_deref() == Some("#32770"),
+        }
+    }
+}
+
+// ================================================================================================
+// macOS: accessibility subrole of the front window
+// ================================================================================================
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use super::WindowTraits;
+    use crate::WindowHandleInfo;
+
+    pub(super) fn window_traits(_window: &WindowHandleInfo) -> WindowTraits {
+        // active-win-pos-rs は常に最前面ウィンドウを返すので、その subrole を見る
+        let subrole = crate::platform::macos::front_window_subrole();
+        let subrole = subrole.as_deref().unwrap_or_default();
+        WindowTraits {
+            floating: subrole == "AXFloatingWindow",
+            tool: false,
+            dialog: matches!(subrole, "AXDialog" | "AXSystemDialog"),
+        }
+    }
+}
+
+// ================================================================================================
+// Linux: EWMH window type and state (X11 / XWayland)
+// ================================================================================================
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod imp {
+    use super::WindowTraits;
+    use crate::WindowHandleInfo;
+    use crate::process_runner::ProcessRunner;
+
+    pub(super) fn window_traits(window: &WindowHandleInfo) -> WindowTraits {
+        // window_id は X11 のリソース ID (10 進数)
+        if window.window_id.parse::<u64>().is_err() {
+            return WindowTraits::default();
+        }
+        let Ok(output) = ProcessRunner::new("xprop")
+            .args(["-id", &window.window_id])
+            .args(["_NET_WM_WINDOW_TYPE", "_NET_WM_STATE"])
+            .run()
+        else {
+            return WindowTraits::default();
+        };
+        if !output.success() {
+            return WindowTraits::default();
+        }
+        parse_xprop(&output.stdout)
+    }
+
+    /// `_NET_WM_WINDOW_TYPE(ATOM) = _NET_WM_WINDOW_TYPE_DIALOG` style lines
+    fn parse_xprop(stdout: &str) -> WindowTraits {
+        let atoms = |property: &str| -> Vec<String> {
+            stdout
+                .lines()
+                .filter_map(|line| line.strip_prefix(property))
+                .filter(|rest| rest.starts_with('('))
+                .filter_map(|rest| rest.split_once(" = "))
+                .flat_map(|(_, values)| values.split(", ").map(|atom| atom.trim().to_string()))
+                .collect()
+        };
+        let types = atoms("_NET_WM_WINDOW_TYPE");
+        let states = atoms("_NET_WM_STATE");
+        let has_type = |suffix: &str| {
+            types
+                .iter()
+                .any(|atom| atom == &format!("_NET_WM_WINDOW_TYPE_{suffix}"))
+        };
+
+        WindowTraits {
+            floating: has_type("UTILITY")
+                || states.iter().any(|atom| atom == "_NET_WM_STATE_ABOVE"),
+            tool: ["POPUP_MENU", "DROPDOWN_MENU", "TOOLTIP"]
+                .iter()
+                .any(|suffix| has_type(suffix)),
+            dialog: has_type("DIALOG"),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+// ================================================================================================
+// Every open browser window - 全ブラウザウィンドウの一覧
+// ================================================================================================
+
+use crate::browser_detection::{classify_browser, classify_window_kind};
+use crate::window_provider::{active_window, all_windows};
+use crate::{
+    BrowserInfoError, BrowserType, BrowserWindowKind, ExtractionConfig, WindowHandleInfo,
+    WindowPosition,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// One open browser window, focused or not
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BrowserWindowInfo {
+    pub browser_type: BrowserType,
+    pub browser_name: String,
+    pub process_id: u64,
+    /// Window title (usually the title of the window's active tab)
+    pub title: String,
+    /// Platform window id, as in [`WindowHandleInfo::window_id`]; empty on macOS
+    pub window_id: String,
+    pub position: WindowPosition,
+    pub window_kind: BrowserWindowKind,
+    /// Whether this is the window with the keyboard focus
+    pub is_focused: bool,
+}
+
+/// List every visible browser window, whether or not a browser has the focus
+///
+/// Windows come from the installed [`WindowProvider`](crate::window_provider::WindowProvider):
+/// `EnumWindows` on Windows, System Events on macOS (needs the Accessibility
+/// permission) and `_NET_CLIENT_LIST` through `xprop` on Linux/X11. Nothing is
+/// extracted from the windows, so this is cheap enough for a dashboard refresh;
+/// WebView2 hosts are left out as they are applications, not browsers, and so
+/// are the browsers [`ExtractionConfig::check_browser`] refuses under the
+/// [machine policy](crate::policy) (including Tor Browser).
+///
+/// ```rust,no_run
+/// use browser_info::get_all_browser_windows;
+///
+/// for window in get_all_browser_windows()? {
+///     let marker = if window.is_focused { "👉" } else { "  " };
+///     println!("{marker} {:?} [{}] {}", window.browser_type, window.process_id, window.title);
+/// }
+/// # Ok::<(), browser_info::BrowserInfoError>(())
+/// ```
+pub fn get_all_browser_windows() -> Result<Vec<BrowserWindowInfo>, BrowserInfoError> {
+    let config = ExtractionConfig::default().effective();
+    let focused = active_window().ok();
+    Ok(all_windows()?
+        .into_iter()
+        .filter_map(|window| {
+            let browser_type = classify_browser(&window).ok()?;
+            (browser_type != BrowserType::Embedded && config.check_browser(&browser_type).is_ok())
+                .then(|| browser_window(window, browser_type, focused.as_ref()))
+        })
+        .collect())
+}
+
+fn browser_window(
+    window: WindowHandleInfo,
+    browser_type: BrowserType,
+    focused: Option<&WindowHandleInfo>,
+) -> BrowserWindowInfo {
+    let is_focused = focused.is_some_and(|focused| is_same_window(focused, &window));
+    BrowserWindowInfo {
+        window_kind: classify_window_kind(&window),
+        browser_type,
+        browser_name: window.app_name,
+        process_id: window.process_id,
+        title: window.title,
+        window_id: window.window_id,
+        position: window.position,
+        is_focused,
+    }
+}
+
+/// Window ids are not always available (macOS lists windows without them)
+fn is_same_window(a: &WindowHandleInfo, b: &WindowHandleInfo) -> bool {
+    if !a.window_id.is_empty() && !b.window_id.is_empty() {
+        return a.window_id == b.window_id;
+    }
+    a.process_id == b.process_id && a.title == b.title
+}
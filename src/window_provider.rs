@@ -0,0 +1,382 @@
+//! Where the focused window comes from.
+//!
+//! By default every lookup goes through active-win-pos-rs. Some environments
+//! confuse it (tiling or minimal Linux window managers, VM guests whose focus
+//! lives on the host, remote desktops), so the provider can be swapped for the
+//! whole process with [`set_window_provider`]. Classification, extraction and
+//! the watcher keep working unchanged on whatever the provider returns.
+//!
+//! ```rust,no_run
+//! use browser_info::window_provider::{WindowProvider, set_window_provider};
+//! use browser_info::{BrowserInfoError, WindowHandleInfo};
+//!
+//! /// Focused window as reported by the host over some RPC
+//! struct RemoteProvider;
+//!
+//! impl WindowProvider for RemoteProvider {
+//!     fn active_window(&self) -> Result<WindowHandleInfo, BrowserInfoError> {
+//!         Err(BrowserInfoError::WindowNotFound)
+//!     }
+//! }
+//!
+//! set_window_provider(RemoteProvider);
+//! ```
+//!
+//! To swap it for a single call instead, hand the provider to
+//! [`ExtractionConfig::window_provider`](crate::ExtractionConfig::window_provider).
+//! [`FixedWindow`] always reports the same window, which makes the lookup
+//! deterministic in tests and lets embedders read a window they picked
+//! themselves:
+//!
+//! ```rust
+//! use browser_info::window_provider::FixedWindow;
+//! use browser_info::{BrowserInfoError, ExtractionConfig, WindowHandleInfo};
+//!
+//! let editor = WindowHandleInfo {
+//!     title: "notes.txt - gedit".to_string(),
+//!     app_name: "gedit".to_string(),
+//!     process_path: "/usr/bin/gedit".into(),
+//!     ..WindowHandleInfo::default()
+//! };
+//! let config = ExtractionConfig::new().window_provider(FixedWindow::new(editor));
+//! assert!(matches!(
+//!     browser_info::get_active_browser_info_with_config(&config),
+//!     Err(BrowserInfoError::NotABrowser)
+//! ));
+//! ```
+
+use crate::{BrowserInfoError, WindowHandleInfo};
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Source of the focused window
+pub trait WindowProvider: Send + Sync {
+    /// The window that currently has the keyboard focus
+    fn active_window(&self) -> Result<WindowHandleInfo, BrowserInfoError>;
+
+    /// Every visible top-level window, in no particular order
+    ///
+    /// Defaults to the platform's window list; override it for providers whose
+    /// windows live elsewhere (a VM host, a remote desktop).
+    fn windows(&self) -> Result<Vec<WindowHandleInfo>, BrowserInfoError> {
+        platform_windows()
+    }
+}
+
+/// The default provider, backed by active-win-pos-rs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ActiveWinProvider;
+
+impl WindowProvider for ActiveWinProvider {
+    fn active_window(&self) -> Result<WindowHandleInfo, BrowserInfoError> {
+        active_win_pos_rs::get_active_window()
+            .map(WindowHandleInfo::from)
+            .map_err(|_| BrowserInfoError::WindowNotFound)
+    }
+}
+
+/// Always reports the same window
+///
+/// [`windows`](WindowProvider::windows) lists only that window too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedWindow(pub WindowHandleInfo);
+
+impl FixedWindow {
+    pub fn new(window: WindowHandleInfo) -> Self {
+        Self(window)
+    }
+
+    /// The visible window whose [`window_id`](WindowHandleInfo::window_id) is
+    /// `window_id` (`HWND(<handle>)` on Windows), as it is right now
+    ///
+    /// The title is not updated afterwards; look the window up again when it
+    /// may have navigated.
+    pub fn find(window_id: &str) -> Result<Self, BrowserInfoError> {
+        all_windows()?
+            .into_iter()
+            .find(|window| window.window_id == window_id)
+            .map(Self)
+            .ok_or(BrowserInfoError::WindowNotFound)
+    }
+}
+
+impl WindowProvider for FixedWindow {
+    fn active_window(&self) -> Result<WindowHandleInfo, BrowserInfoError> {
+        Ok(self.0.clone())
+    }
+
+    fn windows(&self) -> Result<Vec<WindowHandleInfo>, BrowserInfoError> {
+        Ok(vec![self.0.clone()])
+    }
+}
+
+/// Provider carried by an [`ExtractionConfig`](crate::ExtractionConfig)
+#[derive(Clone)]
+pub(crate) struct InjectedProvider(pub(crate) Arc<dyn WindowProvider>);
+
+impl std::fmt::Debug for InjectedProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("InjectedProvider(..)")
+    }
+}
+
+static PROVIDER: RwLock<Option<Arc<dyn WindowProvider>>> = RwLock::new(None);
+
+/// Use `provider` for every window lookup of this process
+pub fn set_window_provider(provider: impl WindowProvider + 'static) {
+    *PROVIDER.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(provider));
+}
+
+/// Go back to [`ActiveWinProvider`]
+pub fn reset_window_provider() {
+    *PROVIDER.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Focused window from the installed provider
+pub(crate) fn active_window() -> Result<WindowHandleInfo, BrowserInfoError> {
+    let provider = PROVIDER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    match provider {
+        Some(provider) => provider.active_window(),
+        None => ActiveWinProvider.active_window(),
+    }
+}
+
+/// Focused window from the config's provider, or the installed one
+pub(crate) fn active_window_for(
+    config: &crate::ExtractionConfig,
+) -> Result<WindowHandleInfo, BrowserInfoError> {
+    match &config.window_provider {
+        Some(InjectedProvider(provider)) => provider.active_window(),
+        None => active_window(),
+    }
+}
+
+/// Every visible window from the installed provider
+pub(crate) fn all_windows() -> Result<Vec<WindowHandleInfo>, BrowserInfoError> {
+    let provider = PROVIDER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    match provider {
+        Some(provider) => provider.windows(),
+        None => ActiveWinProvider.windows(),
+    }
+}
+
+/// Visible top-level windows as listed by the OS
+fn platform_windows() -> Result<Vec<WindowHandleInfo>, BrowserInfoError> {
+    #[cfg(target_os = "windows")]
+    return Ok(crate::platform::windows::top_level_windows());
+
+    #[cfg(target_os = "macos")]
+    return crate::platform::macos::visible_windows();
+
+    #[cfg(target_os = "linux")]
+    return x11_client_windows();
+
+    #[allow(unreachable_code)]
+    Err(BrowserInfoError::PlatformError(
+        "Window enumeration is not supported on this platform".to_string(),
+    ))
+}
+
+// ================================================================================================
+// X11: EWMH properties through xprop / xwininfo
+// ================================================================================================
+
+/// Reads `_NET_ACTIVE_WINDOW` with `xprop` and `xwininfo`
+///
+/// For X11 window managers where active-win-pos-rs reports the wrong window or
+/// none at all. Needs the `x11-utils` tools on `PATH`; the process path comes
+/// from `/proc/<pid>/exe`.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XpropProvider;
+
+#[cfg(target_os = "linux")]
+impl WindowProvider for XpropProvider {
+    fn active_window(&self) -> Result<WindowHandleInfo, BrowserInfoError> {
+        // "_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007"
+        let active = xprop(&["-root", "_NET_ACTIVE_WINDOW"])?;
+        let id = active
+            .rsplit(' ')
+            .next()
+            .and_then(parse_window_id)
+            .ok_or(BrowserInfoError::WindowNotFound)?;
+        x11_window(id.to_string())
+    }
+}
+
+/// Top-level windows managed by the window manager (`_NET_CLIENT_LIST`)
+#[cfg(target_os = "linux")]
+fn x11_client_windows() -> Result<Vec<WindowHandleInfo>, BrowserInfoError> {
+    // "_NET_CLIENT_LIST(WINDOW): window id # 0x1e00003, 0x3a00007"
+    let list = xprop(&["-root", "_NET_CLIENT_LIST"])?;
+    let ids = list
+        .split_once('#')
+        .map(|(_, ids)| ids.split(',').filter_map(parse_window_id).collect())
+        .unwrap_or_else(Vec::new);
+    // 一覧取得と問い合わせの間に閉じられたウィンドウは飛ばす
+    Ok(ids
+        .into_iter()
+        .filter_map(|id| x11_window(id.to_string()).ok())
+        .collect())
+}
+
+#[cfg(target_os = "linux")]
+fn parse_window_id(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex.trim().trim_start_matches("0x"), 16)
+        .ok()
+        .filter(|id| *id != 0)
+}
+
+#[cfg(target_os = "linux")]
+fn x11_window(window_id: String) -> Result<WindowHandleInfo, BrowserInfoError> {
+    use std::path::PathBuf;
+
+    let properties = xprop(&["-id", &window_id, "_NET_WM_PID", "_NET_WM_NAME", "WM_CLASS"])?;
+    let value = |property: &str| {
+        properties
+            .lines()
+            .find(|line| line.starts_with(property))
+            .and_then(|line| line.split_once(" = "))
+            .map(|(_, value)| value.trim().to_string())
+    };
+    let process_id = value("_NET_WM_PID")
+        .and_then(|pid| pid.parse().ok())
+        .unwrap_or(0);
+    let title = value("_NET_WM_NAME")
+        .map(|name| name.trim_matches('"').to_string())
+        .unwrap_or_default();
+    // WM_CLASS = "instance", "Class"
+    let app_name = value("WM_CLASS")
+        .and_then(|class| {
+            class
+                .rsplit(", ")
+                .next()
+                .map(|c| c.trim_matches('"').to_string())
+        })
+        .unwrap_or_default();
+    let process_path =
+        std::fs::read_link(format!("/proc/{process_id}/exe")).unwrap_or_else(|_| PathBuf::new());
+
+    Ok(WindowHandleInfo {
+        title,
+        app_name,
+        process_path,
+        process_id,
+        position: geometry(&window_id).unwrap_or_default(),
+        window_id,
+    })
+}
+
+#[cfg(target_os = "linux")]
+use crate::process_runner::ProcessRunner;
+
+#[cfg(target_os = "linux")]
+fn xprop(args: &[&str]) -> Result<String, BrowserInfoError> {
+    let output = ProcessRunner::new("xprop").args(args).run()?;
+    if !output.success() {
+        return Err(BrowserInfoError::WindowNotFound);
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(target_os = "linux")]
+fn geometry(window_id: &str) -> Option<crate::WindowPosition> {
+    let stdout = ProcessRunner::new("xwininfo")
+        .args(["-id", window_id])
+        .run()
+        .ok()?
+        .stdout;
+    let field = |label: &str| -> Option<f64> {
+        stdout
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(label))
+            .and_then(|value| value.trim().parse().ok())
+    };
+
+    Some(crate::WindowPosition::new(
+        field("Absolute upper-left X:")?,
+        field("Absolute upper-left Y:")?,
+        field("Width:")?,
+        field("Height:")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BrowserType, ExtractionConfig, UrlConfidence, UrlSource};
+    use std::time::Duration;
+
+    fn chrome_window(title: &str) -> WindowHandleInfo {
+        WindowHandleInfo {
+            title: title.to_string(),
+            app_name: "Google Chrome".to_string(),
+            process_path: "/opt/google/chrome/chrome".into(),
+            window_id: "42".to_string(),
+            ..WindowHandleInfo::default()
+        }
+    }
+
+    // Linux にはネイティブ抽出がないので、タイトル推測まで確実に進む
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn extraction_reads_the_window_of_the_config_provider() {
+        let config = ExtractionConfig::new()
+            .allow_title_heuristics(true)
+            .window_provider(FixedWindow::new(chrome_window(
+                "rust-lang/rust - GitHub - Google Chrome",
+            )));
+
+        let info = crate::get_active_browser_info_with_config(&config).unwrap();
+        assert_eq!(info.browser_type, BrowserType::Chrome);
+        assert_eq!(info.title, "rust-lang/rust - GitHub - Google Chrome");
+        assert_eq!(info.url, "https://github.com");
+        assert_eq!(info.url_source, Some(UrlSource::TitleHeuristic));
+        assert_eq!(info.confidence, Some(UrlConfidence::Guessed));
+
+        let disabled = config.disable_browser(BrowserType::Chrome);
+        assert!(matches!(
+            crate::get_active_browser_info_with_config(&disabled),
+            Err(BrowserInfoError::NotABrowser)
+        ));
+    }
+
+    struct NoWindow;
+
+    impl WindowProvider for NoWindow {
+        fn active_window(&self) -> Result<WindowHandleInfo, BrowserInfoError> {
+            Err(BrowserInfoError::WindowNotFound)
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_and_async_report_a_missing_window_alike() {
+        let config = ExtractionConfig::new().window_provider(NoWindow);
+
+        assert!(matches!(
+            crate::get_active_browser_info_with_config(&config),
+            Err(BrowserInfoError::NotABrowser)
+        ));
+        assert!(matches!(
+            crate::get_browser_info_with_config(&config).await,
+            Err(BrowserInfoError::NotABrowser)
+        ));
+    }
+
+    #[test]
+    fn title_changes_follow_the_config_provider() {
+        let config =
+            ExtractionConfig::new().window_provider(FixedWindow::new(chrome_window("Inbox")));
+        let change = crate::watcher::watch_titles_with_config(Duration::ZERO, config)
+            .next()
+            .unwrap();
+        assert_eq!(change.title, "Inbox");
+        assert_eq!(change.browser_type, BrowserType::Chrome);
+        assert_eq!(change.window_id, "42");
+    }
+}